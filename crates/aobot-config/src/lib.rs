@@ -176,16 +176,18 @@ pub struct GlobalMemoryConfig {
     /// Whether the memory system is enabled.
     #[serde(default)]
     pub enabled: bool,
-    /// Embedding provider: "auto", "openai", "gemini", "voyage".
+    /// Embedding provider: "auto", "local" (offline), "openai", "gemini", "voyage",
+    /// "openai-compatible" (Ollama, vLLM, LM Studio, ...).
     #[serde(default = "default_memory_provider")]
     pub provider: String,
-    /// Directories to index for memory.
+    /// Directories (or single files) to index for memory. Each entry is a
+    /// path, or a table with `path` and optional `include`/`exclude` globs.
     #[serde(default)]
-    pub dirs: Vec<String>,
+    pub dirs: Vec<MemoryDirConfig>,
     /// Maximum tokens per chunk.
     #[serde(default = "default_chunk_size")]
     pub chunk_size: usize,
-    /// Overlap lines between chunks.
+    /// Overlap between consecutive chunks, in tokens.
     #[serde(default = "default_chunk_overlap")]
     pub chunk_overlap: usize,
     /// Whether to sync on startup.
@@ -194,6 +196,79 @@ pub struct GlobalMemoryConfig {
     /// OpenAI embedding config.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub openai: Option<EmbeddingProviderConfig>,
+    /// Gemini embedding config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini: Option<EmbeddingProviderConfig>,
+    /// Voyage embedding config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voyage: Option<EmbeddingProviderConfig>,
+    /// OpenAI-compatible embedding server config (`base_url` and `model` required).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai_compatible: Option<EmbeddingProviderConfig>,
+    /// Hybrid search ranking.
+    #[serde(default)]
+    pub search: MemorySearchConfig,
+    /// Indexing of gateway conversations into memory.
+    #[serde(default)]
+    pub sessions: SessionMemoryConfig,
+}
+
+/// A memory directory with optional glob filters.
+///
+/// Patterns are matched against paths relative to `path`. With no
+/// `include` patterns every file type memory can chunk is indexed;
+/// `exclude` patterns that match a directory skip it entirely.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "MemoryDirSpec")]
+pub struct MemoryDirConfig {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+impl From<&str> for MemoryDirConfig {
+    fn from(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Accepted TOML forms of a memory directory entry.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MemoryDirSpec {
+    Path(String),
+    Filtered {
+        path: String,
+        #[serde(default)]
+        include: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+    },
+}
+
+impl From<MemoryDirSpec> for MemoryDirConfig {
+    fn from(spec: MemoryDirSpec) -> Self {
+        match spec {
+            MemoryDirSpec::Path(path) => Self {
+                path,
+                ..Default::default()
+            },
+            MemoryDirSpec::Filtered {
+                path,
+                include,
+                exclude,
+            } => Self {
+                path,
+                include,
+                exclude,
+            },
+        }
+    }
 }
 
 fn default_memory_provider() -> String {
@@ -208,6 +283,99 @@ fn default_chunk_overlap() -> usize {
     50
 }
 
+/// Hybrid search ranking configuration (`[memory.search]`).
+///
+/// Vector and full-text rankings are merged with weighted reciprocal rank
+/// fusion: a chunk at rank `r` in a list contributes `weight / (rrf_k + r)`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySearchConfig {
+    /// RRF rank constant. Larger values flatten the gap between top ranks.
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f32,
+    /// Weight of the vector similarity ranking.
+    #[serde(default = "default_vector_weight")]
+    pub vector_weight: f32,
+    /// Weight of the full-text (BM25) ranking.
+    #[serde(default = "default_text_weight")]
+    pub text_weight: f32,
+    /// Diversify results with maximal marginal relevance (MMR).
+    #[serde(default)]
+    pub mmr: bool,
+    /// MMR trade-off between relevance (1.0) and diversity (0.0).
+    #[serde(default = "default_mmr_lambda")]
+    pub mmr_lambda: f32,
+    /// How queries are turned into full-text match expressions.
+    #[serde(default)]
+    pub text_mode: FtsQueryMode,
+}
+
+/// How memory full-text queries are interpreted.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FtsQueryMode {
+    /// Free text: every word is matched literally, any word may match.
+    #[default]
+    Plain,
+    /// Like `plain`, but words also match as prefixes ("deploy" matches "deployment").
+    Prefix,
+    /// Raw FTS5 query syntax (phrases, `AND`/`OR`/`NOT`, `NEAR`, prefixes).
+    Advanced,
+}
+
+impl Default for MemorySearchConfig {
+    fn default() -> Self {
+        Self {
+            rrf_k: default_rrf_k(),
+            vector_weight: default_vector_weight(),
+            text_weight: default_text_weight(),
+            mmr: false,
+            mmr_lambda: default_mmr_lambda(),
+            text_mode: FtsQueryMode::default(),
+        }
+    }
+}
+
+/// Indexing of gateway conversations into memory (`[memory.sessions]`).
+///
+/// Indexed conversations are stored with source `"session"` and are only
+/// returned by searches made on behalf of the same user.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionMemoryConfig {
+    /// Whether conversations are indexed.
+    #[serde(default)]
+    pub enabled: bool,
+    /// What gets indexed.
+    #[serde(default)]
+    pub mode: SessionMemoryMode,
+}
+
+/// What conversation content is written to memory.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionMemoryMode {
+    /// Each user message and reply, after every turn.
+    #[default]
+    Turns,
+    /// The compaction summary, whenever the session is compacted.
+    Summary,
+}
+
+fn default_rrf_k() -> f32 {
+    60.0
+}
+
+fn default_vector_weight() -> f32 {
+    0.7
+}
+
+fn default_text_weight() -> f32 {
+    0.3
+}
+
+fn default_mmr_lambda() -> f32 {
+    0.7
+}
+
 /// Configuration for a specific embedding provider.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EmbeddingProviderConfig {
@@ -217,6 +385,12 @@ pub struct EmbeddingProviderConfig {
     /// Model identifier.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    /// API base URL (e.g. "http://localhost:11434/v1" for Ollama).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Output vector dimensions, for models that support shortening.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<usize>,
 }
 
 // ──────────────────── Media Config ────────────────────
@@ -256,6 +430,54 @@ pub struct HooksConfig {
     /// Additional hook directories.
     #[serde(default)]
     pub dirs: Vec<String>,
+    /// Maximum number of hook processes running at once.
+    #[serde(default = "default_hook_concurrency")]
+    pub max_concurrent: usize,
+    /// Outbound webhooks that receive hook events.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// Maximum number of webhook deliveries waiting to be sent or retried.
+    /// The oldest are dropped beyond this.
+    #[serde(default = "default_webhook_queue_limit")]
+    pub webhook_queue_limit: usize,
+}
+
+fn default_hook_concurrency() -> usize {
+    4
+}
+
+fn default_webhook_queue_limit() -> usize {
+    1000
+}
+
+/// An outbound webhook (`[[hooks.webhooks]]`). Events are POSTed to `url`
+/// as JSON and retried with exponential backoff.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Webhook name, used in logs and delivery status.
+    pub name: String,
+    /// URL events are POSTed to.
+    pub url: String,
+    /// Event types to send, e.g. "tool_call_after" (all when empty).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// Secret for the HMAC-SHA256 request signature (unsigned when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Delivery attempts before an event is given up on.
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    /// Request timeout in milliseconds.
+    #[serde(default = "default_webhook_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    8
+}
+
+fn default_webhook_timeout_ms() -> u64 {
+    10_000
 }
 
 // ──────────────────── Skills Config ────────────────────
@@ -380,6 +602,14 @@ pub fn config_dir() -> Result<PathBuf, ConfigError> {
         .ok_or(ConfigError::NoDirFound)
 }
 
+/// Expand a leading `~/` in a configured directory to `$HOME`.
+pub fn expand_home(dir: &str) -> PathBuf {
+    match (dir.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(dir),
+    }
+}
+
 /// Resolve the config file path (~/.aobot/config.toml).
 pub fn config_file_path() -> Result<PathBuf, ConfigError> {
     Ok(config_dir()?.join("config.toml"))
@@ -431,6 +661,14 @@ pub fn save_config(config: &AoBotConfig) -> Result<(), ConfigError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_expand_home() {
+        assert_eq!(expand_home("/srv/notes"), PathBuf::from("/srv/notes"));
+        if let Ok(home) = std::env::var("HOME") {
+            assert_eq!(expand_home("~/notes"), PathBuf::from(home).join("notes"));
+        }
+    }
+
     #[test]
     fn test_default_config() {
         let config = AoBotConfig::default();
@@ -500,6 +738,29 @@ deny = ["bash"]
         assert_eq!(agent.tools.deny, vec!["bash"]);
     }
 
+    #[test]
+    fn test_toml_parse_with_webhooks() {
+        let toml_str = r#"
+[hooks]
+dirs = ["~/hooks"]
+
+[[hooks.webhooks]]
+name = "audit"
+url = "https://example.com/aobot"
+events = ["tool_call_after", "session_start"]
+secret = "s3cret"
+"#;
+        let config: AoBotConfig = toml::from_str(toml_str).unwrap();
+        let hooks = config.hooks.unwrap();
+        assert!(hooks.enabled);
+        assert_eq!(hooks.webhook_queue_limit, 1000);
+        let webhook = &hooks.webhooks[0];
+        assert_eq!(webhook.name, "audit");
+        assert_eq!(webhook.events, vec!["tool_call_after", "session_start"]);
+        assert_eq!(webhook.secret.as_deref(), Some("s3cret"));
+        assert_eq!(webhook.max_attempts, 8);
+    }
+
     #[test]
     fn test_toml_parse_with_memory_config() {
         let toml_str = r#"
@@ -513,8 +774,100 @@ chunk_size = 300
         let mem = config.memory.unwrap();
         assert!(mem.enabled);
         assert_eq!(mem.provider, "openai");
-        assert_eq!(mem.dirs, vec!["~/.aobot/MEMORY.md"]);
+        assert_eq!(mem.dirs, vec![MemoryDirConfig::from("~/.aobot/MEMORY.md")]);
         assert_eq!(mem.chunk_size, 300);
+        assert_eq!(mem.search.rrf_k, 60.0);
+        assert!(!mem.search.mmr);
+    }
+
+    #[test]
+    fn test_toml_parse_memory_dirs_with_filters() {
+        let toml_str = r#"
+[memory]
+enabled = true
+dirs = [
+    "~/notes",
+    { path = "~/src/app", include = ["*.rs", "*.py"], exclude = ["target"] },
+]
+"#;
+        let config: AoBotConfig = toml::from_str(toml_str).unwrap();
+        let dirs = config.memory.unwrap().dirs;
+        assert_eq!(dirs[0], MemoryDirConfig::from("~/notes"));
+        assert_eq!(dirs[1].path, "~/src/app");
+        assert_eq!(dirs[1].include, vec!["*.rs", "*.py"]);
+        assert_eq!(dirs[1].exclude, vec!["target"]);
+
+        // Serialized entries read back the same
+        let serialized = toml::to_string(&dirs[1]).unwrap();
+        let parsed: MemoryDirConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(parsed, dirs[1]);
+    }
+
+    #[test]
+    fn test_toml_parse_memory_search_config() {
+        let toml_str = r#"
+[memory]
+enabled = true
+
+[memory.search]
+vector_weight = 0.5
+text_weight = 0.5
+mmr = true
+text_mode = "prefix"
+"#;
+        let config: AoBotConfig = toml::from_str(toml_str).unwrap();
+        let search = config.memory.unwrap().search;
+        assert_eq!(search.vector_weight, 0.5);
+        assert_eq!(search.text_weight, 0.5);
+        assert!(search.mmr);
+        assert_eq!(search.mmr_lambda, 0.7);
+        assert_eq!(search.text_mode, FtsQueryMode::Prefix);
+    }
+
+    #[test]
+    fn test_toml_parse_session_memory_config() {
+        let toml_str = r#"
+[memory]
+enabled = true
+
+[memory.sessions]
+enabled = true
+mode = "summary"
+"#;
+        let config: AoBotConfig = toml::from_str(toml_str).unwrap();
+        let sessions = config.memory.unwrap().sessions;
+        assert!(sessions.enabled);
+        assert_eq!(sessions.mode, SessionMemoryMode::Summary);
+
+        let config: AoBotConfig = toml::from_str("[memory]\nenabled = true\n").unwrap();
+        let sessions = config.memory.unwrap().sessions;
+        assert!(!sessions.enabled);
+        assert_eq!(sessions.mode, SessionMemoryMode::Turns);
+    }
+
+    #[test]
+    fn test_toml_parse_embedding_provider_config() {
+        let toml_str = r#"
+[memory]
+enabled = true
+provider = "openai-compatible"
+
+[memory.openai_compatible]
+base_url = "http://localhost:11434/v1"
+model = "nomic-embed-text"
+dimensions = 768
+"#;
+        let config: AoBotConfig = toml::from_str(toml_str).unwrap();
+        let mem = config.memory.unwrap();
+        assert_eq!(mem.provider, "openai-compatible");
+        let compat = mem.openai_compatible.unwrap();
+        assert_eq!(
+            compat.base_url.as_deref(),
+            Some("http://localhost:11434/v1")
+        );
+        assert_eq!(compat.model.as_deref(), Some("nomic-embed-text"));
+        assert_eq!(compat.dimensions, Some(768));
+        assert!(compat.api_key_env.is_none());
     }
 
     #[test]
@@ -3,27 +3,87 @@
 //! Provides a cron-like scheduler for periodic tasks that can be managed
 //! by AI agents through the cron tool.
 
+pub mod schedule;
 pub mod scheduler;
 pub mod store;
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+pub use schedule::ScheduleKind;
+
+/// Channel recipient that receives a job's output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CronDelivery {
+    /// Channel instance ID (e.g. "my-telegram").
+    pub channel_id: String,
+    /// Recipient identifier on the external platform.
+    pub recipient_id: String,
+    /// Platform-specific routing metadata (e.g. Telegram `chat_id`).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+/// What to do with runs that came due while the gateway was down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// Drop missed runs and wait for the next scheduled time.
+    #[default]
+    Skip,
+    /// Run once on startup, however many runs were missed.
+    RunOnce,
+}
+
+/// What to do when a job comes due while its previous run is still in progress.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapPolicy {
+    /// Skip the new run.
+    #[default]
+    Skip,
+    /// Run once more as soon as the current run finishes.
+    Queue,
+    /// Start the new run alongside the current one.
+    Concurrent,
+}
+
 /// A scheduled cron job.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CronJob {
     /// Unique job ID.
     pub id: String,
-    /// Cron expression (e.g. "0 * * * *" for every hour).
+    /// How `schedule` is interpreted.
+    #[serde(default)]
+    pub kind: ScheduleKind,
+    /// Cron expression (e.g. "0 * * * *" for every hour), RFC3339 time for
+    /// one-shot jobs, or interval (e.g. "20m") for `every` jobs.
     pub schedule: String,
+    /// IANA timezone the schedule is evaluated in (UTC when unset).
+    #[serde(default)]
+    pub timezone: Option<String>,
     /// Task description to execute.
     pub task: String,
     /// Agent ID to run the task on.
     pub agent_id: String,
     /// Session key for the task.
     pub session_key: String,
+    /// Where to send the agent's response (not delivered when unset).
+    #[serde(default)]
+    pub delivery: Option<CronDelivery>,
     /// Whether this job is enabled.
     pub enabled: bool,
+    /// Delete a one-shot job after it fires instead of disabling it.
+    #[serde(default)]
+    pub delete_after_run: bool,
+    /// Handling of runs missed while the gateway was down.
+    #[serde(default)]
+    pub missed_runs: MissedRunPolicy,
+    /// Handling of runs that come due while a previous run is in progress.
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
     /// Last execution time.
     pub last_run: Option<DateTime<Utc>>,
     /// Next scheduled execution time.
@@ -31,3 +91,49 @@ pub struct CronJob {
     /// Creation time.
     pub created_at: DateTime<Utc>,
 }
+
+impl CronJob {
+    /// Compute the next fire time strictly after `after`.
+    pub fn next_run_after(&self, after: DateTime<Utc>) -> anyhow::Result<Option<DateTime<Utc>>> {
+        let schedule = schedule::Schedule::new(
+            self.kind,
+            &self.schedule,
+            self.timezone.as_deref(),
+            self.created_at,
+        )?;
+        Ok(schedule.next_after(after))
+    }
+}
+
+/// Outcome of a job run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CronRunStatus {
+    /// The run is in progress.
+    Running,
+    /// The agent finished the task.
+    Success,
+    /// The run failed or was interrupted.
+    Error,
+    /// The run was skipped by the job's overlap policy.
+    Skipped,
+}
+
+/// A single recorded execution of a cron job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronRun {
+    /// Run ID.
+    pub id: i64,
+    /// Job this run belongs to.
+    pub job_id: String,
+    /// When the run started.
+    pub started_at: DateTime<Utc>,
+    /// When the run finished (None while running).
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Run outcome.
+    pub status: CronRunStatus,
+    /// Error message for failed or skipped runs.
+    pub error: Option<String>,
+    /// Beginning of the agent's response.
+    pub response: Option<String>,
+}
@@ -0,0 +1,373 @@
+//! Schedule parsing and next-run computation.
+//!
+//! Three schedule kinds are supported:
+//! - `cron`: standard 5-field expressions (`minute hour day-of-month month day-of-week`),
+//!   6-field expressions with a leading seconds field, and macros (`@yearly`,
+//!   `@annually`, `@monthly`, `@weekly`, `@daily`, `@hourly`). Evaluated in the
+//!   job's IANA timezone (UTC by default).
+//! - `at`: a single RFC3339 time (e.g. `2025-01-01T09:00:00+01:00`).
+//! - `every`: a fixed interval such as `30m` or `1h30m`, counted from job creation.
+//!
+//! Relative one-shot input (`in 20m`) is resolved to an `at` time on creation,
+//! see [`ScheduleSpec`].
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+
+/// How a job's `schedule` string is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleKind {
+    /// Cron expression.
+    #[default]
+    Cron,
+    /// One-shot at an RFC3339 time.
+    At,
+    /// Fixed interval (e.g. `20m`).
+    Every,
+}
+
+/// A parsed schedule.
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    /// Cron expression bound to a timezone.
+    Cron { cron: Cron, tz: Tz },
+    /// Single fire time.
+    At(DateTime<Utc>),
+    /// Fixed interval counted from `anchor`.
+    Every {
+        interval: Duration,
+        anchor: DateTime<Utc>,
+    },
+}
+
+impl Schedule {
+    /// Parse a cron expression, optionally in an IANA timezone (e.g. "Europe/Berlin").
+    pub fn parse(expr: &str, timezone: Option<&str>) -> Result<Self> {
+        let tz = parse_timezone(timezone)?;
+        let cron = Cron::new(expr.trim())
+            .with_seconds_optional()
+            .parse()
+            .map_err(|e| anyhow!("Invalid cron expression '{expr}': {e}"))?;
+        Ok(Self::Cron { cron, tz })
+    }
+
+    /// Parse a schedule of the given kind.
+    ///
+    /// `anchor` is the start of `every` intervals (normally the job's creation time).
+    pub fn new(
+        kind: ScheduleKind,
+        expr: &str,
+        timezone: Option<&str>,
+        anchor: DateTime<Utc>,
+    ) -> Result<Self> {
+        match kind {
+            ScheduleKind::Cron => Self::parse(expr, timezone),
+            ScheduleKind::At => Ok(Self::At(parse_time(expr)?)),
+            ScheduleKind::Every => Ok(Self::Every {
+                interval: parse_duration(expr)?,
+                anchor,
+            }),
+        }
+    }
+
+    /// Compute the first fire time strictly after `after`. `None` if there
+    /// is none, or it is beyond the range of representable times.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Cron { cron, tz } => cron
+                .find_next_occurrence(&after.with_timezone(tz), false)
+                .ok()
+                .map(|t| t.with_timezone(&Utc)),
+            Self::At(at) => (*at > after).then_some(*at),
+            Self::Every { interval, anchor } => {
+                if after < *anchor {
+                    return anchor.checked_add_signed(*interval);
+                }
+                let step = interval.num_milliseconds();
+                let elapsed = (after - *anchor).num_milliseconds();
+                let periods = elapsed / step + 1;
+                let offset = Duration::try_milliseconds(periods.checked_mul(step)?)?;
+                anchor.checked_add_signed(offset)
+            }
+        }
+    }
+}
+
+/// Schedule as requested by a user, before it is stored on a job.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleSpec {
+    /// Cron expression.
+    Cron(String),
+    /// Absolute RFC3339 time.
+    At(String),
+    /// Duration from now (e.g. `20m`), stored as an `at` time.
+    In(String),
+    /// Fixed interval (e.g. `1h`).
+    Every(String),
+}
+
+impl ScheduleSpec {
+    /// Build a spec from optional `schedule`/`at`/`in`/`every` inputs.
+    /// Exactly one must be set.
+    pub fn from_params(
+        schedule: Option<&str>,
+        at: Option<&str>,
+        in_: Option<&str>,
+        every: Option<&str>,
+    ) -> Result<Self> {
+        let specs: Vec<Self> = [
+            schedule.map(|s| Self::Cron(s.to_string())),
+            at.map(|s| Self::At(s.to_string())),
+            in_.map(|s| Self::In(s.to_string())),
+            every.map(|s| Self::Every(s.to_string())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        match <[Self; 1]>::try_from(specs) {
+            Ok([spec]) => Ok(spec),
+            Err(specs) if specs.is_empty() => {
+                bail!("Missing schedule: set one of 'schedule', 'at', 'in' or 'every'")
+            }
+            Err(_) => bail!("Only one of 'schedule', 'at', 'in' or 'every' may be set"),
+        }
+    }
+
+    /// Validate the spec and convert it to the stored kind and schedule string.
+    ///
+    /// One-shot times must lie in the future.
+    pub fn resolve(
+        &self,
+        timezone: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(ScheduleKind, String)> {
+        match self {
+            Self::Cron(expr) => {
+                Schedule::parse(expr, timezone)?;
+                Ok((ScheduleKind::Cron, expr.trim().to_string()))
+            }
+            Self::At(time) => {
+                let at = parse_time(time)?;
+                if at <= now {
+                    bail!("Time '{time}' is in the past");
+                }
+                Ok((
+                    ScheduleKind::At,
+                    at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                ))
+            }
+            Self::In(duration) => {
+                let at = now
+                    .checked_add_signed(parse_duration(duration)?)
+                    .ok_or_else(|| anyhow!("Duration '{duration}' is too far in the future"))?;
+                Ok((
+                    ScheduleKind::At,
+                    at.to_rfc3339_opts(SecondsFormat::Secs, true),
+                ))
+            }
+            Self::Every(interval) => {
+                if now.checked_add_signed(parse_duration(interval)?).is_none() {
+                    bail!("Interval '{interval}' is too far in the future");
+                }
+                Ok((ScheduleKind::Every, interval.trim().to_string()))
+            }
+        }
+    }
+}
+
+/// Parse an RFC3339 time (e.g. `2025-01-01T09:00:00Z`).
+pub fn parse_time(time: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time.trim())
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| {
+            anyhow!("Invalid time '{time}' (expected RFC3339, e.g. 2025-01-01T09:00:00Z): {e}")
+        })
+}
+
+/// Parse a duration like `90s`, `20m`, `1h30m`, `2d` or `1w`.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let invalid = || anyhow!("Invalid duration '{input}' (expected e.g. '20m', '1h30m', '2d')");
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let value: i64 = digits.parse().map_err(|_| invalid())?;
+        digits.clear();
+        let unit = match c {
+            's' => Duration::try_seconds(value),
+            'm' => Duration::try_minutes(value),
+            'h' => Duration::try_hours(value),
+            'd' => Duration::try_days(value),
+            'w' => Duration::try_weeks(value),
+            _ => None,
+        };
+        total = unit
+            .and_then(|unit| total.checked_add(&unit))
+            .ok_or_else(invalid)?;
+    }
+    if !digits.is_empty() || total <= Duration::zero() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Parse an optional IANA timezone name, defaulting to UTC.
+pub fn parse_timezone(timezone: Option<&str>) -> Result<Tz> {
+    match timezone {
+        Some(name) => name.parse::<Tz>().map_err(|_| {
+            anyhow!("Unknown timezone '{name}' (expected an IANA name like 'Europe/Berlin')")
+        }),
+        None => Ok(Tz::UTC),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap()
+    }
+
+    #[test]
+    fn test_five_field() {
+        let s = Schedule::parse("30 9 * * *", None).unwrap();
+        let next = s.next_after(utc(2025, 1, 1, 10, 0, 0)).unwrap();
+        assert_eq!(next, utc(2025, 1, 2, 9, 30, 0));
+    }
+
+    #[test]
+    fn test_next_is_strictly_after() {
+        let s = Schedule::parse("0 * * * *", None).unwrap();
+        let next = s.next_after(utc(2025, 1, 1, 10, 0, 0)).unwrap();
+        assert_eq!(next, utc(2025, 1, 1, 11, 0, 0));
+    }
+
+    #[test]
+    fn test_seconds_field() {
+        let s = Schedule::parse("*/15 * * * * *", None).unwrap();
+        let next = s.next_after(utc(2025, 1, 1, 10, 0, 5)).unwrap();
+        assert_eq!(next, utc(2025, 1, 1, 10, 0, 15));
+    }
+
+    #[test]
+    fn test_macros() {
+        let s = Schedule::parse("@hourly", None).unwrap();
+        let next = s.next_after(utc(2025, 1, 1, 10, 20, 0)).unwrap();
+        assert_eq!(next, utc(2025, 1, 1, 11, 0, 0));
+
+        let s = Schedule::parse("@daily", None).unwrap();
+        let next = s.next_after(utc(2025, 1, 1, 10, 20, 0)).unwrap();
+        assert_eq!(next, utc(2025, 1, 2, 0, 0, 0));
+    }
+
+    #[test]
+    fn test_timezone() {
+        // 09:00 in Berlin is 08:00 UTC in winter
+        let s = Schedule::parse("0 9 * * *", Some("Europe/Berlin")).unwrap();
+        let next = s.next_after(utc(2025, 1, 1, 0, 0, 0)).unwrap();
+        assert_eq!(next, utc(2025, 1, 1, 8, 0, 0));
+    }
+
+    #[test]
+    fn test_invalid_expression() {
+        let err = Schedule::parse("61 * * * *", None).unwrap_err();
+        assert!(err.to_string().contains("Invalid cron expression"));
+        assert!(Schedule::parse("every day", None).is_err());
+    }
+
+    #[test]
+    fn test_at() {
+        let s = Schedule::new(
+            ScheduleKind::At,
+            "2025-01-01T09:00:00+01:00",
+            None,
+            Utc::now(),
+        )
+        .unwrap();
+        assert_eq!(
+            s.next_after(utc(2025, 1, 1, 0, 0, 0)),
+            Some(utc(2025, 1, 1, 8, 0, 0))
+        );
+        assert_eq!(s.next_after(utc(2025, 1, 1, 8, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_every() {
+        let anchor = utc(2025, 1, 1, 10, 0, 0);
+        let s = Schedule::new(ScheduleKind::Every, "20m", None, anchor).unwrap();
+        assert_eq!(s.next_after(anchor), Some(utc(2025, 1, 1, 10, 20, 0)));
+        assert_eq!(
+            s.next_after(utc(2025, 1, 1, 10, 50, 0)),
+            Some(utc(2025, 1, 1, 11, 0, 0))
+        );
+        assert_eq!(
+            s.next_after(utc(2025, 1, 1, 11, 0, 0)),
+            Some(utc(2025, 1, 1, 11, 20, 0))
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::seconds(90));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("2d").unwrap(), Duration::days(2));
+        assert!(parse_duration("20").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("soon").is_err());
+        // Out of range, as a single unit or as a sum
+        assert!(parse_duration("99999999999999w").is_err());
+        assert!(parse_duration("10000000000w10000000000w").is_err());
+    }
+
+    #[test]
+    fn test_far_future_is_rejected() {
+        let now = utc(2025, 1, 1, 10, 0, 0);
+        for (in_, every) in [
+            (Some("1000000000w"), None),
+            (Some("10000000000w10000000000w"), None),
+            (None, Some("1000000000w")),
+        ] {
+            let spec = ScheduleSpec::from_params(None, None, in_, every).unwrap();
+            assert!(spec.resolve(None, now).is_err(), "{spec:?}");
+        }
+
+        // Stored schedules that can't fire again have no next run
+        let s = Schedule::new(ScheduleKind::Every, "1000000000w", None, now).unwrap();
+        assert_eq!(s.next_after(now), None);
+        assert_eq!(s.next_after(now - Duration::days(1)), None);
+    }
+
+    #[test]
+    fn test_spec_resolve() {
+        let now = utc(2025, 1, 1, 10, 0, 0);
+
+        let spec = ScheduleSpec::from_params(None, None, Some("20m"), None).unwrap();
+        let (kind, schedule) = spec.resolve(None, now).unwrap();
+        assert_eq!(kind, ScheduleKind::At);
+        assert_eq!(parse_time(&schedule).unwrap(), utc(2025, 1, 1, 10, 20, 0));
+
+        let spec = ScheduleSpec::At("2024-12-31T00:00:00Z".into());
+        assert!(spec.resolve(None, now).is_err());
+
+        let spec = ScheduleSpec::from_params(Some("@daily"), None, None, None).unwrap();
+        assert_eq!(spec.resolve(None, now).unwrap().0, ScheduleKind::Cron);
+
+        assert!(ScheduleSpec::from_params(None, None, None, None).is_err());
+        assert!(ScheduleSpec::from_params(Some("@daily"), None, None, Some("1h")).is_err());
+    }
+
+    #[test]
+    fn test_invalid_timezone() {
+        let err = Schedule::parse("0 * * * *", Some("Mars/Olympus")).unwrap_err();
+        assert!(err.to_string().contains("Unknown timezone"));
+    }
+}
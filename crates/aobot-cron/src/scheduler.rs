@@ -1,16 +1,53 @@
 //! Cron job scheduler — evaluates cron expressions and triggers execution.
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, Notify, RwLock};
 use tracing::{info, warn};
 
-use crate::CronJob;
 use crate::store::CronStore;
+use crate::{CronJob, CronRun, CronRunStatus, MissedRunPolicy, OverlapPolicy, ScheduleKind};
+
+/// Upper bound on how long the scheduler sleeps between checks.
+const MAX_SLEEP: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Number of response characters kept in run history.
+const RESPONSE_EXCERPT_CHARS: usize = 500;
+
+/// A job run handed to the executor.
+///
+/// The executor must report the outcome through `CronManager::finish_run`.
+#[derive(Debug, Clone)]
+pub struct DispatchedRun {
+    /// ID of the run record in the store.
+    pub run_id: i64,
+    /// The job to execute.
+    pub job: CronJob,
+}
+
+/// In-flight runs of a single job.
+#[derive(Debug, Default)]
+struct RunState {
+    active: usize,
+    /// Another run is waiting for the active ones to finish (`OverlapPolicy::Queue`).
+    queued: bool,
+}
+
+/// Scheduler decision for a due job.
+enum Admission {
+    Start,
+    Skip,
+    Queued,
+}
 
 /// Manages cron job scheduling and execution.
 pub struct CronManager {
     store: Arc<CronStore>,
     jobs: RwLock<Vec<CronJob>>,
+    /// Wakes the scheduler loop when jobs change.
+    changed: Notify,
+    /// In-flight runs by job ID.
+    running: Mutex<HashMap<String, RunState>>,
 }
 
 impl CronManager {
@@ -19,22 +56,73 @@ impl CronManager {
         Self {
             store,
             jobs: RwLock::new(Vec::new()),
+            changed: Notify::new(),
+            running: Mutex::new(HashMap::new()),
         }
     }
 
-    /// Load jobs from storage.
+    /// Load jobs from storage and compute their next run times.
+    ///
+    /// Runs missed while the gateway was down are handled per job
+    /// according to its `MissedRunPolicy`.
     pub async fn load(&self) -> anyhow::Result<()> {
-        let jobs = self.store.list_jobs()?;
+        let interrupted = self
+            .store
+            .fail_running_runs("Interrupted by gateway shutdown")?;
+        if interrupted > 0 {
+            warn!("Marked {interrupted} interrupted cron runs as failed");
+        }
+
+        let mut jobs = self.store.list_jobs()?;
+        let now = chrono::Utc::now();
+        for job in &mut jobs {
+            if !job.enabled {
+                continue;
+            }
+            let missed = job.next_run.is_some_and(|next| next <= now);
+            if missed && job.missed_runs == MissedRunPolicy::RunOnce {
+                // Leave the past next_run in place so the job fires once right away
+                info!(job_id = %job.id, "Catching up missed cron run");
+                continue;
+            }
+            match job.next_run_after(now) {
+                Ok(next) => {
+                    job.next_run = next;
+                    if next.is_none() && job.kind == ScheduleKind::At {
+                        // A missed one-shot job never becomes due again
+                        info!(job_id = %job.id, "Disabling missed one-shot cron job");
+                        job.enabled = false;
+                    }
+                    if let Err(e) = self.store.upsert_job(job) {
+                        warn!("Failed to persist next run for cron job {}: {e}", job.id);
+                    }
+                }
+                Err(e) => {
+                    warn!("Cron job {} has an invalid schedule: {e}", job.id);
+                    job.next_run = None;
+                }
+            }
+        }
         info!("Loaded {} cron jobs", jobs.len());
         *self.jobs.write().await = jobs;
+        self.changed.notify_one();
         Ok(())
     }
 
     /// Add a new cron job.
-    pub async fn add_job(&self, job: CronJob) -> anyhow::Result<()> {
+    ///
+    /// Computes the job's first run time and returns the stored job;
+    /// fails if the schedule is invalid.
+    pub async fn add_job(&self, mut job: CronJob) -> anyhow::Result<CronJob> {
+        job.next_run = if job.enabled {
+            job.next_run_after(chrono::Utc::now())?
+        } else {
+            None
+        };
         self.store.upsert_job(&job)?;
-        self.jobs.write().await.push(job);
-        Ok(())
+        self.jobs.write().await.push(job.clone());
+        self.changed.notify_one();
+        Ok(job)
     }
 
     /// Remove a cron job.
@@ -42,6 +130,7 @@ impl CronManager {
         let removed = self.store.delete_job(id)?;
         if removed {
             self.jobs.write().await.retain(|j| j.id != id);
+            self.changed.notify_one();
         }
         Ok(removed)
     }
@@ -51,12 +140,26 @@ impl CronManager {
         self.jobs.read().await.clone()
     }
 
+    /// Get a job by ID.
+    pub async fn get_job(&self, id: &str) -> Option<CronJob> {
+        self.jobs.read().await.iter().find(|j| j.id == id).cloned()
+    }
+
     /// Update a job's enabled status.
+    ///
+    /// Re-enabling a job schedules it from now; disabling clears its next run.
     pub async fn set_enabled(&self, id: &str, enabled: bool) -> anyhow::Result<bool> {
         let mut jobs = self.jobs.write().await;
         if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+            let next_run = if enabled {
+                job.next_run_after(chrono::Utc::now())?
+            } else {
+                None
+            };
             job.enabled = enabled;
+            job.next_run = next_run;
             self.store.upsert_job(job)?;
+            self.changed.notify_one();
             Ok(true)
         } else {
             Ok(false)
@@ -73,37 +176,358 @@ impl CronManager {
             .collect()
     }
 
-    /// Mark a job as having run and compute next run time.
+    /// Mark a job as having been dispatched and compute next run time.
+    ///
+    /// One-shot (`at`) jobs are disabled. Those with `delete_after_run`
+    /// are deleted by [`finish_run`](Self::finish_run) once their run is
+    /// recorded, so a crash mid-run leaves the job and a failed run behind.
     pub async fn mark_ran(&self, id: &str) -> anyhow::Result<()> {
         let mut jobs = self.jobs.write().await;
         if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
-            job.last_run = Some(chrono::Utc::now());
-            // Simple next-run computation: parse cron expression would go here.
-            // For now, we just clear next_run and let the scheduler recompute.
-            job.next_run = None;
+            let now = chrono::Utc::now();
+            job.last_run = Some(now);
+            if job.kind == ScheduleKind::At {
+                job.enabled = false;
+                job.next_run = None;
+                self.store.upsert_job(job)?;
+                return Ok(());
+            }
+            // Clear next_run on failure so a broken schedule can't re-fire in a loop.
+            job.next_run = job.next_run_after(now).unwrap_or_else(|e| {
+                warn!("Cron job {id} has an invalid schedule: {e}");
+                None
+            });
             self.store.upsert_job(job)?;
         }
         Ok(())
     }
 
+    /// Record the start of a run and count it as in flight.
+    pub async fn start_run(&self, job: &CronJob) -> anyhow::Result<DispatchedRun> {
+        let run_id =
+            self.store
+                .insert_run(&job.id, chrono::Utc::now(), CronRunStatus::Running, None)?;
+        self.running
+            .lock()
+            .await
+            .entry(job.id.clone())
+            .or_default()
+            .active += 1;
+        Ok(DispatchedRun {
+            run_id,
+            job: job.clone(),
+        })
+    }
+
+    /// Record the outcome of a run.
+    ///
+    /// One-shot jobs with `delete_after_run` are deleted once their last
+    /// in-flight run is recorded; the run history is kept. Returns a new
+    /// run to execute when one was queued behind this one.
+    pub async fn finish_run(
+        &self,
+        run: &DispatchedRun,
+        result: &Result<String, String>,
+    ) -> anyhow::Result<Option<DispatchedRun>> {
+        let (idle, queued) = {
+            let mut running = self.running.lock().await;
+            let state = running.entry(run.job.id.clone()).or_default();
+            state.active = state.active.saturating_sub(1);
+            let idle = state.active == 0;
+            let queued = idle && state.queued;
+            if idle {
+                running.remove(&run.job.id);
+            }
+            (idle, queued)
+        };
+
+        let (status, error, response) = match result {
+            Ok(text) => (
+                CronRunStatus::Success,
+                None,
+                Some(
+                    text.chars()
+                        .take(RESPONSE_EXCERPT_CHARS)
+                        .collect::<String>(),
+                ),
+            ),
+            Err(e) => (CronRunStatus::Error, Some(e.as_str()), None),
+        };
+        if let Err(e) = self
+            .store
+            .finish_run(run.run_id, status, error, response.as_deref())
+        {
+            warn!("Failed to record cron run {}: {e}", run.run_id);
+        }
+
+        if idle && run.job.kind == ScheduleKind::At && run.job.delete_after_run {
+            self.store.delete_job_keep_runs(&run.job.id)?;
+            self.jobs.write().await.retain(|j| j.id != run.job.id);
+            info!(job_id = %run.job.id, "Deleted one-shot cron job");
+            return Ok(None);
+        }
+
+        if !queued {
+            return Ok(None);
+        }
+        match self.get_job(&run.job.id).await {
+            Some(job) if job.enabled => Ok(Some(self.start_run(&job).await?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// List recent runs, newest first, optionally for a single job.
+    pub fn list_runs(&self, job_id: Option<&str>, limit: usize) -> anyhow::Result<Vec<CronRun>> {
+        self.store.list_runs(job_id, limit)
+    }
+
+    /// Decide whether a due job may start, given its in-flight runs.
+    async fn admit(&self, job: &CronJob) -> Admission {
+        let mut running = self.running.lock().await;
+        match running.get_mut(&job.id) {
+            Some(state) if state.active > 0 => match job.overlap {
+                OverlapPolicy::Skip => Admission::Skip,
+                OverlapPolicy::Queue => {
+                    state.queued = true;
+                    Admission::Queued
+                }
+                OverlapPolicy::Concurrent => Admission::Start,
+            },
+            _ => Admission::Start,
+        }
+    }
+
+    /// How long to sleep until the next enabled job is due, capped at `MAX_SLEEP`.
+    async fn time_until_next(&self) -> std::time::Duration {
+        let now = chrono::Utc::now();
+        self.jobs
+            .read()
+            .await
+            .iter()
+            .filter(|j| j.enabled)
+            .filter_map(|j| j.next_run)
+            .min()
+            .map(|next| (next - now).to_std().unwrap_or_default())
+            .unwrap_or(MAX_SLEEP)
+            .min(MAX_SLEEP)
+    }
+
     /// Start the scheduler loop (runs in background).
     pub async fn run_scheduler(
         self: Arc<Self>,
-        task_sender: tokio::sync::mpsc::UnboundedSender<CronJob>,
+        task_sender: tokio::sync::mpsc::UnboundedSender<DispatchedRun>,
     ) {
         info!("Cron scheduler started");
         loop {
             let due_jobs = self.get_due_jobs().await;
             for job in due_jobs {
-                info!(job_id = %job.id, task = %job.task, "Executing cron job");
-                if let Err(e) = task_sender.send(job.clone()) {
-                    warn!("Failed to dispatch cron job {}: {e}", job.id);
+                match self.admit(&job).await {
+                    Admission::Start => match self.start_run(&job).await {
+                        Ok(run) => {
+                            info!(job_id = %job.id, task = %job.task, "Executing cron job");
+                            if let Err(e) = task_sender.send(run) {
+                                warn!("Failed to dispatch cron job {}: {e}", job.id);
+                            }
+                        }
+                        Err(e) => warn!("Failed to start cron job {}: {e}", job.id),
+                    },
+                    Admission::Skip => {
+                        info!(job_id = %job.id, "Previous run still in progress, skipping");
+                        if let Err(e) = self.store.insert_run(
+                            &job.id,
+                            chrono::Utc::now(),
+                            CronRunStatus::Skipped,
+                            Some("Previous run still in progress"),
+                        ) {
+                            warn!("Failed to record skipped cron run {}: {e}", job.id);
+                        }
+                    }
+                    Admission::Queued => {
+                        info!(job_id = %job.id, "Previous run still in progress, queued");
+                    }
                 }
                 if let Err(e) = self.mark_ran(&job.id).await {
                     warn!("Failed to mark cron job {} as ran: {e}", job.id);
                 }
             }
-            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+
+            let sleep = self.time_until_next().await;
+            tokio::select! {
+                _ = tokio::time::sleep(sleep) => {}
+                _ = self.changed.notified() => {}
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_job(id: &str, overlap: OverlapPolicy) -> CronJob {
+        CronJob {
+            id: id.into(),
+            kind: ScheduleKind::Cron,
+            schedule: "0 * * * *".into(),
+            timezone: None,
+            task: "Check the inbox".into(),
+            agent_id: "default".into(),
+            session_key: format!("cron:default:{id}"),
+            delivery: None,
+            enabled: true,
+            delete_after_run: false,
+            missed_runs: MissedRunPolicy::Skip,
+            overlap,
+            last_run: None,
+            next_run: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn test_manager() -> (Arc<CronStore>, CronManager) {
+        let store = Arc::new(CronStore::open_in_memory().unwrap());
+        (store.clone(), CronManager::new(store))
+    }
+
+    #[tokio::test]
+    async fn test_run_history() {
+        let (_, manager) = test_manager();
+        let job = manager
+            .add_job(test_job("job-1", OverlapPolicy::Skip))
+            .await
+            .unwrap();
+
+        let run = manager.start_run(&job).await.unwrap();
+        let runs = manager.list_runs(Some("job-1"), 10).unwrap();
+        assert_eq!(runs[0].status, CronRunStatus::Running);
+
+        let next = manager
+            .finish_run(&run, &Ok("All clear".into()))
+            .await
+            .unwrap();
+        assert!(next.is_none());
+        let runs = manager.list_runs(Some("job-1"), 10).unwrap();
+        assert_eq!(runs[0].status, CronRunStatus::Success);
+        assert_eq!(runs[0].response.as_deref(), Some("All clear"));
+        assert!(runs[0].finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_overlap_policies() {
+        let (_, manager) = test_manager();
+        let skip = manager
+            .add_job(test_job("skip", OverlapPolicy::Skip))
+            .await
+            .unwrap();
+        let queue = manager
+            .add_job(test_job("queue", OverlapPolicy::Queue))
+            .await
+            .unwrap();
+        let concurrent = manager
+            .add_job(test_job("concurrent", OverlapPolicy::Concurrent))
+            .await
+            .unwrap();
+
+        let skip_run = manager.start_run(&skip).await.unwrap();
+        let queue_run = manager.start_run(&queue).await.unwrap();
+        manager.start_run(&concurrent).await.unwrap();
+
+        assert!(matches!(manager.admit(&skip).await, Admission::Skip));
+        assert!(matches!(manager.admit(&queue).await, Admission::Queued));
+        assert!(matches!(manager.admit(&concurrent).await, Admission::Start));
+
+        // Finishing the queued job's run starts the queued one
+        let next = manager
+            .finish_run(&queue_run, &Err("timeout".into()))
+            .await
+            .unwrap();
+        assert_eq!(next.unwrap().job.id, "queue");
+        let next = manager
+            .finish_run(&skip_run, &Ok(String::new()))
+            .await
+            .unwrap();
+        assert!(next.is_none());
+        assert!(matches!(manager.admit(&skip).await, Admission::Start));
+    }
+
+    #[tokio::test]
+    async fn test_missed_run_policies() {
+        let (store, manager) = test_manager();
+        let past = chrono::Utc::now() - chrono::Duration::hours(3);
+
+        let mut skip = test_job("skip", OverlapPolicy::Skip);
+        skip.next_run = Some(past);
+        store.upsert_job(&skip).unwrap();
+        let mut run_once = test_job("run-once", OverlapPolicy::Skip);
+        run_once.missed_runs = MissedRunPolicy::RunOnce;
+        run_once.next_run = Some(past);
+        store.upsert_job(&run_once).unwrap();
+        let mut missed_at = test_job("missed-at", OverlapPolicy::Skip);
+        missed_at.kind = ScheduleKind::At;
+        missed_at.schedule = past.to_rfc3339();
+        missed_at.next_run = Some(past);
+        store.upsert_job(&missed_at).unwrap();
+        // A run left behind by a crash
+        store
+            .insert_run("skip", past, CronRunStatus::Running, None)
+            .unwrap();
+
+        manager.load().await.unwrap();
+        let due: Vec<String> = manager
+            .get_due_jobs()
+            .await
+            .into_iter()
+            .map(|j| j.id)
+            .collect();
+        assert_eq!(due, vec!["run-once".to_string()]);
+
+        let runs = manager.list_runs(Some("skip"), 10).unwrap();
+        assert_eq!(runs[0].status, CronRunStatus::Error);
+
+        // A skipped one-shot job is disabled rather than left pending
+        let missed_at = store.get_job("missed-at").unwrap().unwrap();
+        assert!(!missed_at.enabled);
+        assert!(missed_at.next_run.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_one_shot_jobs() {
+        let (store, manager) = test_manager();
+        let at = (chrono::Utc::now() + chrono::Duration::minutes(5)).to_rfc3339();
+
+        let mut keep = test_job("keep", OverlapPolicy::Skip);
+        keep.kind = ScheduleKind::At;
+        keep.schedule = at.clone();
+        let keep = manager.add_job(keep).await.unwrap();
+        assert!(keep.next_run.is_some());
+
+        let mut delete = test_job("delete", OverlapPolicy::Skip);
+        delete.kind = ScheduleKind::At;
+        delete.schedule = at;
+        delete.delete_after_run = true;
+        manager.add_job(delete).await.unwrap();
+
+        manager.mark_ran("keep").await.unwrap();
+        let delete = manager.get_job("delete").await.unwrap();
+        let run = manager.start_run(&delete).await.unwrap();
+        manager.mark_ran("delete").await.unwrap();
+
+        let keep = manager.get_job("keep").await.unwrap();
+        assert!(!keep.enabled);
+        assert!(keep.next_run.is_none());
+        assert!(keep.last_run.is_some());
+
+        // Deleted only once the run is recorded, keeping its history
+        let delete = store.get_job("delete").unwrap().unwrap();
+        assert!(!delete.enabled);
+        let next = manager
+            .finish_run(&run, &Ok("Reminder sent".into()))
+            .await
+            .unwrap();
+        assert!(next.is_none());
+        assert!(manager.get_job("delete").await.is_none());
+        assert!(store.get_job("delete").unwrap().is_none());
+        let runs = manager.list_runs(Some("delete"), 10).unwrap();
+        assert_eq!(runs[0].status, CronRunStatus::Success);
+    }
+}
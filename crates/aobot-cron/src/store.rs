@@ -1,11 +1,25 @@
 //! SQLite-backed cron job storage.
 
 use anyhow::Result;
-use rusqlite::Connection;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, Row};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use std::path::Path;
 use std::sync::Mutex;
 
-use crate::CronJob;
+use crate::schedule::Schedule;
+use crate::{CronJob, CronRun, CronRunStatus};
+
+/// Columns selected for a `CronJob`, in `row_to_job` order.
+const JOB_COLUMNS: &str = "id, schedule, task, agent_id, session_key, enabled, last_run, next_run, \
+     created_at, timezone, delivery, missed_runs, overlap, kind, delete_after_run";
+
+/// Columns selected for a `CronRun`, in `row_to_run` order.
+const RUN_COLUMNS: &str = "id, job_id, started_at, finished_at, status, error, response";
+
+/// Run history kept per job; older runs are pruned.
+const MAX_RUNS_PER_JOB: i64 = 100;
 
 /// Persistent storage for cron jobs.
 pub struct CronStore {
@@ -16,11 +30,18 @@ impl CronStore {
     /// Open or create a cron store.
     pub fn open(db_path: &Path) -> Result<Self> {
         let conn = Connection::open(db_path)?;
+        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        Self::init(conn)
+    }
 
-        conn.execute_batch(
-            "PRAGMA journal_mode = WAL;
+    /// Open an in-memory cron store (for testing).
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
 
-             CREATE TABLE IF NOT EXISTS cron_jobs (
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cron_jobs (
                  id TEXT PRIMARY KEY,
                  schedule TEXT NOT NULL,
                  task TEXT NOT NULL,
@@ -33,6 +54,29 @@ impl CronStore {
              );",
         )?;
 
+        // Migrations: add columns (ignore error if already exists)
+        let _ = conn.execute_batch("ALTER TABLE cron_jobs ADD COLUMN timezone TEXT;");
+        let _ = conn.execute_batch("ALTER TABLE cron_jobs ADD COLUMN delivery TEXT;");
+        let _ = conn.execute_batch("ALTER TABLE cron_jobs ADD COLUMN missed_runs TEXT;");
+        let _ = conn.execute_batch("ALTER TABLE cron_jobs ADD COLUMN overlap TEXT;");
+        let _ = conn.execute_batch("ALTER TABLE cron_jobs ADD COLUMN kind TEXT;");
+        let _ = conn.execute_batch(
+            "ALTER TABLE cron_jobs ADD COLUMN delete_after_run INTEGER NOT NULL DEFAULT 0;",
+        );
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cron_runs (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 job_id TEXT NOT NULL,
+                 started_at TEXT NOT NULL,
+                 finished_at TEXT,
+                 status TEXT NOT NULL,
+                 error TEXT,
+                 response TEXT
+             );
+             CREATE INDEX IF NOT EXISTS idx_cron_runs_job ON cron_runs(job_id, id);",
+        )?;
+
         Ok(Self {
             conn: Mutex::new(conn),
         })
@@ -41,40 +85,30 @@ impl CronStore {
     /// List all cron jobs.
     pub fn list_jobs(&self) -> Result<Vec<CronJob>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, schedule, task, agent_id, session_key, enabled, last_run, next_run, created_at FROM cron_jobs",
-        )?;
+        let mut stmt = conn.prepare(&format!("SELECT {JOB_COLUMNS} FROM cron_jobs"))?;
         let jobs = stmt
-            .query_map([], |row| {
-                Ok(CronJob {
-                    id: row.get(0)?,
-                    schedule: row.get(1)?,
-                    task: row.get(2)?,
-                    agent_id: row.get(3)?,
-                    session_key: row.get(4)?,
-                    enabled: row.get::<_, i64>(5)? != 0,
-                    last_run: row
-                        .get::<_, Option<String>>(6)?
-                        .and_then(|s| s.parse().ok()),
-                    next_run: row
-                        .get::<_, Option<String>>(7)?
-                        .and_then(|s| s.parse().ok()),
-                    created_at: row
-                        .get::<_, String>(8)?
-                        .parse()
-                        .unwrap_or_else(|_| chrono::Utc::now()),
-                })
-            })?
+            .query_map([], row_to_job)?
             .collect::<Result<Vec<_>, _>>()?;
         Ok(jobs)
     }
 
     /// Insert or update a cron job.
+    ///
+    /// Fails if the job's schedule or timezone is invalid.
     pub fn upsert_job(&self, job: &CronJob) -> Result<()> {
+        Schedule::new(
+            job.kind,
+            &job.schedule,
+            job.timezone.as_deref(),
+            job.created_at,
+        )?;
+
         let conn = self.conn.lock().unwrap();
         conn.execute(
-            "INSERT OR REPLACE INTO cron_jobs (id, schedule, task, agent_id, session_key, enabled, last_run, next_run, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            &format!(
+                "INSERT OR REPLACE INTO cron_jobs ({JOB_COLUMNS})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"
+            ),
             rusqlite::params![
                 job.id,
                 job.schedule,
@@ -85,13 +119,33 @@ impl CronStore {
                 job.last_run.map(|t| t.to_rfc3339()),
                 job.next_run.map(|t| t.to_rfc3339()),
                 job.created_at.to_rfc3339(),
+                job.timezone,
+                job.delivery
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?,
+                enum_to_sql(&job.missed_runs),
+                enum_to_sql(&job.overlap),
+                enum_to_sql(&job.kind),
+                job.delete_after_run as i64,
             ],
         )?;
         Ok(())
     }
 
-    /// Delete a cron job.
+    /// Delete a cron job and its run history.
     pub fn delete_job(&self, id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let count = conn.execute("DELETE FROM cron_jobs WHERE id = ?1", rusqlite::params![id])?;
+        conn.execute(
+            "DELETE FROM cron_runs WHERE job_id = ?1",
+            rusqlite::params![id],
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Delete a cron job but keep its run history.
+    pub fn delete_job_keep_runs(&self, id: &str) -> Result<bool> {
         let conn = self.conn.lock().unwrap();
         let count = conn.execute("DELETE FROM cron_jobs WHERE id = ?1", rusqlite::params![id])?;
         Ok(count > 0)
@@ -100,33 +154,238 @@ impl CronStore {
     /// Get a cron job by ID.
     pub fn get_job(&self, id: &str) -> Result<Option<CronJob>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, schedule, task, agent_id, session_key, enabled, last_run, next_run, created_at FROM cron_jobs WHERE id = ?1",
-        )?;
-        let result = stmt.query_row(rusqlite::params![id], |row| {
-            Ok(CronJob {
-                id: row.get(0)?,
-                schedule: row.get(1)?,
-                task: row.get(2)?,
-                agent_id: row.get(3)?,
-                session_key: row.get(4)?,
-                enabled: row.get::<_, i64>(5)? != 0,
-                last_run: row
-                    .get::<_, Option<String>>(6)?
-                    .and_then(|s| s.parse().ok()),
-                next_run: row
-                    .get::<_, Option<String>>(7)?
-                    .and_then(|s| s.parse().ok()),
-                created_at: row
-                    .get::<_, String>(8)?
-                    .parse()
-                    .unwrap_or_else(|_| chrono::Utc::now()),
-            })
-        });
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {JOB_COLUMNS} FROM cron_jobs WHERE id = ?1"
+        ))?;
+        let result = stmt.query_row(rusqlite::params![id], row_to_job);
         match result {
             Ok(j) => Ok(Some(j)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
         }
     }
+
+    /// Record a new run of a job. Returns the run ID.
+    ///
+    /// Runs beyond the newest `MAX_RUNS_PER_JOB` for the job are pruned.
+    pub fn insert_run(
+        &self,
+        job_id: &str,
+        started_at: DateTime<Utc>,
+        status: CronRunStatus,
+        error: Option<&str>,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let finished_at = (status != CronRunStatus::Running).then(|| started_at.to_rfc3339());
+        conn.execute(
+            "INSERT INTO cron_runs (job_id, started_at, finished_at, status, error)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                job_id,
+                started_at.to_rfc3339(),
+                finished_at,
+                enum_to_sql(&status),
+                error,
+            ],
+        )?;
+        let run_id = conn.last_insert_rowid();
+        conn.execute(
+            "DELETE FROM cron_runs WHERE job_id = ?1 AND id NOT IN
+                 (SELECT id FROM cron_runs WHERE job_id = ?1 ORDER BY id DESC LIMIT ?2)",
+            rusqlite::params![job_id, MAX_RUNS_PER_JOB],
+        )?;
+        Ok(run_id)
+    }
+
+    /// Record the outcome of a run.
+    pub fn finish_run(
+        &self,
+        run_id: i64,
+        status: CronRunStatus,
+        error: Option<&str>,
+        response: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE cron_runs SET finished_at = ?1, status = ?2, error = ?3, response = ?4
+             WHERE id = ?5",
+            rusqlite::params![
+                Utc::now().to_rfc3339(),
+                enum_to_sql(&status),
+                error,
+                response,
+                run_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Mark runs left `running` (e.g. by a gateway crash) as failed.
+    /// Returns the number of runs updated.
+    pub fn fail_running_runs(&self, error: &str) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let count = conn.execute(
+            "UPDATE cron_runs SET finished_at = ?1, status = ?2, error = ?3 WHERE status = ?4",
+            rusqlite::params![
+                Utc::now().to_rfc3339(),
+                enum_to_sql(&CronRunStatus::Error),
+                error,
+                enum_to_sql(&CronRunStatus::Running),
+            ],
+        )?;
+        Ok(count)
+    }
+
+    /// List recent runs, newest first, optionally for a single job.
+    pub fn list_runs(&self, job_id: Option<&str>, limit: usize) -> Result<Vec<CronRun>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {RUN_COLUMNS} FROM cron_runs
+             WHERE ?1 IS NULL OR job_id = ?1
+             ORDER BY id DESC LIMIT ?2"
+        ))?;
+        let runs = stmt
+            .query_map(rusqlite::params![job_id, limit as i64], row_to_run)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(runs)
+    }
+}
+
+/// Map a row selected with `JOB_COLUMNS` to a `CronJob`.
+fn row_to_job(row: &Row<'_>) -> rusqlite::Result<CronJob> {
+    Ok(CronJob {
+        id: row.get(0)?,
+        schedule: row.get(1)?,
+        task: row.get(2)?,
+        agent_id: row.get(3)?,
+        session_key: row.get(4)?,
+        enabled: row.get::<_, i64>(5)? != 0,
+        last_run: row
+            .get::<_, Option<String>>(6)?
+            .and_then(|s| s.parse().ok()),
+        next_run: row
+            .get::<_, Option<String>>(7)?
+            .and_then(|s| s.parse().ok()),
+        created_at: row
+            .get::<_, String>(8)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+        timezone: row.get(9)?,
+        delivery: row
+            .get::<_, Option<String>>(10)?
+            .and_then(|s| serde_json::from_str(&s).ok()),
+        missed_runs: enum_from_sql(row.get(11)?).unwrap_or_default(),
+        overlap: enum_from_sql(row.get(12)?).unwrap_or_default(),
+        kind: enum_from_sql(row.get(13)?).unwrap_or_default(),
+        delete_after_run: row.get::<_, i64>(14)? != 0,
+    })
+}
+
+/// Map a row selected with `RUN_COLUMNS` to a `CronRun`.
+fn row_to_run(row: &Row<'_>) -> rusqlite::Result<CronRun> {
+    Ok(CronRun {
+        id: row.get(0)?,
+        job_id: row.get(1)?,
+        started_at: row
+            .get::<_, String>(2)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+        finished_at: row
+            .get::<_, Option<String>>(3)?
+            .and_then(|s| s.parse().ok()),
+        status: enum_from_sql(row.get(4)?).unwrap_or(CronRunStatus::Error),
+        error: row.get(5)?,
+        response: row.get(6)?,
+    })
+}
+
+/// Store a unit enum as its serde name (e.g. `run_once`).
+fn enum_to_sql<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+}
+
+/// Parse a unit enum from its serde name.
+fn enum_from_sql<T: DeserializeOwned>(value: Option<String>) -> Option<T> {
+    value.and_then(|s| serde_json::from_value(serde_json::Value::String(s)).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CronDelivery;
+
+    fn test_job(id: &str, schedule: &str) -> CronJob {
+        CronJob {
+            id: id.into(),
+            kind: Default::default(),
+            schedule: schedule.into(),
+            timezone: None,
+            task: "Send the daily briefing".into(),
+            agent_id: "default".into(),
+            session_key: format!("cron:default:{id}"),
+            delivery: None,
+            enabled: true,
+            delete_after_run: false,
+            missed_runs: Default::default(),
+            overlap: Default::default(),
+            last_run: None,
+            next_run: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_and_get() {
+        let store = CronStore::open_in_memory().unwrap();
+        let mut job = test_job("job-1", "0 9 * * *");
+        job.timezone = Some("Europe/Berlin".into());
+        job.delivery = Some(CronDelivery {
+            channel_id: "tg".into(),
+            recipient_id: "42".into(),
+            metadata: [("chat_id".to_string(), serde_json::json!(42))].into(),
+        });
+        store.upsert_job(&job).unwrap();
+
+        let loaded = store.get_job("job-1").unwrap().unwrap();
+        assert_eq!(loaded.timezone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(loaded.delivery, job.delivery);
+        assert_eq!(store.list_jobs().unwrap().len(), 1);
+
+        assert!(store.delete_job("job-1").unwrap());
+        assert!(store.get_job("job-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rejects_invalid_schedule() {
+        let store = CronStore::open_in_memory().unwrap();
+        let err = store
+            .upsert_job(&test_job("job-1", "not a schedule"))
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid cron expression"));
+        assert!(store.list_jobs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_run_history_is_pruned() {
+        let store = CronStore::open_in_memory().unwrap();
+        let now = chrono::Utc::now();
+        for _ in 0..MAX_RUNS_PER_JOB + 5 {
+            store
+                .insert_run("job-1", now, CronRunStatus::Skipped, None)
+                .unwrap();
+        }
+        store
+            .insert_run("job-2", now, CronRunStatus::Running, None)
+            .unwrap();
+
+        let runs = store.list_runs(Some("job-1"), 1000).unwrap();
+        assert_eq!(runs.len() as i64, MAX_RUNS_PER_JOB);
+        assert_eq!(
+            store.list_runs(None, 1000).unwrap().len() as i64,
+            MAX_RUNS_PER_JOB + 1
+        );
+        assert_eq!(store.fail_running_runs("interrupted").unwrap(), 1);
+    }
 }
@@ -43,11 +43,13 @@ use tokio::sync::{RwLock, mpsc};
 use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
+use aobot_hooks::events::HookEvent;
+use aobot_hooks::registry::{HookRegistry, InterceptOutcome};
 use aobot_types::{ChannelInfo, ChannelStatus, InboundMessage, OutboundMessage};
 
 use crate::session_manager::StreamEvent;
 
-use crate::session_manager::GatewaySessionManager;
+use crate::session_manager::{GatewaySessionManager, SessionOrigin};
 
 /// Trait for channel plugins that bridge external platforms to the gateway.
 ///
@@ -205,6 +207,25 @@ impl ChannelManager {
         channel.send(message).await
     }
 
+    /// Send a message after running it through the `message_sending`
+    /// interceptors and events, as replies to inbound messages are.
+    /// Returns `Ok(false)` if an interceptor rejected it.
+    pub async fn send_intercepted(
+        &self,
+        hooks: Option<&HookRegistry>,
+        message: OutboundMessage,
+    ) -> anyhow::Result<bool> {
+        let message = match hooks {
+            Some(hooks) => match intercept_outbound(hooks, message).await {
+                Some(message) => message,
+                None => return Ok(false),
+            },
+            None => message,
+        };
+        self.send_message(message).await?;
+        Ok(true)
+    }
+
     /// List all registered channels with their status.
     pub async fn list_channels(&self) -> Vec<ChannelInfo> {
         let channels = self.channels.read().await;
@@ -239,7 +260,7 @@ impl ChannelManager {
     pub async fn run_message_loop(
         self: &Arc<Self>,
         manager: Arc<GatewaySessionManager>,
-        hooks: Arc<aobot_hooks::registry::HookRegistry>,
+        hooks: Arc<HookRegistry>,
         skills: Arc<Vec<aobot_skills::loader::SkillEntry>>,
     ) {
         let mut rx = self.inbound_rx.lock().await;
@@ -253,12 +274,11 @@ impl ChannelManager {
             let skills = skills.clone();
 
             tokio::spawn(async move {
-                // Emit MessageReceived hook
-                hooks
-                    .emit(aobot_hooks::events::HookEvent::MessageReceived {
-                        inbound: inbound.clone(),
-                    })
-                    .await;
+                // Interceptors may rewrite or drop the message before
+                // anything else sees it
+                let Some(inbound) = intercept_inbound(&hooks, inbound).await else {
+                    return;
+                };
 
                 // Derive session key from channel + sender if not provided
                 let session_key = inbound.session_key.clone().unwrap_or_else(|| {
@@ -270,6 +290,11 @@ impl ChannelManager {
 
                 let agent = inbound.agent.as_deref();
 
+                // Remember where replies for this session go (used by cron delivery)
+                manager
+                    .set_session_origin(&session_key, SessionOrigin::from_inbound(&inbound))
+                    .await;
+
                 info!(
                     channel_type = %inbound.channel_type,
                     channel_id = %inbound.channel_id,
@@ -289,7 +314,7 @@ impl ChannelManager {
                         "new" => {
                             // Emit CommandNew hook
                             hooks
-                                .emit(aobot_hooks::events::HookEvent::CommandNew {
+                                .emit(HookEvent::CommandNew {
                                     session_key: session_key.clone(),
                                 })
                                 .await;
@@ -299,7 +324,7 @@ impl ChannelManager {
                         "help" | "start" => {
                             // Emit CommandHelp hook
                             hooks
-                                .emit(aobot_hooks::events::HookEvent::CommandHelp {
+                                .emit(HookEvent::CommandHelp {
                                     session_key: session_key.clone(),
                                 })
                                 .await;
@@ -326,13 +351,8 @@ impl ChannelManager {
                             attachments: vec![],
                             metadata: inbound.metadata,
                         };
-                        // Emit MessageSending hook
-                        hooks
-                            .emit(aobot_hooks::events::HookEvent::MessageSending {
-                                outbound: outbound.clone(),
-                            })
-                            .await;
-                        if let Err(e) = channel_mgr.send_message(outbound).await {
+                        if let Err(e) = channel_mgr.send_intercepted(Some(&*hooks), outbound).await
+                        {
                             warn!("Failed to send command response: {e}");
                         }
                         return;
@@ -340,7 +360,7 @@ impl ChannelManager {
                 }
 
                 // Check for skill slash commands (e.g. /review-pr <args>)
-                let (effective_text, skill_prompt) = if inbound.text.starts_with('/') {
+                let (effective_text, skill) = if inbound.text.starts_with('/') {
                     let parts: Vec<&str> = inbound.text.splitn(2, ' ').collect();
                     let cmd_name = &parts[0][1..]; // strip leading /
                     let args = parts.get(1).unwrap_or(&"").to_string();
@@ -358,19 +378,19 @@ impl ChannelManager {
                                 args
                             }
                         );
-                        (prompt, Some(skill.name.clone()))
+                        (prompt, Some(skill.clone()))
                     } else {
                         (inbound.text.clone(), None)
                     }
                 } else {
                     (inbound.text.clone(), None)
                 };
-                let _ = skill_prompt; // skill_prompt available for future use (e.g. logging)
 
-                // Check if channel supports streaming
+                // Check if channel supports streaming. Streamed replies can't
+                // be intercepted, so interceptors force a full response
                 let use_streaming =
                     if let Some(ch) = channel_mgr.get_channel(&inbound.channel_id).await {
-                        ch.supports_streaming()
+                        ch.supports_streaming() && !hooks.has_interceptors("message_sending").await
                     } else {
                         false
                     };
@@ -395,6 +415,7 @@ impl ChannelManager {
                             &effective_text,
                             agent,
                             &inbound.attachments,
+                            skill.as_ref(),
                             event_tx,
                         )
                         .await
@@ -435,6 +456,7 @@ impl ChannelManager {
                             &effective_text,
                             agent,
                             &inbound.attachments,
+                            skill.as_ref(),
                         )
                         .await
                     {
@@ -450,14 +472,9 @@ impl ChannelManager {
                                 metadata: inbound.metadata,
                             };
 
-                            // Emit MessageSending hook
-                            hooks
-                                .emit(aobot_hooks::events::HookEvent::MessageSending {
-                                    outbound: outbound.clone(),
-                                })
-                                .await;
-
-                            if let Err(e) = channel_mgr.send_message(outbound).await {
+                            if let Err(e) =
+                                channel_mgr.send_intercepted(Some(&*hooks), outbound).await
+                            {
                                 warn!("Failed to send response to channel: {e}");
                             }
                         }
@@ -474,6 +491,62 @@ impl ChannelManager {
     }
 }
 
+/// Run the `message_received` interceptors on an inbound message, then emit
+/// it to observers. Returns `None` if an interceptor rejected it.
+async fn intercept_inbound(
+    hooks: &HookRegistry,
+    inbound: InboundMessage,
+) -> Option<InboundMessage> {
+    let inbound = match hooks
+        .intercept(HookEvent::MessageReceived { inbound })
+        .await
+    {
+        InterceptOutcome::Continue(HookEvent::MessageReceived { inbound }) => inbound,
+        InterceptOutcome::Continue(_) => unreachable!("interceptors keep the event type"),
+        InterceptOutcome::Rejected {
+            interceptor,
+            reason,
+        } => {
+            info!(%interceptor, "Inbound message rejected: {reason}");
+            return None;
+        }
+    };
+    hooks
+        .emit(HookEvent::MessageReceived {
+            inbound: inbound.clone(),
+        })
+        .await;
+    Some(inbound)
+}
+
+/// Run the `message_sending` interceptors on an outbound message, then emit
+/// it to observers. Returns `None` if an interceptor rejected it.
+async fn intercept_outbound(
+    hooks: &HookRegistry,
+    outbound: OutboundMessage,
+) -> Option<OutboundMessage> {
+    let outbound = match hooks
+        .intercept(HookEvent::MessageSending { outbound })
+        .await
+    {
+        InterceptOutcome::Continue(HookEvent::MessageSending { outbound }) => outbound,
+        InterceptOutcome::Continue(_) => unreachable!("interceptors keep the event type"),
+        InterceptOutcome::Rejected {
+            interceptor,
+            reason,
+        } => {
+            info!(%interceptor, "Outbound message rejected: {reason}");
+            return None;
+        }
+    };
+    hooks
+        .emit(HookEvent::MessageSending {
+            outbound: outbound.clone(),
+        })
+        .await;
+    Some(outbound)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
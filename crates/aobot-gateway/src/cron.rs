@@ -0,0 +1,342 @@
+//! Cron integration for the Gateway.
+//!
+//! Owns the `CronManager`, runs its scheduler, and routes dispatched jobs
+//! into agent sessions through the `GatewaySessionManager`.
+//!
+//! ```text
+//! CronManager::run_scheduler()
+//!     ↓ (DispatchedRun via mpsc)
+//! CronService::run_dispatch_loop()
+//!     ↓
+//! GatewaySessionManager.send_message(job.session_key, job.task, job.agent_id)
+//!     ↓ (response text, when job.delivery is set)
+//! ChannelManager.send_message(OutboundMessage)
+//!     ↓
+//! CronManager::finish_run() (run history, queued re-runs)
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use serde::Serialize;
+
+use aobot_cron::scheduler::{CronManager, DispatchedRun};
+use aobot_cron::store::CronStore;
+use aobot_cron::{CronDelivery, CronJob, CronRun, MissedRunPolicy, OverlapPolicy, ScheduleKind};
+use aobot_types::OutboundMessage;
+
+use crate::channel::ChannelManager;
+use crate::session_manager::{GatewaySessionManager, SessionOrigin};
+
+/// Parameters for creating a cron job.
+#[derive(Debug, Clone)]
+pub struct NewCronJob {
+    /// How `schedule` is interpreted.
+    pub kind: ScheduleKind,
+    /// Cron expression, RFC3339 time or interval, depending on `kind`.
+    pub schedule: String,
+    /// IANA timezone for the schedule (UTC when unset).
+    pub timezone: Option<String>,
+    /// Prompt sent to the agent on each run.
+    pub task: String,
+    /// Agent that runs the task.
+    pub agent_id: String,
+    /// Session to run in; defaults to a dedicated `cron:` session.
+    pub session_key: Option<String>,
+    /// Channel recipient for the agent's response.
+    pub delivery: Option<CronDelivery>,
+    /// Handling of runs missed while the gateway was down.
+    pub missed_runs: MissedRunPolicy,
+    /// Handling of runs that come due while a previous run is in progress.
+    pub overlap: OverlapPolicy,
+    /// Delete one-shot jobs after they run instead of disabling them.
+    pub delete_after_run: bool,
+}
+
+/// A job together with its most recent run, as returned by `cron.list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CronJobSummary {
+    #[serde(flatten)]
+    pub job: CronJob,
+    /// Most recent run, if the job has run.
+    pub latest_run: Option<CronRun>,
+}
+
+impl From<SessionOrigin> for CronDelivery {
+    fn from(origin: SessionOrigin) -> Self {
+        Self {
+            channel_id: origin.channel_id,
+            recipient_id: origin.sender_id,
+            metadata: origin.metadata,
+        }
+    }
+}
+
+/// Gateway-side cron service: job management plus the dispatch queue.
+pub struct CronService {
+    jobs: Arc<CronManager>,
+    dispatch_tx: mpsc::UnboundedSender<DispatchedRun>,
+    dispatch_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<DispatchedRun>>,
+}
+
+impl CronService {
+    /// Open the cron database at `db_path` and load all jobs.
+    pub async fn open(db_path: &Path) -> anyhow::Result<Self> {
+        let store = Arc::new(CronStore::open(db_path)?);
+        Self::with_store(store).await
+    }
+
+    /// Create a cron service on top of an existing store and load all jobs.
+    pub async fn with_store(store: Arc<CronStore>) -> anyhow::Result<Self> {
+        let jobs = Arc::new(CronManager::new(store));
+        jobs.load().await?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        Ok(Self {
+            jobs,
+            dispatch_tx: tx,
+            dispatch_rx: tokio::sync::Mutex::new(rx),
+        })
+    }
+
+    /// Start the scheduler and the dispatch loop as background tasks.
+    pub fn start(
+        self: &Arc<Self>,
+        manager: Arc<GatewaySessionManager>,
+        channel_mgr: Arc<ChannelManager>,
+    ) {
+        let jobs = self.jobs.clone();
+        let tx = self.dispatch_tx.clone();
+        tokio::spawn(async move {
+            jobs.run_scheduler(tx).await;
+        });
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            service.run_dispatch_loop(manager, channel_mgr).await;
+        });
+    }
+
+    /// Consume dispatched jobs and run each one on its target session.
+    async fn run_dispatch_loop(
+        &self,
+        manager: Arc<GatewaySessionManager>,
+        channel_mgr: Arc<ChannelManager>,
+    ) {
+        let mut rx = self.dispatch_rx.lock().await;
+
+        info!("Cron dispatch loop started");
+
+        while let Some(run) = rx.recv().await {
+            let manager = manager.clone();
+            let channel_mgr = channel_mgr.clone();
+            let jobs = self.jobs.clone();
+            let tx = self.dispatch_tx.clone();
+            tokio::spawn(async move {
+                let result = run_job(&manager, &channel_mgr, &run.job).await;
+                match jobs.finish_run(&run, &result).await {
+                    Ok(Some(queued)) => {
+                        let _ = tx.send(queued);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!(job_id = %run.job.id, "Failed to start queued cron run: {e}"),
+                }
+            });
+        }
+
+        info!("Cron dispatch loop stopped");
+    }
+
+    /// List all jobs with their most recent run.
+    pub async fn list(&self) -> Vec<CronJobSummary> {
+        self.jobs
+            .list_jobs()
+            .await
+            .into_iter()
+            .map(|job| {
+                let latest_run = self
+                    .jobs
+                    .list_runs(Some(&job.id), 1)
+                    .ok()
+                    .and_then(|runs| runs.into_iter().next());
+                CronJobSummary { job, latest_run }
+            })
+            .collect()
+    }
+
+    /// List recent runs, newest first, optionally for a single job.
+    pub fn runs(&self, job_id: Option<&str>, limit: usize) -> Result<Vec<CronRun>, String> {
+        self.jobs
+            .list_runs(job_id, limit)
+            .map_err(|e| format!("Failed to list cron runs: {e}"))
+    }
+
+    /// Create and persist a new job.
+    ///
+    /// When `session_key` is `None`, the job gets its own session
+    /// (`cron:<agent_id>:<job_id>`) so runs don't interleave with live chats.
+    pub async fn add(&self, new_job: NewCronJob) -> Result<CronJob, String> {
+        let NewCronJob {
+            kind,
+            schedule,
+            timezone,
+            task,
+            agent_id,
+            session_key,
+            delivery,
+            missed_runs,
+            overlap,
+            delete_after_run,
+        } = new_job;
+        let id = uuid::Uuid::new_v4().to_string();
+        let session_key = session_key.unwrap_or_else(|| format!("cron:{agent_id}:{id}"));
+        let job = CronJob {
+            id,
+            kind,
+            schedule,
+            timezone,
+            task,
+            agent_id,
+            session_key,
+            delivery,
+            enabled: true,
+            delete_after_run,
+            missed_runs,
+            overlap,
+            last_run: None,
+            next_run: None,
+            created_at: chrono::Utc::now(),
+        };
+        self.jobs
+            .add_job(job)
+            .await
+            .map_err(|e| format!("Failed to add cron job: {e}"))
+    }
+
+    /// Remove a job. Returns true if it existed.
+    pub async fn remove(&self, job_id: &str) -> Result<bool, String> {
+        self.jobs
+            .remove_job(job_id)
+            .await
+            .map_err(|e| format!("Failed to remove cron job: {e}"))
+    }
+
+    /// Update a job. Returns the updated job, or `None` if it doesn't exist.
+    pub async fn update(
+        &self,
+        job_id: &str,
+        enabled: Option<bool>,
+    ) -> Result<Option<CronJob>, String> {
+        if let Some(enabled) = enabled {
+            let found = self
+                .jobs
+                .set_enabled(job_id, enabled)
+                .await
+                .map_err(|e| format!("Failed to update cron job: {e}"))?;
+            if !found {
+                return Ok(None);
+            }
+        }
+        Ok(self.jobs.get_job(job_id).await)
+    }
+
+    /// Queue a job for immediate execution, outside its schedule.
+    ///
+    /// Manual runs are recorded in the run history but bypass the overlap policy.
+    pub async fn run_now(&self, job_id: &str) -> Result<CronJob, String> {
+        let job = self
+            .jobs
+            .get_job(job_id)
+            .await
+            .ok_or_else(|| format!("Cron job not found: {job_id}"))?;
+        let run = self
+            .jobs
+            .start_run(&job)
+            .await
+            .map_err(|e| format!("Failed to start cron job: {e}"))?;
+        self.dispatch_tx
+            .send(run)
+            .map_err(|_| "Cron dispatch loop is not running".to_string())?;
+        Ok(job)
+    }
+}
+
+/// Run a single cron job by prompting its agent session with the task,
+/// then deliver the response to the job's channel recipient, if any.
+async fn run_job(
+    manager: &GatewaySessionManager,
+    channel_mgr: &ChannelManager,
+    job: &CronJob,
+) -> Result<String, String> {
+    info!(
+        job_id = %job.id,
+        session = %job.session_key,
+        agent = %job.agent_id,
+        "Running cron job"
+    );
+
+    let result = manager
+        .send_message(&job.session_key, &job.task, Some(&job.agent_id))
+        .await;
+    match &result {
+        Ok(response) => {
+            info!(
+                job_id = %job.id,
+                response_len = response.len(),
+                "Cron job finished"
+            );
+            if let Some(delivery) = &job.delivery {
+                deliver(manager, channel_mgr, job, delivery, response.clone()).await;
+            }
+        }
+        Err(e) => {
+            warn!(job_id = %job.id, "Cron job failed: {e}");
+        }
+    }
+    result
+}
+
+/// Push a job's response to its delivery target, through the
+/// `message_sending` hooks.
+async fn deliver(
+    manager: &GatewaySessionManager,
+    channel_mgr: &ChannelManager,
+    job: &CronJob,
+    delivery: &CronDelivery,
+    response: String,
+) {
+    if response.trim().is_empty() {
+        warn!(job_id = %job.id, "Cron job produced an empty response, nothing to deliver");
+        return;
+    }
+
+    let channel_type = channel_mgr
+        .get_channel(&delivery.channel_id)
+        .await
+        .map(|ch| ch.channel_type().to_string())
+        .unwrap_or_default();
+    let outbound = OutboundMessage {
+        channel_type,
+        channel_id: delivery.channel_id.clone(),
+        recipient_id: delivery.recipient_id.clone(),
+        text: response,
+        session_key: Some(job.session_key.clone()),
+        attachments: vec![],
+        metadata: delivery.metadata.clone(),
+    };
+
+    match channel_mgr
+        .send_intercepted(manager.hooks().map(|h| h.as_ref()), outbound)
+        .await
+    {
+        Ok(true) => info!(
+            job_id = %job.id,
+            channel_id = %delivery.channel_id,
+            "Cron job output delivered"
+        ),
+        Ok(false) => info!(job_id = %job.id, "Cron job output rejected by a hook"),
+        Err(e) => warn!(job_id = %job.id, "Failed to deliver cron job output: {e}"),
+    }
+}
@@ -27,6 +27,13 @@ pub async fn handle_rpc(
         "channels.status" => handle_channels_status(params, id, channel_mgr).await,
         "config.get" => handle_config_get(id, manager).await,
         "config.set" => handle_config_set(params, id, manager).await,
+        "cron.list" => handle_cron_list(id, manager).await,
+        "cron.add" => handle_cron_add(params, id, manager).await,
+        "cron.remove" => handle_cron_remove(params, id, manager).await,
+        "cron.update" => handle_cron_update(params, id, manager).await,
+        "cron.run" => handle_cron_run(params, id, manager).await,
+        "cron.runs" => handle_cron_runs(params, id, manager).await,
+        "webhooks.deliveries" => handle_webhooks_deliveries(params, id, manager).await,
         // chat.stream is handled specially in ws.rs, but we route it here as a fallback
         "chat.stream" => handle_chat_send(params, id, manager).await,
         _ => JsonRpcResponse::error(id, METHOD_NOT_FOUND, format!("Method not found: {method}")),
@@ -317,6 +324,324 @@ async fn handle_channels_status(
     }
 }
 
+/// Error response for cron methods when the cron service is not running.
+fn cron_disabled(id: Value) -> JsonRpcResponse {
+    JsonRpcResponse::error(
+        id,
+        INTERNAL_ERROR,
+        "Cron is not enabled. Set [cron] enabled = true in config.toml.",
+    )
+}
+
+/// cron.list — list all scheduled jobs.
+async fn handle_cron_list(id: Value, manager: &GatewaySessionManager) -> JsonRpcResponse {
+    let Some(cron) = manager.cron() else {
+        return cron_disabled(id);
+    };
+
+    let jobs = cron.list().await;
+    JsonRpcResponse::success(
+        id,
+        json!({
+            "jobs": jobs,
+        }),
+    )
+}
+
+/// cron.add — create a scheduled job.
+///
+/// Params:
+///   - schedule: string (cron expression)
+///   - at: string (RFC3339 time, runs once)
+///   - in: string (delay like "20m" or "1h30m", runs once)
+///   - every: string (fixed interval like "30m")
+///     Exactly one of schedule/at/in/every is required.
+///   - delete_after_run: bool (optional, delete one-shot jobs after they run, defaults to true)
+///   - timezone: string (optional, IANA timezone, defaults to UTC)
+///   - task: string (required, prompt sent to the agent)
+///   - agent: string (optional, uses default agent)
+///   - session_key: string (optional, defaults to a dedicated `cron:` session)
+///   - delivery: object (optional, `{channel_id, recipient_id, metadata}` to send output to)
+///   - missed_runs: string (optional, "skip" | "run_once", defaults to "skip")
+///   - overlap: string (optional, "skip" | "queue" | "concurrent", defaults to "skip")
+async fn handle_cron_add(
+    params: &Value,
+    id: Value,
+    manager: &GatewaySessionManager,
+) -> JsonRpcResponse {
+    let Some(cron) = manager.cron() else {
+        return cron_disabled(id);
+    };
+
+    let timezone = params
+        .get("timezone")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    let str_param = |key: &str| params.get(key).and_then(|v| v.as_str());
+    let spec = aobot_cron::schedule::ScheduleSpec::from_params(
+        str_param("schedule"),
+        str_param("at"),
+        str_param("in"),
+        str_param("every"),
+    );
+    let (kind, schedule) =
+        match spec.and_then(|s| s.resolve(timezone.as_deref(), chrono::Utc::now())) {
+            Ok(resolved) => resolved,
+            Err(e) => return JsonRpcResponse::error(id, INVALID_PARAMS, e.to_string()),
+        };
+
+    let delete_after_run = params
+        .get("delete_after_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+
+    let task = match params.get("task").and_then(|v| v.as_str()) {
+        Some(t) => t.to_string(),
+        None => return JsonRpcResponse::error(id, INVALID_PARAMS, "Missing 'task' parameter"),
+    };
+
+    let agent = match params.get("agent").and_then(|v| v.as_str()) {
+        Some(a) => a.to_string(),
+        None => manager.get_config().await.default_agent,
+    };
+
+    let session_key = params
+        .get("session_key")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    let delivery = match params.get("delivery") {
+        Some(v) if !v.is_null() => {
+            match serde_json::from_value::<aobot_cron::CronDelivery>(v.clone()) {
+                Ok(d) => Some(d),
+                Err(e) => {
+                    return JsonRpcResponse::error(
+                        id,
+                        INVALID_PARAMS,
+                        format!("Invalid 'delivery' parameter: {e}"),
+                    );
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let missed_runs = match parse_enum_param(params, "missed_runs") {
+        Ok(p) => p,
+        Err(msg) => return JsonRpcResponse::error(id, INVALID_PARAMS, msg),
+    };
+
+    let overlap = match parse_enum_param(params, "overlap") {
+        Ok(p) => p,
+        Err(msg) => return JsonRpcResponse::error(id, INVALID_PARAMS, msg),
+    };
+
+    let new_job = crate::cron::NewCronJob {
+        kind,
+        schedule,
+        timezone,
+        task,
+        agent_id: agent,
+        session_key,
+        delivery,
+        missed_runs,
+        overlap,
+        delete_after_run,
+    };
+
+    match cron.add(new_job).await {
+        Ok(job) => JsonRpcResponse::success(
+            id,
+            json!({
+                "job": job,
+            }),
+        ),
+        Err(e) => JsonRpcResponse::error(id, INTERNAL_ERROR, e),
+    }
+}
+
+/// cron.remove — delete a scheduled job.
+///
+/// Params:
+///   - job_id: string (required)
+async fn handle_cron_remove(
+    params: &Value,
+    id: Value,
+    manager: &GatewaySessionManager,
+) -> JsonRpcResponse {
+    let Some(cron) = manager.cron() else {
+        return cron_disabled(id);
+    };
+
+    let job_id = match params.get("job_id").and_then(|v| v.as_str()) {
+        Some(j) => j,
+        None => return JsonRpcResponse::error(id, INVALID_PARAMS, "Missing 'job_id' parameter"),
+    };
+
+    match cron.remove(job_id).await {
+        Ok(deleted) => JsonRpcResponse::success(
+            id,
+            json!({
+                "deleted": deleted,
+            }),
+        ),
+        Err(e) => JsonRpcResponse::error(id, INTERNAL_ERROR, e),
+    }
+}
+
+/// cron.update — update a scheduled job.
+///
+/// Params:
+///   - job_id: string (required)
+///   - enabled: bool (optional)
+async fn handle_cron_update(
+    params: &Value,
+    id: Value,
+    manager: &GatewaySessionManager,
+) -> JsonRpcResponse {
+    let Some(cron) = manager.cron() else {
+        return cron_disabled(id);
+    };
+
+    let job_id = match params.get("job_id").and_then(|v| v.as_str()) {
+        Some(j) => j,
+        None => return JsonRpcResponse::error(id, INVALID_PARAMS, "Missing 'job_id' parameter"),
+    };
+
+    let enabled = params.get("enabled").and_then(|v| v.as_bool());
+
+    match cron.update(job_id, enabled).await {
+        Ok(Some(job)) => JsonRpcResponse::success(
+            id,
+            json!({
+                "job": job,
+            }),
+        ),
+        Ok(None) => {
+            JsonRpcResponse::error(id, INVALID_PARAMS, format!("Cron job not found: {job_id}"))
+        }
+        Err(e) => JsonRpcResponse::error(id, INTERNAL_ERROR, e),
+    }
+}
+
+/// cron.run — run a scheduled job immediately.
+///
+/// The job is queued on the dispatch loop; the response does not wait for
+/// the agent to finish.
+///
+/// Params:
+///   - job_id: string (required)
+async fn handle_cron_run(
+    params: &Value,
+    id: Value,
+    manager: &GatewaySessionManager,
+) -> JsonRpcResponse {
+    let Some(cron) = manager.cron() else {
+        return cron_disabled(id);
+    };
+
+    let job_id = match params.get("job_id").and_then(|v| v.as_str()) {
+        Some(j) => j,
+        None => return JsonRpcResponse::error(id, INVALID_PARAMS, "Missing 'job_id' parameter"),
+    };
+
+    match cron.run_now(job_id).await {
+        Ok(job) => JsonRpcResponse::success(
+            id,
+            json!({
+                "queued": true,
+                "job_id": job.id,
+                "session_key": job.session_key,
+            }),
+        ),
+        Err(e) => JsonRpcResponse::error(id, INVALID_PARAMS, e),
+    }
+}
+
+/// cron.runs — list recent job runs, newest first.
+///
+/// Params:
+///   - job_id: string (optional, all jobs when omitted)
+///   - limit: number (optional, default 20)
+async fn handle_cron_runs(
+    params: &Value,
+    id: Value,
+    manager: &GatewaySessionManager,
+) -> JsonRpcResponse {
+    let Some(cron) = manager.cron() else {
+        return cron_disabled(id);
+    };
+
+    let job_id = params.get("job_id").and_then(|v| v.as_str());
+    let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+
+    match cron.runs(job_id, limit) {
+        Ok(runs) => JsonRpcResponse::success(
+            id,
+            json!({
+                "runs": runs,
+            }),
+        ),
+        Err(e) => JsonRpcResponse::error(id, INTERNAL_ERROR, e),
+    }
+}
+
+/// webhooks.deliveries — list recent webhook deliveries, newest first, with
+/// delivery counts by status.
+///
+/// Params:
+///   - webhook: string (optional, all webhooks when omitted)
+///   - status: string (optional, "pending" | "delivered" | "failed")
+///   - limit: number (optional, default 20)
+async fn handle_webhooks_deliveries(
+    params: &Value,
+    id: Value,
+    manager: &GatewaySessionManager,
+) -> JsonRpcResponse {
+    let Some(webhooks) = manager.webhooks() else {
+        return JsonRpcResponse::error(
+            id,
+            INTERNAL_ERROR,
+            "No webhooks are configured. Add [[hooks.webhooks]] to config.toml.",
+        );
+    };
+
+    let webhook = params.get("webhook").and_then(|v| v.as_str());
+    let status = match parse_enum_param(params, "status") {
+        Ok(status) => status,
+        Err(e) => return JsonRpcResponse::error(id, INVALID_PARAMS, e),
+    };
+    let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+
+    let result = webhooks
+        .deliveries(webhook, status, limit)
+        .and_then(|deliveries| Ok((deliveries, webhooks.counts(webhook)?)));
+    match result {
+        Ok((deliveries, counts)) => JsonRpcResponse::success(
+            id,
+            json!({
+                "deliveries": deliveries,
+                "counts": counts,
+            }),
+        ),
+        Err(e) => JsonRpcResponse::error(id, INTERNAL_ERROR, e),
+    }
+}
+
+/// Parse an optional string enum parameter, using the default when absent.
+fn parse_enum_param<T: serde::de::DeserializeOwned + Default>(
+    params: &Value,
+    key: &str,
+) -> Result<T, String> {
+    match params.get(key) {
+        Some(v) if !v.is_null() => {
+            serde_json::from_value(v.clone()).map_err(|_| format!("Invalid '{key}' parameter: {v}"))
+        }
+        _ => Ok(T::default()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -459,4 +784,174 @@ mod tests {
         assert!(resp.error.is_some());
         assert_eq!(resp.error.unwrap().code, INVALID_PARAMS);
     }
+
+    async fn create_cron_test_manager() -> GatewaySessionManager {
+        let store = std::sync::Arc::new(aobot_cron::store::CronStore::open_in_memory().unwrap());
+        let service = crate::cron::CronService::with_store(store).await.unwrap();
+        let mut manager = create_test_manager();
+        manager.set_cron(std::sync::Arc::new(service));
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_handle_webhooks_deliveries() {
+        use aobot_hooks::webhook::{WebhookSink, WebhookStore};
+
+        let manager = create_test_manager();
+        let resp = handle_webhooks_deliveries(&json!({}), json!(1), &manager).await;
+        assert_eq!(resp.error.unwrap().code, INTERNAL_ERROR);
+
+        let mut manager = create_test_manager();
+        let webhook: aobot_config::WebhookConfig =
+            serde_json::from_value(json!({"name": "audit", "url": "http://localhost/"})).unwrap();
+        let sink = WebhookSink::new(vec![webhook], WebhookStore::open_in_memory().unwrap(), 10);
+        sink.enqueue(&aobot_hooks::events::HookEvent::GatewayStartup);
+        manager.set_webhooks(std::sync::Arc::new(sink));
+
+        let params = json!({"status": "pending"});
+        let resp = handle_webhooks_deliveries(&params, json!(2), &manager).await;
+        let result = resp.result.unwrap();
+        assert_eq!(result["deliveries"][0]["webhook"], "audit");
+        assert_eq!(result["deliveries"][0]["event_type"], "gateway_startup");
+        assert_eq!(result["counts"]["pending"], 1);
+
+        let params = json!({"status": "lost"});
+        let resp = handle_webhooks_deliveries(&params, json!(3), &manager).await;
+        assert_eq!(resp.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_disabled() {
+        let manager = create_test_manager();
+        let resp = handle_cron_list(json!(1), &manager).await;
+        assert!(resp.error.is_some());
+        assert_eq!(resp.error.unwrap().code, INTERNAL_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_add_list_update_remove() {
+        let manager = create_cron_test_manager().await;
+
+        // Add job
+        let params = json!({"schedule": "0 * * * *", "task": "Summarize inbox"});
+        let resp = handle_cron_add(&params, json!(1), &manager).await;
+        let job = resp.result.unwrap()["job"].clone();
+        let job_id = job["id"].as_str().unwrap().to_string();
+        assert_eq!(job["agent_id"], "default");
+        assert_eq!(job["session_key"], format!("cron:default:{job_id}"));
+        assert_eq!(job["enabled"], true);
+        assert!(job["next_run"].is_string());
+
+        // List
+        let resp = handle_cron_list(json!(2), &manager).await;
+        let jobs = resp.result.unwrap()["jobs"].clone();
+        assert_eq!(jobs.as_array().unwrap().len(), 1);
+
+        // Disable
+        let params = json!({"job_id": job_id, "enabled": false});
+        let resp = handle_cron_update(&params, json!(3), &manager).await;
+        assert_eq!(resp.result.unwrap()["job"]["enabled"], false);
+
+        // Remove
+        let params = json!({"job_id": job_id});
+        let resp = handle_cron_remove(&params, json!(4), &manager).await;
+        assert_eq!(resp.result.unwrap()["deleted"], true);
+
+        let resp = handle_cron_list(json!(5), &manager).await;
+        assert_eq!(resp.result.unwrap()["jobs"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_add_missing_schedule() {
+        let manager = create_cron_test_manager().await;
+        let params = json!({"task": "Summarize inbox"});
+        let resp = handle_cron_add(&params, json!(1), &manager).await;
+        assert!(resp.error.is_some());
+        assert_eq!(resp.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_add_with_delivery() {
+        let manager = create_cron_test_manager().await;
+        let params = json!({
+            "schedule": "@daily",
+            "task": "Morning briefing",
+            "delivery": {"channel_id": "tg", "recipient_id": "42", "metadata": {"chat_id": 42}},
+        });
+        let resp = handle_cron_add(&params, json!(1), &manager).await;
+        let job = resp.result.unwrap()["job"].clone();
+        assert_eq!(job["delivery"]["channel_id"], "tg");
+        assert_eq!(job["delivery"]["metadata"]["chat_id"], 42);
+
+        let params = json!({"schedule": "@daily", "task": "x", "delivery": {"channel_id": "tg"}});
+        let resp = handle_cron_add(&params, json!(2), &manager).await;
+        assert_eq!(resp.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_add_policies_and_runs() {
+        let manager = create_cron_test_manager().await;
+        let params = json!({
+            "schedule": "@hourly",
+            "task": "Check the inbox",
+            "missed_runs": "run_once",
+            "overlap": "queue",
+        });
+        let resp = handle_cron_add(&params, json!(1), &manager).await;
+        let job = resp.result.unwrap()["job"].clone();
+        assert_eq!(job["missed_runs"], "run_once");
+        assert_eq!(job["overlap"], "queue");
+
+        let params = json!({"schedule": "@hourly", "task": "x", "overlap": "sometimes"});
+        let resp = handle_cron_add(&params, json!(2), &manager).await;
+        assert_eq!(resp.error.unwrap().code, INVALID_PARAMS);
+
+        let params = json!({"job_id": job["id"]});
+        let resp = handle_cron_runs(&params, json!(3), &manager).await;
+        assert_eq!(resp.result.unwrap()["runs"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_add_invalid_schedule() {
+        let manager = create_cron_test_manager().await;
+        let params = json!({"schedule": "every day", "task": "Summarize inbox"});
+        let resp = handle_cron_add(&params, json!(1), &manager).await;
+        let err = resp.error.unwrap();
+        assert_eq!(err.code, INVALID_PARAMS);
+        assert!(err.message.contains("Invalid cron expression"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_add_one_shot_and_interval() {
+        let manager = create_cron_test_manager().await;
+
+        let params = json!({"in": "20m", "task": "Remind me to stretch"});
+        let resp = handle_cron_add(&params, json!(1), &manager).await;
+        let job = resp.result.unwrap()["job"].clone();
+        assert_eq!(job["kind"], "at");
+        assert_eq!(job["delete_after_run"], true);
+        assert!(job["next_run"].is_string());
+
+        let params = json!({"every": "30m", "task": "Check the inbox"});
+        let resp = handle_cron_add(&params, json!(2), &manager).await;
+        let job = resp.result.unwrap()["job"].clone();
+        assert_eq!(job["kind"], "every");
+        assert!(job["next_run"].is_string());
+
+        let params = json!({"at": "2020-01-01T00:00:00Z", "task": "Too late"});
+        let resp = handle_cron_add(&params, json!(3), &manager).await;
+        assert_eq!(resp.error.unwrap().code, INVALID_PARAMS);
+
+        let params = json!({"schedule": "@daily", "every": "1h", "task": "Ambiguous"});
+        let resp = handle_cron_add(&params, json!(4), &manager).await;
+        assert_eq!(resp.error.unwrap().code, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_handle_cron_run_not_found() {
+        let manager = create_cron_test_manager().await;
+        let params = json!({"job_id": "nonexistent"});
+        let resp = handle_cron_run(&params, json!(1), &manager).await;
+        assert!(resp.error.is_some());
+    }
 }
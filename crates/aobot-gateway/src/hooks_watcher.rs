@@ -0,0 +1,169 @@
+//! Hook directory watcher for hot-reload.
+//!
+//! Watches the hook directories and reloads all file-based hooks shortly
+//! after a hook or its manifest is added, changed or removed. Scripts are
+//! run from disk on every event, so changing them needs no reload. Hook
+//! directories that don't exist yet are watched once they are created.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+use aobot_hooks::script::{MANIFEST_FILE, ScriptHooks};
+use notify::Watcher;
+use notify_debouncer_mini::new_debouncer;
+use tracing::{info, warn};
+
+/// How often hook directories that don't exist are checked for.
+const MISSING_DIR_POLL: Duration = Duration::from_secs(5);
+
+/// Start watching the hook directories for changes.
+/// Returns a JoinHandle that can be used to abort the watcher.
+pub fn start_hooks_watcher(
+    hooks: Arc<ScriptHooks>,
+    dirs: Vec<PathBuf>,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if dirs.is_empty() {
+        return None;
+    }
+
+    let runtime = match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle,
+        Err(_) => {
+            warn!("No tokio runtime available for hooks watcher");
+            return None;
+        }
+    };
+
+    let handle = tokio::task::spawn_blocking(move || {
+        run_watcher(dirs, hooks, runtime);
+    });
+
+    Some(handle)
+}
+
+fn run_watcher(dirs: Vec<PathBuf>, hooks: Arc<ScriptHooks>, runtime: tokio::runtime::Handle) {
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    let mut debouncer = match new_debouncer(Duration::from_secs(1), tx) {
+        Ok(d) => d,
+        Err(e) => {
+            warn!("Failed to create hooks watcher: {e}");
+            return;
+        }
+    };
+
+    let mut watched = Vec::new();
+    let mut missing = Vec::new();
+    for dir in &dirs {
+        if dir.exists() {
+            watched.push(dir.clone());
+        } else {
+            info!(
+                "Hook path {} does not exist, watching for it",
+                dir.display()
+            );
+            missing.push(dir.clone());
+        }
+    }
+    watched.retain(|dir| watch(debouncer.watcher(), dir));
+
+    loop {
+        match rx.recv_timeout(MISSING_DIR_POLL) {
+            Ok(Ok(events)) => {
+                // Hooks run in their own directory and may write files there,
+                // which must not trigger a reload
+                let relevant = events
+                    .iter()
+                    .any(|event| affects_hooks(&watched, &event.path));
+                if !relevant {
+                    continue;
+                }
+                // A removed hook directory loses its watch; look for it again
+                let (gone, present): (Vec<_>, Vec<_>) =
+                    watched.into_iter().partition(|dir| !dir.exists());
+                watched = present;
+                missing.extend(gone);
+
+                info!("Hook directory changed, reloading hooks...");
+                // Reload all directories, so a hook moving between them is
+                // resolved the same way as at startup
+                runtime.block_on(hooks.reload(&dirs));
+            }
+            Ok(Err(e)) => {
+                warn!("Hooks watcher error: {e:?}");
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let (created, still_missing): (Vec<_>, Vec<_>) =
+                    missing.into_iter().partition(|dir| dir.exists());
+                missing = still_missing;
+                let created: Vec<PathBuf> = created
+                    .into_iter()
+                    .filter(|dir| watch(debouncer.watcher(), dir))
+                    .collect();
+                if created.is_empty() {
+                    continue;
+                }
+                watched.extend(created);
+                info!("Hook directory created, reloading hooks...");
+                runtime.block_on(hooks.reload(&dirs));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                info!("Hooks watcher channel closed, stopping");
+                break;
+            }
+        }
+    }
+}
+
+fn watch(watcher: &mut dyn Watcher, dir: &Path) -> bool {
+    match watcher.watch(dir, notify::RecursiveMode::Recursive) {
+        Ok(()) => {
+            info!("Hooks watcher started: watching {}", dir.display());
+            true
+        }
+        Err(e) => {
+            warn!("Failed to watch hook path {}: {e}", dir.display());
+            false
+        }
+    }
+}
+
+/// Whether a change to `path` can change the loaded hooks: a manifest, a
+/// hook directory, or a watched directory itself.
+fn affects_hooks(watched: &[PathBuf], path: &Path) -> bool {
+    let is_manifest = path.file_name().is_some_and(|name| name == MANIFEST_FILE);
+    watched.iter().any(|dir| {
+        path == dir
+            || path.parent() == Some(dir.as_path())
+            || (is_manifest && path.parent().and_then(Path::parent) == Some(dir.as_path()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affects_hooks() {
+        let watched = vec![PathBuf::from("/hooks")];
+        assert!(affects_hooks(&watched, Path::new("/hooks/audit")));
+        assert!(affects_hooks(&watched, Path::new("/hooks/audit/hook.json")));
+        assert!(affects_hooks(&watched, Path::new("/hooks")));
+        // Files written by a hook, and its scripts
+        assert!(!affects_hooks(
+            &watched,
+            Path::new("/hooks/audit/state.txt")
+        ));
+        assert!(!affects_hooks(&watched, Path::new("/hooks/audit/hook.sh")));
+        assert!(!affects_hooks(
+            &watched,
+            Path::new("/hooks/audit/logs/today")
+        ));
+        assert!(!affects_hooks(
+            &watched,
+            Path::new("/hooks/audit/logs/hook.json")
+        ));
+    }
+}
@@ -6,16 +6,20 @@
 //! - Channel plugin framework for external platform integrations
 //! - RPC methods: health, chat.send/stream/history,
 //!   sessions.list/delete, agents.list/add/delete,
-//!   channels.list/status, config.get/set
+//!   channels.list/status, config.get/set,
+//!   cron.list/add/remove/update/run/runs, webhooks.deliveries
 //! - Bearer token authentication
 //! - HTTP health check endpoint
-//! - Configuration hot-reload
+//! - Configuration and hook directory hot-reload
 
 pub mod channel;
 pub mod config_watcher;
+pub mod cron;
 pub mod external_channel;
 pub mod handlers;
+pub mod hooks_watcher;
 pub mod jsonrpc;
+pub mod memory_watcher;
 pub mod plugin_protocol;
 pub mod session_manager;
 pub mod ws;
@@ -34,7 +38,9 @@ use axum::routing::get;
 use serde::Deserialize;
 use tracing::info;
 
-use aobot_config::AoBotConfig;
+use aobot_config::{AoBotConfig, expand_home};
+use aobot_memory::manager::MemoryManager;
+use aobot_memory::store::SearchFilter;
 use aobot_storage::AoBotStorage;
 use aobot_types::ChannelConfig;
 use channel::ChannelManager;
@@ -65,6 +71,9 @@ pub async fn start_gateway(
     let port = port_override.unwrap_or(config.gateway.port);
     let host = config.gateway.host.clone();
     let auth_token = config.gateway.auth_token.clone();
+    let cron_enabled = config.cron.as_ref().is_some_and(|c| c.enabled);
+    let memory_config = config.memory.clone().filter(|m| m.enabled);
+    let hooks_config = config.hooks.clone();
 
     // Initialize persistent storage
     let storage = match aobot_config::ensure_config_dir() {
@@ -96,6 +105,107 @@ pub async fn start_gateway(
         None => GatewaySessionManager::new(config, working_dir),
     };
     session_manager.set_ops_tx(ops_tx);
+
+    // Initialize cron service
+    if cron_enabled {
+        match aobot_config::ensure_config_dir() {
+            Ok(dir) => {
+                let db_path = dir.join("cron.db");
+                match cron::CronService::open(&db_path).await {
+                    Ok(service) => {
+                        info!("Cron initialized: {}", db_path.display());
+                        session_manager.set_cron(Arc::new(service));
+                    }
+                    Err(e) => tracing::warn!("Failed to open cron store, cron disabled: {e}"),
+                }
+            }
+            Err(e) => tracing::warn!("Failed to resolve config dir, cron disabled: {e}"),
+        }
+    }
+
+    // Initialize memory index
+    if let Some(memory_config) = &memory_config {
+        match aobot_config::ensure_config_dir() {
+            Ok(dir) => {
+                let db_path = dir.join("memory.db");
+                match MemoryManager::from_config(memory_config, &db_path) {
+                    Ok(memory) => {
+                        info!("Memory initialized: {}", db_path.display());
+                        session_manager.set_memory(Arc::new(memory));
+                    }
+                    Err(e) => tracing::warn!("Failed to initialize memory, memory disabled: {e}"),
+                }
+            }
+            Err(e) => tracing::warn!("Failed to resolve config dir, memory disabled: {e}"),
+        }
+    }
+
+    // Create hook registry; its interceptors see every tool call
+    let hook_registry = Arc::new(aobot_hooks::registry::HookRegistry::new());
+    session_manager.set_hooks(hook_registry.clone());
+
+    // Forward hook events to configured webhooks
+    if let Some(hooks_config) = hooks_config
+        .as_ref()
+        .filter(|c| c.enabled && !c.webhooks.is_empty())
+    {
+        match aobot_config::ensure_config_dir() {
+            Ok(dir) => {
+                let db_path = dir.join("webhooks.db");
+                match aobot_hooks::webhook::WebhookStore::open(&db_path) {
+                    Ok(store) => {
+                        let sink = Arc::new(aobot_hooks::webhook::WebhookSink::new(
+                            hooks_config.webhooks.clone(),
+                            store,
+                            hooks_config.webhook_queue_limit,
+                        ));
+                        sink.register(&hook_registry).await;
+                        sink.start();
+                        info!(
+                            "Webhooks initialized: {} configured",
+                            hooks_config.webhooks.len()
+                        );
+                        session_manager.set_webhooks(sink);
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to open webhook store, webhooks disabled: {e}")
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Failed to resolve config dir, webhooks disabled: {e}"),
+        }
+    }
+
+    // Load file-based hooks from the hook directories
+    let script_hooks = match hooks_config {
+        Some(hooks_config) if !hooks_config.enabled => {
+            info!("Hooks disabled, not loading hook directories");
+            None
+        }
+        hooks_config => {
+            let max_concurrent = hooks_config
+                .as_ref()
+                .map_or(aobot_hooks::script::DEFAULT_MAX_CONCURRENT, |c| {
+                    c.max_concurrent
+                });
+            let mut dirs: Vec<PathBuf> = hooks_config
+                .iter()
+                .flat_map(|c| &c.dirs)
+                .map(|dir| expand_home(dir))
+                .collect();
+            // Always include the default location
+            if let Ok(config_dir) = aobot_config::ensure_config_dir() {
+                dirs.push(config_dir.join("hooks"));
+            }
+            let script_hooks = Arc::new(aobot_hooks::script::ScriptHooks::new(
+                hook_registry.clone(),
+                max_concurrent,
+            ));
+            script_hooks.reload(&dirs).await;
+            Some((script_hooks, dirs))
+        }
+    };
+
     let manager = Arc::new(session_manager);
 
     // Restore sessions from persistent storage
@@ -149,11 +259,38 @@ pub async fn start_gateway(
     // Start all registered channels
     channel_mgr.start_all().await;
 
+    // Start cron scheduler and job dispatch
+    if let Some(cron) = manager.cron() {
+        cron.start(manager.clone(), channel_mgr.clone());
+    }
+
+    // Index memory directories in the background
+    if let Some(memory) = manager.memory()
+        && memory_config.as_ref().is_some_and(|m| m.sync_on_start)
+    {
+        let memory = memory.clone();
+        tokio::spawn(async move {
+            match memory.sync(false).await {
+                Ok(result) => info!(
+                    "Memory sync: {} files scanned, {} updated, {} failed",
+                    result.files_scanned, result.files_updated, result.files_failed
+                ),
+                Err(e) => tracing::warn!("Memory sync failed: {e}"),
+            }
+        });
+    }
+
+    // Re-index memory files as they change
+    let memory_watcher_handle = manager
+        .memory()
+        .and_then(|memory| memory_watcher::start_memory_watcher(memory.clone()));
+
     // Start config file watcher for hot-reload
     let _watcher_handle = config_watcher::start_config_watcher(manager.clone());
 
-    // Create hook registry
-    let hook_registry = Arc::new(aobot_hooks::registry::HookRegistry::new());
+    // Reload file-based hooks as their directories change
+    let hooks_watcher_handle =
+        script_hooks.and_then(|(hooks, dirs)| hooks_watcher::start_hooks_watcher(hooks, dirs));
 
     // Emit GatewayStartup hook event
     hook_registry
@@ -173,16 +310,7 @@ pub async fn start_gateway(
         let mut dirs: Vec<(std::path::PathBuf, aobot_skills::SkillSource)> = Vec::new();
         if let Some(skills_config) = &config_for_skills.skills {
             for dir in &skills_config.dirs {
-                let expanded = if dir.starts_with("~/") {
-                    if let Ok(home) = std::env::var("HOME") {
-                        std::path::PathBuf::from(home).join(&dir[2..])
-                    } else {
-                        std::path::PathBuf::from(dir)
-                    }
-                } else {
-                    std::path::PathBuf::from(dir)
-                };
-                dirs.push((expanded, aobot_skills::SkillSource::Managed));
+                dirs.push((expand_home(dir), aobot_skills::SkillSource::Managed));
             }
         }
         // Always include default locations
@@ -233,14 +361,57 @@ pub async fn start_gateway(
     if _watcher_handle.is_some() {
         info!("  Config watcher: active");
     }
+    if memory_watcher_handle.is_some() {
+        info!("  Memory watcher: active");
+    }
+    if hooks_watcher_handle.is_some() {
+        info!("  Hooks watcher: active");
+    }
     info!("  Channel manager: active");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    info!("Gateway shutting down");
+    hook_registry
+        .emit_and_wait(aobot_hooks::events::HookEvent::GatewayShutdown)
+        .await;
 
     Ok(())
 }
 
+/// Resolve on Ctrl+C, or SIGTERM on Unix.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::warn!("Failed to listen for Ctrl+C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
 /// GET /health — simple HTTP health check.
 async fn health_handler() -> impl IntoResponse {
     axum::Json(serde_json::json!({
@@ -288,6 +459,153 @@ fn extract_bearer_token(headers: &HeaderMap) -> Option<&str> {
         .and_then(|v| v.strip_prefix("Bearer "))
 }
 
+/// Error returned by cron operations when the cron service is not running.
+const CRON_DISABLED: &str = "Cron is not enabled. Set [cron] enabled = true in config.toml.";
+
+/// Error returned by memory operations when the memory index is not initialized.
+const MEMORY_DISABLED: &str = "Memory is not enabled. Set [memory] enabled = true in config.toml.";
+
+/// Largest file `memory_get` will read.
+const MEMORY_GET_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Notes file (relative to the memory notes directory) shared by an agent.
+fn agent_notes_file(agent_id: &str) -> String {
+    format!("agents/{}.md", aobot_memory::notes::file_stem(agent_id))
+}
+
+/// Execute a memory op from gateway tools.
+async fn run_memory_op(op: aobot_tools::context::GatewayOp, manager: Arc<GatewaySessionManager>) {
+    use aobot_tools::context::{GatewayOp, GatewayOpResult, MemoryNoteScope};
+
+    match op {
+        GatewayOp::MemorySearch {
+            query,
+            max_results,
+            sources,
+            session_key,
+            reply,
+        } => {
+            let result = match manager.memory() {
+                Some(memory) => {
+                    // Conversation memory only ever comes from the caller's own sessions
+                    let filter = SearchFilter {
+                        sources,
+                        owner: Some(manager.memory_owner(&session_key).await),
+                    };
+                    match memory.search(&query, max_results, None, &filter).await {
+                        Ok(results) => GatewayOpResult::Json(serde_json::json!({
+                            "query": query,
+                            "results": results,
+                        })),
+                        Err(e) => GatewayOpResult::Error(format!("Memory search failed: {e}")),
+                    }
+                }
+                None => GatewayOpResult::Error(MEMORY_DISABLED.to_string()),
+            };
+            let _ = reply.send(result);
+        }
+        GatewayOp::MemoryWrite {
+            content,
+            scope,
+            agent_id,
+            session_key,
+            reply,
+        } => {
+            let result = match manager.memory() {
+                Some(memory) => {
+                    let owner = manager.memory_owner(&session_key).await;
+                    let file = match scope {
+                        MemoryNoteScope::User => aobot_memory::notes::user_notes_file(&owner),
+                        MemoryNoteScope::Agent => agent_notes_file(&agent_id),
+                    };
+                    match memory.write_note(&file, &content).await {
+                        Ok(id) => GatewayOpResult::Json(serde_json::json!({
+                            "note_id": id,
+                            "file": file,
+                        })),
+                        Err(e) => GatewayOpResult::Error(format!("Memory write failed: {e}")),
+                    }
+                }
+                None => GatewayOpResult::Error(MEMORY_DISABLED.to_string()),
+            };
+            let _ = reply.send(result);
+        }
+        GatewayOp::MemoryForget {
+            id,
+            agent_id,
+            session_key,
+            reply,
+        } => {
+            let result = match manager.memory() {
+                Some(memory) => {
+                    let owner = manager.memory_owner(&session_key).await;
+                    let files = [
+                        aobot_memory::notes::user_notes_file(&owner),
+                        agent_notes_file(&agent_id),
+                    ];
+                    match memory.forget_note(&files, &id).await {
+                        Ok(removed) if removed.is_empty() => {
+                            GatewayOpResult::Error(format!("No note found for id: {id}"))
+                        }
+                        Ok(removed) => {
+                            GatewayOpResult::Json(serde_json::json!({ "removed": removed }))
+                        }
+                        Err(e) => GatewayOpResult::Error(format!("Memory forget failed: {e}")),
+                    }
+                }
+                None => GatewayOpResult::Error(MEMORY_DISABLED.to_string()),
+            };
+            let _ = reply.send(result);
+        }
+        GatewayOp::MemoryGet {
+            path,
+            start_line,
+            end_line,
+            session_key,
+            reply,
+        } => {
+            let Some(memory) = manager.memory() else {
+                let _ = reply.send(GatewayOpResult::Error(MEMORY_DISABLED.to_string()));
+                return;
+            };
+            let owner = manager.memory_owner(&session_key).await;
+            let mut file = match memory.open_for_read(&path, &owner) {
+                Ok(file) => tokio::fs::File::from_std(file),
+                Err(e) => {
+                    tracing::warn!(session_key, path, "Denied memory_get: {e}");
+                    let _ = reply.send(GatewayOpResult::Error(e.to_string()));
+                    return;
+                }
+            };
+            let size = file.metadata().await.map(|m| m.len());
+            if let Ok(size) = size
+                && size > MEMORY_GET_MAX_BYTES
+            {
+                let _ = reply.send(GatewayOpResult::Error(format!(
+                    "{path} is too large to read ({size} bytes, limit {MEMORY_GET_MAX_BYTES})"
+                )));
+                return;
+            }
+            let mut content = String::new();
+            match tokio::io::AsyncReadExt::read_to_string(&mut file, &mut content).await {
+                Ok(_) => {
+                    let lines: Vec<&str> = content.lines().collect();
+                    let start = start_line.unwrap_or(1).saturating_sub(1);
+                    let end = end_line.unwrap_or(lines.len()).min(lines.len());
+                    let selected: Vec<&str> = lines.get(start..end).unwrap_or_default().to_vec();
+                    let _ = reply.send(GatewayOpResult::Text(selected.join("\n")));
+                }
+                Err(e) => {
+                    let _ = reply.send(GatewayOpResult::Error(format!(
+                        "Failed to read {path}: {e}"
+                    )));
+                }
+            }
+        }
+        _ => unreachable!("not a memory op"),
+    }
+}
+
 /// Process GatewayOp messages from gateway tools.
 ///
 /// This loop receives operation requests from gateway tools and executes them
@@ -379,8 +697,14 @@ async fn run_gateway_ops_loop(
                 text,
                 reply,
             } => {
+                // Resolved here so hooks see which kind of channel it goes to
+                let channel_type = channel_mgr
+                    .get_channel(&channel_id)
+                    .await
+                    .map(|ch| ch.channel_type().to_string())
+                    .unwrap_or_default();
                 let outbound = aobot_types::OutboundMessage {
-                    channel_type: String::new(), // will be resolved by channel
+                    channel_type,
                     channel_id: channel_id.clone(),
                     recipient_id,
                     text,
@@ -388,12 +712,20 @@ async fn run_gateway_ops_loop(
                     attachments: vec![],
                     metadata: std::collections::HashMap::new(),
                 };
-                match channel_mgr.send_message(outbound).await {
-                    Ok(()) => {
+                match channel_mgr
+                    .send_intercepted(manager.hooks().map(|h| h.as_ref()), outbound)
+                    .await
+                {
+                    Ok(true) => {
                         let _ = reply.send(GatewayOpResult::Json(
                             serde_json::json!({"status": "sent", "channel_id": channel_id}),
                         ));
                     }
+                    Ok(false) => {
+                        let _ = reply.send(GatewayOpResult::Error(
+                            "Message was rejected by a message_sending hook".to_string(),
+                        ));
+                    }
                     Err(e) => {
                         let _ = reply.send(GatewayOpResult::Error(e.to_string()));
                     }
@@ -432,84 +764,132 @@ async fn run_gateway_ops_loop(
                     }
                 }
             }
-            GatewayOp::MemorySearch {
-                query,
-                max_results,
-                reply,
-            } => {
-                // Memory search — placeholder until memory system is fully wired
-                let _ = reply.send(GatewayOpResult::Json(serde_json::json!({
-                    "query": query,
-                    "max_results": max_results,
-                    "results": [],
-                    "note": "Memory system not yet initialized. Configure [memory] in config.toml."
-                })));
-            }
-            GatewayOp::MemoryGet {
-                path,
-                start_line,
-                end_line,
-                reply,
-            } => {
-                // Read memory file directly
-                match tokio::fs::read_to_string(&path).await {
-                    Ok(content) => {
-                        let lines: Vec<&str> = content.lines().collect();
-                        let start = start_line.unwrap_or(1).saturating_sub(1);
-                        let end = end_line.unwrap_or(lines.len()).min(lines.len());
-                        let selected: Vec<&str> =
-                            lines.get(start..end).unwrap_or_default().to_vec();
-                        let _ = reply.send(GatewayOpResult::Text(selected.join("\n")));
-                    }
-                    Err(e) => {
-                        let _ = reply.send(GatewayOpResult::Error(format!(
-                            "Failed to read {path}: {e}"
-                        )));
-                    }
-                }
+            op @ (GatewayOp::MemorySearch { .. }
+            | GatewayOp::MemoryWrite { .. }
+            | GatewayOp::MemoryForget { .. }
+            | GatewayOp::MemoryGet { .. }) => {
+                // Embedding requests can be slow; don't hold up other ops
+                tokio::spawn(run_memory_op(op, manager.clone()));
             }
             GatewayOp::CronList { reply } => {
-                let _ = reply.send(GatewayOpResult::Json(serde_json::json!({
-                    "jobs": [],
-                    "note": "Cron system not yet initialized. Configure [cron] in config.toml."
-                })));
+                let result = match manager.cron() {
+                    Some(cron) => {
+                        let jobs = cron.list().await;
+                        GatewayOpResult::Json(serde_json::json!({ "jobs": jobs }))
+                    }
+                    None => GatewayOpResult::Error(CRON_DISABLED.to_string()),
+                };
+                let _ = reply.send(result);
             }
             GatewayOp::CronAdd {
+                kind,
                 schedule,
+                timezone,
                 task,
                 agent_id,
+                delivery,
+                origin_session_key,
+                missed_runs,
+                overlap,
+                delete_after_run,
                 reply,
             } => {
-                let _ = reply.send(GatewayOpResult::Json(serde_json::json!({
-                    "status": "not_available",
-                    "schedule": schedule,
-                    "task": task,
-                    "agent_id": agent_id,
-                    "note": "Cron system not yet initialized."
-                })));
+                let result = match manager.cron() {
+                    Some(cron) => {
+                        let agent_id = match agent_id {
+                            Some(id) => id,
+                            None => manager.get_config().await.default_agent,
+                        };
+                        // Default to replying where the job was requested from
+                        let delivery = match delivery {
+                            Some(d) => Some(d),
+                            None => manager
+                                .session_origin(&origin_session_key)
+                                .await
+                                .map(Into::into),
+                        };
+                        let new_job = cron::NewCronJob {
+                            kind,
+                            schedule,
+                            timezone,
+                            task,
+                            agent_id,
+                            session_key: None,
+                            delivery,
+                            missed_runs,
+                            overlap,
+                            delete_after_run,
+                        };
+                        match cron.add(new_job).await {
+                            Ok(job) => GatewayOpResult::Json(
+                                serde_json::to_value(&job).unwrap_or_default(),
+                            ),
+                            Err(e) => GatewayOpResult::Error(e),
+                        }
+                    }
+                    None => GatewayOpResult::Error(CRON_DISABLED.to_string()),
+                };
+                let _ = reply.send(result);
             }
             GatewayOp::CronRemove { job_id, reply } => {
-                let _ = reply.send(GatewayOpResult::Json(serde_json::json!({
-                    "status": "not_available",
-                    "job_id": job_id,
-                })));
+                let result = match manager.cron() {
+                    Some(cron) => match cron.remove(&job_id).await {
+                        Ok(true) => GatewayOpResult::Json(
+                            serde_json::json!({"status": "removed", "job_id": job_id}),
+                        ),
+                        Ok(false) => {
+                            GatewayOpResult::Error(format!("Cron job not found: {job_id}"))
+                        }
+                        Err(e) => GatewayOpResult::Error(e),
+                    },
+                    None => GatewayOpResult::Error(CRON_DISABLED.to_string()),
+                };
+                let _ = reply.send(result);
             }
             GatewayOp::CronUpdate {
                 job_id,
                 enabled,
                 reply,
             } => {
-                let _ = reply.send(GatewayOpResult::Json(serde_json::json!({
-                    "status": "not_available",
-                    "job_id": job_id,
-                    "enabled": enabled,
-                })));
+                let result = match manager.cron() {
+                    Some(cron) => match cron.update(&job_id, enabled).await {
+                        Ok(Some(job)) => {
+                            GatewayOpResult::Json(serde_json::to_value(&job).unwrap_or_default())
+                        }
+                        Ok(None) => GatewayOpResult::Error(format!("Cron job not found: {job_id}")),
+                        Err(e) => GatewayOpResult::Error(e),
+                    },
+                    None => GatewayOpResult::Error(CRON_DISABLED.to_string()),
+                };
+                let _ = reply.send(result);
             }
             GatewayOp::CronRun { job_id, reply } => {
-                let _ = reply.send(GatewayOpResult::Json(serde_json::json!({
-                    "status": "not_available",
-                    "job_id": job_id,
-                })));
+                let result = match manager.cron() {
+                    Some(cron) => match cron.run_now(&job_id).await {
+                        Ok(job) => GatewayOpResult::Json(serde_json::json!({
+                            "status": "queued",
+                            "job_id": job.id,
+                            "session_key": job.session_key,
+                        })),
+                        Err(e) => GatewayOpResult::Error(e),
+                    },
+                    None => GatewayOpResult::Error(CRON_DISABLED.to_string()),
+                };
+                let _ = reply.send(result);
+            }
+            GatewayOp::CronRuns {
+                job_id,
+                limit,
+                reply,
+            } => {
+                let result = match manager.cron() {
+                    Some(cron) => match cron.runs(job_id.as_deref(), limit) {
+                        Ok(runs) => GatewayOpResult::Json(serde_json::json!({ "runs": runs })),
+                        Err(e) => GatewayOpResult::Error(e),
+                    },
+                    None => GatewayOpResult::Error(CRON_DISABLED.to_string()),
+                };
+                let _ = reply.send(result);
             }
         }
     }
@@ -0,0 +1,112 @@
+//! Memory directory watcher for live re-indexing.
+//!
+//! Watches the configured `[memory] dirs` and re-indexes changed files
+//! shortly after they are saved. Deleted or renamed files are removed
+//! from the index.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aobot_memory::manager::MemoryManager;
+use notify_debouncer_mini::new_debouncer;
+use tracing::{info, warn};
+
+/// Start watching the memory directories for changes.
+/// Returns a JoinHandle that can be used to abort the watcher.
+pub fn start_memory_watcher(memory: Arc<MemoryManager>) -> Option<tokio::task::JoinHandle<()>> {
+    let watch_paths: Vec<PathBuf> = memory
+        .dirs()
+        .into_iter()
+        .filter(|dir| {
+            let exists = dir.exists();
+            if !exists {
+                info!(
+                    "Memory path {} does not exist, not watching it",
+                    dir.display()
+                );
+            }
+            exists
+        })
+        .collect();
+    if watch_paths.is_empty() {
+        return None;
+    }
+
+    let runtime = match tokio::runtime::Handle::try_current() {
+        Ok(handle) => handle,
+        Err(_) => {
+            warn!("No tokio runtime available for memory watcher");
+            return None;
+        }
+    };
+
+    let handle = tokio::task::spawn_blocking(move || {
+        run_watcher(watch_paths, memory, runtime);
+    });
+
+    Some(handle)
+}
+
+fn run_watcher(
+    watch_paths: Vec<PathBuf>,
+    memory: Arc<MemoryManager>,
+    runtime: tokio::runtime::Handle,
+) {
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    let mut debouncer = match new_debouncer(Duration::from_secs(1), tx) {
+        Ok(d) => d,
+        Err(e) => {
+            warn!("Failed to create memory watcher: {e}");
+            return;
+        }
+    };
+
+    for path in &watch_paths {
+        if let Err(e) = debouncer
+            .watcher()
+            .watch(path, notify::RecursiveMode::Recursive)
+        {
+            warn!("Failed to watch memory path {}: {e}", path.display());
+        } else {
+            info!("Memory watcher started: watching {}", path.display());
+        }
+    }
+
+    loop {
+        match rx.recv() {
+            Ok(Ok(events)) => {
+                // Directories are rescanned; vanished paths may have been
+                // directories, so keep them for removal as well
+                let changed: BTreeSet<PathBuf> = events
+                    .into_iter()
+                    .map(|event| event.path)
+                    .filter(|path| path.is_dir() || !path.exists() || memory.should_index(path))
+                    .collect();
+                if changed.is_empty() {
+                    continue;
+                }
+
+                let paths: Vec<PathBuf> = changed.into_iter().collect();
+                // Block this watcher thread so overlapping changes are indexed one batch at a time
+                match runtime.block_on(memory.sync_paths(&paths)) {
+                    Ok(result) if result.files_updated > 0 || result.files_removed > 0 => info!(
+                        "Memory re-indexed: {} files updated, {} removed",
+                        result.files_updated, result.files_removed
+                    ),
+                    Ok(_) => {}
+                    Err(e) => warn!("Memory re-index failed: {e}"),
+                }
+            }
+            Ok(Err(e)) => {
+                warn!("Memory watcher error: {e:?}");
+            }
+            Err(_) => {
+                info!("Memory watcher channel closed, stopping");
+                break;
+            }
+        }
+    }
+}
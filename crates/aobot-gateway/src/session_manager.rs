@@ -12,7 +12,7 @@ use std::pin::Pin;
 
 use pi_agent_ai::register::create_default_registry;
 use pi_agent_ai::stream::stream_simple;
-use pi_agent_core::agent_types::{AgentEvent, AgentMessage, StreamFnBox};
+use pi_agent_core::agent_types::{AgentEvent, AgentMessage, AgentTool, StreamFnBox};
 use pi_agent_core::event_stream::create_assistant_message_event_stream;
 use pi_agent_core::types::*;
 use pi_coding_agent::agent_session::events::AgentSessionEvent;
@@ -27,11 +27,22 @@ use pi_coding_agent::extensions::wrapper::{create_extension_tools, wrap_tools_wi
 use pi_coding_agent::retry::RetryConfig as PiRetryConfig;
 use pi_coding_agent::tools::{create_all_tools, create_coding_tools};
 
-use aobot_config::AoBotConfig;
+use aobot_config::{AoBotConfig, SessionMemoryMode};
+use aobot_hooks::events::HookEvent;
+use aobot_hooks::registry::HookRegistry;
+use aobot_hooks::webhook::WebhookSink;
+use aobot_memory::manager::MemoryManager;
+use aobot_skills::loader::SkillEntry;
 use aobot_storage::{AoBotStorage, SessionMetadata};
 use aobot_types::{AgentConfig, AgentToolsConfig};
 
 use aobot_tools::context::GatewayToolContext;
+use aobot_tools::sandbox::SandboxPolicy;
+use aobot_tools::tools::hooked::apply_hooks;
+use aobot_tools::tools::process::BackgroundProcessRegistry;
+use aobot_tools::tools::sandboxed::apply_sandbox;
+
+use crate::cron::CronService;
 
 /// Information about a managed session.
 #[derive(Debug, Clone, serde::Serialize)]
@@ -41,6 +52,39 @@ pub struct SessionInfo {
     pub model_id: String,
     pub message_count: usize,
     pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_skill: Option<String>,
+}
+
+/// Channel route that a session's inbound messages arrive from.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SessionOrigin {
+    pub channel_type: String,
+    pub channel_id: String,
+    pub sender_id: String,
+    /// Routing metadata needed to reply (e.g. Telegram `chat_id`).
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+impl SessionOrigin {
+    /// Metadata keys that only describe a single message, not the route.
+    const PER_MESSAGE_KEYS: &'static [&'static str] = &["message_id", "command"];
+
+    /// Capture the reply route of an inbound channel message.
+    pub fn from_inbound(inbound: &aobot_types::InboundMessage) -> Self {
+        let metadata = inbound
+            .metadata
+            .iter()
+            .filter(|(k, _)| !Self::PER_MESSAGE_KEYS.contains(&k.as_str()))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        Self {
+            channel_type: inbound.channel_type.clone(),
+            channel_id: inbound.channel_id.clone(),
+            sender_id: inbound.sender_id.clone(),
+            metadata,
+        }
+    }
 }
 
 /// Streaming events sent during chat.stream.
@@ -68,6 +112,16 @@ pub struct GatewaySessionManager {
     storage: Option<Arc<AoBotStorage>>,
     /// Sender for gateway operations — shared with all gateway tools.
     ops_tx: Option<tokio::sync::mpsc::UnboundedSender<aobot_tools::context::GatewayOp>>,
+    /// Cron service, present when `[cron] enabled = true`.
+    cron: Option<Arc<CronService>>,
+    /// Memory index, present when `[memory] enabled = true`.
+    memory: Option<Arc<MemoryManager>>,
+    /// Hook registry whose interceptors see every tool call.
+    hooks: Option<Arc<HookRegistry>>,
+    /// Webhook sink, present when `[[hooks.webhooks]]` are configured.
+    webhooks: Option<Arc<WebhookSink>>,
+    /// Last channel route seen per session key.
+    origins: Arc<RwLock<HashMap<String, SessionOrigin>>>,
 }
 
 struct ManagedSession {
@@ -77,6 +131,62 @@ struct ManagedSession {
     created_at: i64,
     /// Whether the pi-agent session ID has been captured and saved to SQLite.
     pi_session_id_saved: bool,
+    /// Full tool set of the session, restored after a skill's turn.
+    tools: Vec<Arc<dyn AgentTool>>,
+    /// Skill whose turn is running, if any.
+    active_skill: Option<String>,
+}
+
+/// A session running a skill's turn, from [`GatewaySessionManager::enter_skill`].
+///
+/// Dropping it restores the session's full tool set, so the restriction
+/// ends even if the turn is cancelled. [`SkillTurn::leave`] also waits for
+/// the active skill to be cleared in storage.
+struct SkillTurn<'a> {
+    managed: &'a mut ManagedSession,
+    session_key: String,
+    storage: Option<Arc<AoBotStorage>>,
+}
+
+impl SkillTurn<'_> {
+    /// End the turn.
+    async fn leave(mut self) {
+        if let Some(storage) = self.storage.take() {
+            if let Err(e) = storage.set_active_skill(&self.session_key, None).await {
+                tracing::warn!("Failed to clear active skill: {e}");
+            }
+        }
+    }
+}
+
+impl std::ops::Deref for SkillTurn<'_> {
+    type Target = ManagedSession;
+
+    fn deref(&self) -> &ManagedSession {
+        self.managed
+    }
+}
+
+impl std::ops::DerefMut for SkillTurn<'_> {
+    fn deref_mut(&mut self) -> &mut ManagedSession {
+        self.managed
+    }
+}
+
+impl Drop for SkillTurn<'_> {
+    fn drop(&mut self) {
+        self.managed.session.set_tools(self.managed.tools.clone());
+        self.managed.active_skill = None;
+        // Cancelled before `leave`
+        if let Some(storage) = self.storage.take() {
+            let session_key = std::mem::take(&mut self.session_key);
+            tokio::spawn(async move {
+                if let Err(e) = storage.set_active_skill(&session_key, None).await {
+                    tracing::warn!("Failed to clear active skill: {e}");
+                }
+            });
+        }
+    }
 }
 
 impl GatewaySessionManager {
@@ -89,6 +199,11 @@ impl GatewaySessionManager {
             registry,
             storage: None,
             ops_tx: None,
+            cron: None,
+            memory: None,
+            hooks: None,
+            webhooks: None,
+            origins: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -106,6 +221,11 @@ impl GatewaySessionManager {
             registry,
             storage: Some(storage),
             ops_tx: None,
+            cron: None,
+            memory: None,
+            hooks: None,
+            webhooks: None,
+            origins: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -117,6 +237,95 @@ impl GatewaySessionManager {
         self.ops_tx = Some(tx);
     }
 
+    /// Attach the cron service.
+    pub fn set_cron(&mut self, cron: Arc<CronService>) {
+        self.cron = Some(cron);
+    }
+
+    /// Get the cron service, if cron is enabled.
+    pub fn cron(&self) -> Option<&Arc<CronService>> {
+        self.cron.as_ref()
+    }
+
+    /// Attach the memory manager.
+    pub fn set_memory(&mut self, memory: Arc<MemoryManager>) {
+        self.memory = Some(memory);
+    }
+
+    /// Get the memory manager, if memory is enabled.
+    pub fn memory(&self) -> Option<&Arc<MemoryManager>> {
+        self.memory.as_ref()
+    }
+
+    /// Attach the hook registry.
+    pub fn set_hooks(&mut self, hooks: Arc<HookRegistry>) {
+        self.hooks = Some(hooks);
+    }
+
+    /// Get the hook registry, if hooks are attached.
+    pub fn hooks(&self) -> Option<&Arc<HookRegistry>> {
+        self.hooks.as_ref()
+    }
+
+    /// Attach the webhook sink.
+    pub fn set_webhooks(&mut self, webhooks: Arc<WebhookSink>) {
+        self.webhooks = Some(webhooks);
+    }
+
+    /// Get the webhook sink, if webhooks are configured.
+    pub fn webhooks(&self) -> Option<&Arc<WebhookSink>> {
+        self.webhooks.as_ref()
+    }
+
+    /// Record the channel route a session's messages arrive from.
+    pub async fn set_session_origin(&self, session_key: &str, origin: SessionOrigin) {
+        self.origins
+            .write()
+            .await
+            .insert(session_key.to_string(), origin);
+    }
+
+    /// Get the channel route of a session, if it was driven by a channel.
+    pub async fn session_origin(&self, session_key: &str) -> Option<SessionOrigin> {
+        self.origins.read().await.get(session_key).cloned()
+    }
+
+    /// User that owns a session's conversation memory.
+    pub async fn memory_owner(&self, session_key: &str) -> String {
+        memory_owner(self.origins.read().await.get(session_key), session_key)
+    }
+
+    /// Memory manager to index conversations into, if `[memory.sessions]`
+    /// is enabled with the given mode.
+    fn session_memory(
+        &self,
+        config: &AoBotConfig,
+        mode: SessionMemoryMode,
+    ) -> Option<Arc<MemoryManager>> {
+        let sessions = &config.memory.as_ref()?.sessions;
+        (sessions.enabled && sessions.mode == mode)
+            .then(|| self.memory.clone())
+            .flatten()
+    }
+
+    /// Index a completed turn into memory in the background, if enabled.
+    async fn index_turn(&self, session_key: &str, message: &str, response: &str) {
+        let config = self.config.read().await;
+        let Some(memory) = self.session_memory(&config, SessionMemoryMode::Turns) else {
+            return;
+        };
+        drop(config);
+
+        let owner = self.memory_owner(session_key).await;
+        let session_key = session_key.to_string();
+        let text = format!("user: {message}\nassistant: {response}");
+        tokio::spawn(async move {
+            if let Err(e) = memory.index_session_turn(&session_key, &owner, &text).await {
+                tracing::warn!(session_key, "Failed to index turn into memory: {e}");
+            }
+        });
+    }
+
     /// Create a new agent session with the given key.
     pub async fn create_session(
         &self,
@@ -215,9 +424,17 @@ impl GatewaySessionManager {
             None
         };
 
-        // Set up tools based on agent config
+        // Set up tools based on agent config; sandboxed agents start in
+        // their workspace
+        let sandbox = Arc::new(SandboxPolicy::from_config(
+            agent_config.sandbox.as_ref(),
+            &self.working_dir,
+        )?);
         let mut tools =
-            build_tools_for_agent(&self.working_dir, &agent_config.tools, &config.tools);
+            build_tools_for_agent(sandbox.working_dir(), &agent_config.tools, &config.tools);
+        // Owned by the session's tools, so background processes are killed
+        // once the session is deleted
+        let processes = Arc::new(BackgroundProcessRegistry::new());
 
         // Add gateway tools if ops channel is available
         if let Some(ops_tx) = &self.ops_tx {
@@ -225,6 +442,8 @@ impl GatewaySessionManager {
                 current_session_key: session_key.to_string(),
                 current_agent_id: agent_name.to_string(),
                 config: Arc::new(tokio::sync::RwLock::new(config.clone())),
+                sandbox: sandbox.clone(),
+                processes: processes.clone(),
                 ops_tx: ops_tx.clone(),
             });
             let gateway_tools = aobot_tools::tools::create_gateway_tools(gateway_ctx);
@@ -255,14 +474,23 @@ impl GatewaySessionManager {
             }
         }
 
-        // If we have MCP extensions, wrap tools and add extension tools
+        tools = apply_sandbox(tools, &sandbox, &processes);
+
+        // If we have MCP extensions, wrap tools and add extension tools.
+        // Extension tools go through the sandbox too, which removes them
+        // when it can't confine them
         if let Some(ref runner) = extension_runner {
             tools = wrap_tools_with_extensions(tools, runner.clone());
             let ext_tools = create_extension_tools(runner.clone());
-            tools.extend(ext_tools);
+            tools.extend(apply_sandbox(ext_tools, &sandbox, &processes));
         }
 
-        session.set_tools(tools);
+        // Hooks see every tool call, including extension tools
+        if let Some(hooks) = &self.hooks {
+            tools = apply_hooks(tools, hooks, session_key, agent_name);
+        }
+
+        session.set_tools(tools.clone());
 
         // Set extension runner on session if available
         if let Some(runner) = extension_runner {
@@ -348,6 +576,15 @@ impl GatewaySessionManager {
                     as Pin<Box<dyn Future<Output = Result<String, CodingAgentError>> + Send>>
             },
         );
+        let summary_fn = match self.session_memory(&config, SessionMemoryMode::Summary) {
+            Some(memory) => index_summaries(
+                summary_fn,
+                memory,
+                self.origins.clone(),
+                session_key.to_string(),
+            ),
+            None => summary_fn,
+        };
         session.set_summary_fn(summary_fn);
 
         // Set up retry configuration from aobot config
@@ -366,6 +603,8 @@ impl GatewaySessionManager {
             model_id: agent_config.model.clone(),
             created_at: now,
             pi_session_id_saved: false,
+            tools,
+            active_skill: None,
         };
 
         self.sessions
@@ -384,12 +623,22 @@ impl GatewaySessionManager {
                 message_count: 0,
                 is_active: true,
                 pi_session_id: None,
+                active_skill: None,
             };
             if let Err(e) = storage.save_session(&meta).await {
                 tracing::warn!("Failed to persist session metadata: {e}");
             }
         }
 
+        if let Some(hooks) = &self.hooks {
+            hooks
+                .emit(HookEvent::SessionStart {
+                    session_key: session_key.to_string(),
+                    agent_id: agent_name.to_string(),
+                })
+                .await;
+        }
+
         Ok(())
     }
 
@@ -497,11 +746,12 @@ impl GatewaySessionManager {
         message: &str,
         agent_name: Option<&str>,
     ) -> Result<String, String> {
-        self.send_message_with_attachments(session_key, message, agent_name, &[])
+        self.send_message_with_attachments(session_key, message, agent_name, &[], None)
             .await
     }
 
-    /// Send a prompt with attachments to a session.
+    /// Send a prompt with attachments to a session, optionally as a skill's
+    /// turn limited to the skill's `allowed_tools`.
     /// Returns collected text response.
     pub async fn send_message_with_attachments(
         &self,
@@ -509,6 +759,7 @@ impl GatewaySessionManager {
         message: &str,
         agent_name: Option<&str>,
         attachments: &[aobot_types::Attachment],
+        skill: Option<&SkillEntry>,
     ) -> Result<String, String> {
         let session_arc = self.ensure_session(session_key, agent_name).await?;
         let mut managed = session_arc.lock().await;
@@ -532,34 +783,14 @@ impl GatewaySessionManager {
         self.maybe_compact(session_key, &mut managed).await;
 
         let content = Self::build_user_content(message, attachments);
-        let prompt_result = managed
-            .session
-            .prompt_with_content(content.clone(), PromptOptions::default())
-            .await;
-
-        // On context overflow, try emergency compaction and retry once
-        if let Err(ref e) = prompt_result {
-            let err_str = e.to_string();
-            if err_str.contains("too long")
-                || err_str.contains("context")
-                || err_str.contains("token")
-            {
-                tracing::warn!(
-                    session_key,
-                    "Context overflow detected, attempting emergency compaction"
-                );
-                if managed.session.compact(None).await.is_ok() {
-                    managed
-                        .session
-                        .prompt_with_content(content, PromptOptions::default())
-                        .await
-                        .map_err(|e| format!("Prompt error after compaction: {e}"))?;
-                } else {
-                    prompt_result.map_err(|e| format!("Prompt error: {e}"))?;
-                }
-            } else {
-                prompt_result.map_err(|e| format!("Prompt error: {e}"))?;
+        match skill {
+            Some(skill) => {
+                let mut turn = self.enter_skill(session_key, &mut managed, skill).await?;
+                let prompt_result = Self::prompt(session_key, &mut turn, content).await;
+                turn.leave().await;
+                prompt_result?;
             }
+            None => Self::prompt(session_key, &mut managed, content).await?,
         }
 
         // Capture pi-agent session ID on first prompt
@@ -584,6 +815,7 @@ impl GatewaySessionManager {
         }
 
         let result = response_text.lock().unwrap().clone();
+        self.index_turn(session_key, message, &result).await;
         Ok(result)
     }
 
@@ -601,12 +833,14 @@ impl GatewaySessionManager {
             message,
             agent_name,
             &[],
+            None,
             event_tx,
         )
         .await
     }
 
-    /// Send a prompt with attachments and streaming events.
+    /// Send a prompt with attachments and streaming events, optionally as a
+    /// skill's turn limited to the skill's `allowed_tools`.
     /// Returns the full response text after completion.
     pub async fn send_message_streaming_with_attachments(
         &self,
@@ -614,6 +848,7 @@ impl GatewaySessionManager {
         message: &str,
         agent_name: Option<&str>,
         attachments: &[aobot_types::Attachment],
+        skill: Option<&SkillEntry>,
         event_tx: tokio::sync::mpsc::UnboundedSender<StreamEvent>,
     ) -> Result<String, String> {
         let session_arc = self.ensure_session(session_key, agent_name).await?;
@@ -673,6 +908,58 @@ impl GatewaySessionManager {
         self.maybe_compact(session_key, &mut managed).await;
 
         let content = Self::build_user_content(message, attachments);
+        match skill {
+            Some(skill) => {
+                let mut turn = self.enter_skill(session_key, &mut managed, skill).await?;
+                let prompt_result = Self::prompt(session_key, &mut turn, content).await;
+                turn.leave().await;
+                prompt_result?;
+            }
+            None => Self::prompt(session_key, &mut managed, content).await?,
+        }
+
+        // Deactivate the subscriber so it becomes a no-op on future prompts
+        active.store(false, std::sync::atomic::Ordering::Relaxed);
+
+        // Capture pi-agent session ID on first prompt
+        if !managed.pi_session_id_saved {
+            if let Some(pi_sid) = managed.session.session_id().map(|s| s.to_string()) {
+                if let Some(storage) = &self.storage {
+                    if let Err(e) = storage.save_pi_session_id(session_key, &pi_sid).await {
+                        tracing::warn!("Failed to save pi_session_id: {e}");
+                    } else {
+                        managed.pi_session_id_saved = true;
+                        tracing::debug!(session_key, pi_session_id = %pi_sid, "Captured pi_session_id");
+                    }
+                }
+            }
+        }
+
+        // Update activity in storage
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.update_session_activity(session_key).await {
+                tracing::warn!("Failed to update session activity: {e}");
+            }
+        }
+
+        let result = response_text.lock().unwrap().clone();
+        self.index_turn(session_key, message, &result).await;
+
+        // Signal streaming completion so send_streaming() can do its final edit
+        let _ = done_tx.send(StreamEvent::Done {
+            full_response: result.clone(),
+        });
+
+        Ok(result)
+    }
+
+    /// Prompt a session. On context overflow, compacts the session and
+    /// retries once.
+    async fn prompt(
+        session_key: &str,
+        managed: &mut ManagedSession,
+        content: pi_agent_core::types::UserContent,
+    ) -> Result<(), String> {
         let prompt_result = managed
             .session
             .prompt_with_content(content.clone(), PromptOptions::default())
@@ -702,39 +989,62 @@ impl GatewaySessionManager {
                 prompt_result.map_err(|e| format!("Prompt error: {e}"))?;
             }
         }
+        Ok(())
+    }
 
-        // Deactivate the subscriber so it becomes a no-op on future prompts
-        active.store(false, std::sync::atomic::Ordering::Relaxed);
-
-        // Capture pi-agent session ID on first prompt
-        if !managed.pi_session_id_saved {
-            if let Some(pi_sid) = managed.session.session_id().map(|s| s.to_string()) {
-                if let Some(storage) = &self.storage {
-                    if let Err(e) = storage.save_pi_session_id(session_key, &pi_sid).await {
-                        tracing::warn!("Failed to save pi_session_id: {e}");
-                    } else {
-                        managed.pi_session_id_saved = true;
-                        tracing::debug!(session_key, pi_session_id = %pi_sid, "Captured pi_session_id");
-                    }
-                }
+    /// Start a skill's turn: record it as the session's active skill and, if
+    /// it declares `allowed_tools`, restrict the session to the tools both
+    /// the agent's policy and the skill allow. Fails if that leaves no tools.
+    async fn enter_skill<'a>(
+        &self,
+        session_key: &str,
+        managed: &'a mut ManagedSession,
+        skill: &SkillEntry,
+    ) -> Result<SkillTurn<'a>, String> {
+        if !skill.allowed_tools.is_empty() {
+            // The session's tools already passed the agent's policy, so
+            // resolving the skill's allow list against them intersects both
+            let names: Vec<String> = managed
+                .tools
+                .iter()
+                .map(|tool| tool.name().to_string())
+                .collect();
+            let policy = aobot_tools::policy::ToolPolicy {
+                allow: skill.allowed_tools.clone(),
+                ..Default::default()
+            };
+            let allowed = aobot_tools::policy::resolve_effective_tools(&policy, &names);
+            if allowed.is_empty() {
+                return Err(format!(
+                    "Skill {} allows none of this agent's tools (allowed_tools: {})",
+                    skill.name,
+                    skill.allowed_tools.join(", ")
+                ));
             }
+            let tools = managed
+                .tools
+                .iter()
+                .filter(|tool| allowed.iter().any(|name| name == tool.name()))
+                .cloned()
+                .collect();
+            tracing::debug!(session_key, skill = %skill.name, tools = ?allowed, "Restricting tools for skill");
+            managed.session.set_tools(tools);
         }
 
-        // Update activity in storage
+        managed.active_skill = Some(skill.name.clone());
         if let Some(storage) = &self.storage {
-            if let Err(e) = storage.update_session_activity(session_key).await {
-                tracing::warn!("Failed to update session activity: {e}");
+            if let Err(e) = storage
+                .set_active_skill(session_key, Some(&skill.name))
+                .await
+            {
+                tracing::warn!("Failed to record active skill: {e}");
             }
         }
-
-        let result = response_text.lock().unwrap().clone();
-
-        // Signal streaming completion so send_streaming() can do its final edit
-        let _ = done_tx.send(StreamEvent::Done {
-            full_response: result.clone(),
-        });
-
-        Ok(result)
+        Ok(SkillTurn {
+            managed,
+            session_key: session_key.to_string(),
+            storage: self.storage.clone(),
+        })
     }
 
     /// Get chat history for a session.
@@ -768,22 +1078,38 @@ impl GatewaySessionManager {
                 model_id: managed.model_id.clone(),
                 message_count: managed.session.messages().len(),
                 created_at: managed.created_at,
+                active_skill: managed.active_skill.clone(),
             });
         }
         result
     }
 
-    /// Delete a session.
+    /// Delete a session. Its background processes are killed once the
+    /// session is no longer in use.
     pub async fn delete_session(&self, session_key: &str) -> bool {
-        let removed = self.sessions.write().await.remove(session_key).is_some();
-        if removed {
-            if let Some(storage) = &self.storage {
-                if let Err(e) = storage.delete_session(session_key).await {
-                    tracing::warn!("Failed to delete session from storage: {e}");
-                }
+        let Some(removed) = self.sessions.write().await.remove(session_key) else {
+            return false;
+        };
+        if let Some(storage) = &self.storage {
+            if let Err(e) = storage.delete_session(session_key).await {
+                tracing::warn!("Failed to delete session from storage: {e}");
             }
         }
-        removed
+        if let Some(hooks) = self.hooks.clone() {
+            // The session may still be mid-prompt; don't hold up the caller
+            // waiting for its lock just to read the agent name
+            let session_key = session_key.to_string();
+            tokio::spawn(async move {
+                let agent_id = removed.lock().await.agent_name.clone();
+                hooks
+                    .emit(HookEvent::SessionEnd {
+                        session_key,
+                        agent_id,
+                    })
+                    .await;
+            });
+        }
+        true
     }
 
     /// Get current config.
@@ -940,6 +1266,41 @@ impl GatewaySessionManager {
     }
 }
 
+/// Memory owner of a session: the channel user that drives it, or the
+/// session itself when it has no channel route.
+fn memory_owner(origin: Option<&SessionOrigin>, session_key: &str) -> String {
+    match origin {
+        Some(origin) => format!("{}:{}", origin.channel_type, origin.sender_id),
+        None => format!("session:{session_key}"),
+    }
+}
+
+/// Wrap a compaction summary function so every summary it produces is
+/// also indexed into memory.
+fn index_summaries(
+    inner: SummaryFn,
+    memory: Arc<MemoryManager>,
+    origins: Arc<RwLock<HashMap<String, SessionOrigin>>>,
+    session_key: String,
+) -> SummaryFn {
+    Arc::new(
+        move |messages: Vec<AgentMessage>, previous_summary: Option<String>| {
+            let inner = inner.clone();
+            let memory = memory.clone();
+            let origins = origins.clone();
+            let session_key = session_key.clone();
+            Box::pin(async move {
+                let summary = inner(messages, previous_summary).await?;
+                let owner = memory_owner(origins.read().await.get(&session_key), &session_key);
+                if let Err(e) = memory.index_session(&session_key, &owner, &summary).await {
+                    tracing::warn!(session_key, "Failed to index summary into memory: {e}");
+                }
+                Ok(summary)
+            }) as Pin<Box<dyn Future<Output = Result<String, CodingAgentError>> + Send>>
+        },
+    )
+}
+
 /// Build a tool set for an agent based on its tool configuration.
 ///
 /// Uses the tool policy system to resolve the effective tool set:
@@ -32,11 +32,15 @@ pub enum HookEvent {
     MessageSending { outbound: OutboundMessage },
     /// A tool call is about to be executed.
     ToolCallBefore {
+        session_key: String,
+        agent_id: String,
         tool_name: String,
         params: serde_json::Value,
     },
     /// A tool call has finished.
     ToolCallAfter {
+        session_key: String,
+        agent_id: String,
         tool_name: String,
         result: serde_json::Value,
         is_error: bool,
@@ -1,7 +1,11 @@
 //! aobot-hooks: Event-driven hook system.
 //!
 //! Hooks respond to gateway lifecycle events (startup, session start/end,
-//! messages, tool calls) and can execute custom logic.
+//! messages, tool calls) and can execute custom logic, either registered in
+//! code or loaded from hook directories as external executables. Events can
+//! also be forwarded to outbound webhooks.
 
 pub mod events;
 pub mod registry;
+pub mod script;
+pub mod webhook;
@@ -1,11 +1,23 @@
 //! Hook registry — manages hook subscriptions and dispatches events.
+//!
+//! There are two kinds of hooks:
+//!
+//! - Handlers ([`HookRegistry::on`]) observe events. They run detached and
+//!   can't affect the gateway.
+//! - Interceptors ([`HookRegistry::intercept_with`]) run in priority order
+//!   and are awaited before the gateway acts on an event. Each returns a
+//!   [`HookDecision`] that lets the event through, replaces it, or rejects
+//!   it.
 
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::warn;
 
 use crate::events::HookEvent;
 
@@ -13,10 +25,82 @@ use crate::events::HookEvent;
 pub type HookHandler =
     Arc<dyn Fn(HookEvent) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
 
+/// Async interceptor function type.
+pub type InterceptorHandler =
+    Arc<dyn Fn(HookEvent) -> Pin<Box<dyn Future<Output = HookDecision> + Send>> + Send + Sync>;
+
+/// Default time an interceptor may take before it is skipped.
+pub const DEFAULT_INTERCEPTOR_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Event types the gateway runs interceptors for. Interceptors registered
+/// for other event types never run.
+pub const INTERCEPTED_EVENTS: &[&str] =
+    &["message_received", "message_sending", "tool_call_before"];
+
+/// What an interceptor decided about an event.
+#[derive(Debug, Clone)]
+pub enum HookDecision {
+    /// Let the event through unchanged.
+    Continue,
+    /// Replace the event. The replacement must be of the same type.
+    Modify(Box<HookEvent>),
+    /// Stop the event.
+    Reject { reason: String },
+}
+
+/// Registration options for an interceptor.
+#[derive(Debug, Clone)]
+pub struct InterceptorOptions {
+    /// Name used in logs and rejection messages.
+    pub name: String,
+    /// Interceptors with a higher priority run first; equal priorities run
+    /// in registration order.
+    pub priority: i32,
+    /// Time the interceptor may take.
+    pub timeout: Duration,
+    /// Reject the event if the interceptor times out or returns an invalid
+    /// replacement, instead of skipping the interceptor.
+    pub fail_closed: bool,
+}
+
+impl Default for InterceptorOptions {
+    fn default() -> Self {
+        Self {
+            name: "interceptor".to_string(),
+            priority: 0,
+            timeout: DEFAULT_INTERCEPTOR_TIMEOUT,
+            fail_closed: false,
+        }
+    }
+}
+
+/// Result of running the interceptors for an event.
+#[derive(Debug, Clone)]
+pub enum InterceptOutcome {
+    /// Proceed with this event (the original or a replacement).
+    Continue(HookEvent),
+    /// An interceptor rejected the event.
+    Rejected { interceptor: String, reason: String },
+}
+
+#[derive(Clone)]
+struct Handler {
+    name: Option<String>,
+    handler: HookHandler,
+}
+
+#[derive(Clone)]
+struct Interceptor {
+    options: InterceptorOptions,
+    handler: InterceptorHandler,
+}
+
 /// Manages hook subscriptions and dispatches events.
 pub struct HookRegistry {
     /// Map from event type name to handlers.
-    handlers: RwLock<HashMap<String, Vec<HookHandler>>>,
+    handlers: RwLock<HashMap<String, Vec<Handler>>>,
+    /// Map from event type name to interceptors, sorted by priority.
+    interceptors: RwLock<HashMap<String, Vec<Interceptor>>>,
 }
 
 impl HookRegistry {
@@ -24,16 +108,39 @@ impl HookRegistry {
     pub fn new() -> Self {
         Self {
             handlers: RwLock::new(HashMap::new()),
+            interceptors: RwLock::new(HashMap::new()),
         }
     }
 
     /// Register a handler for a specific event type.
     pub async fn on(&self, event_type: &str, handler: HookHandler) {
+        self.add_handler(event_type, None, handler).await;
+    }
+
+    /// Register a handler under a name, so it can be removed again with
+    /// [`HookRegistry::remove`].
+    pub async fn on_named(&self, event_type: &str, name: &str, handler: HookHandler) {
+        self.add_handler(event_type, Some(name.to_string()), handler)
+            .await;
+    }
+
+    async fn add_handler(&self, event_type: &str, name: Option<String>, handler: HookHandler) {
         let mut handlers = self.handlers.write().await;
         handlers
             .entry(event_type.to_string())
             .or_default()
-            .push(handler);
+            .push(Handler { name, handler });
+    }
+
+    /// Remove the named handlers and the interceptors registered under
+    /// `name`, for all event types.
+    pub async fn remove(&self, name: &str) {
+        for list in self.handlers.write().await.values_mut() {
+            list.retain(|h| h.name.as_deref() != Some(name));
+        }
+        for list in self.interceptors.write().await.values_mut() {
+            list.retain(|i| i.options.name != name);
+        }
     }
 
     /// Register a handler for multiple event types.
@@ -45,29 +152,116 @@ impl HookRegistry {
 
     /// Dispatch an event to all registered handlers.
     pub async fn emit(&self, event: HookEvent) {
-        let event_type = event_type_name(&event);
-        let handlers = self.handlers.read().await;
+        self.dispatch(event).await;
+    }
 
-        if let Some(handler_list) = handlers.get(event_type) {
-            for handler in handler_list {
-                let event_clone = event.clone();
-                let handler = handler.clone();
-                tokio::spawn(async move {
-                    handler(event_clone).await;
-                });
+    /// Dispatch an event and wait for all handlers to finish. Used where
+    /// detached handlers could be cut short, e.g. on shutdown.
+    pub async fn emit_and_wait(&self, event: HookEvent) {
+        for handle in self.dispatch(event).await {
+            if let Err(e) = handle.await {
+                warn!("Hook handler failed: {e}");
             }
         }
+    }
 
-        // Also dispatch to "*" (wildcard) handlers
-        if let Some(wildcard_handlers) = handlers.get("*") {
-            for handler in wildcard_handlers {
+    /// Spawn the handlers for an event, including "*" (wildcard) handlers.
+    async fn dispatch(&self, event: HookEvent) -> Vec<JoinHandle<()>> {
+        let event_type = event_type_name(&event);
+        let handlers = self.handlers.read().await;
+        [event_type, "*"]
+            .iter()
+            .filter_map(|t| handlers.get(*t))
+            .flatten()
+            .map(|Handler { handler, .. }| {
                 let event_clone = event.clone();
                 let handler = handler.clone();
                 tokio::spawn(async move {
                     handler(event_clone).await;
-                });
+                })
+            })
+            .collect()
+    }
+
+    /// Register an interceptor for a specific event type ("*" for all).
+    pub async fn intercept_with(
+        &self,
+        event_type: &str,
+        options: InterceptorOptions,
+        handler: InterceptorHandler,
+    ) {
+        let mut interceptors = self.interceptors.write().await;
+        let list = interceptors.entry(event_type.to_string()).or_default();
+        let index = list.partition_point(|i| i.options.priority >= options.priority);
+        list.insert(index, Interceptor { options, handler });
+    }
+
+    /// Whether any interceptor would run for an event type.
+    pub async fn has_interceptors(&self, event_type: &str) -> bool {
+        let interceptors = self.interceptors.read().await;
+        [event_type, "*"]
+            .iter()
+            .any(|t| interceptors.get(*t).is_some_and(|list| !list.is_empty()))
+    }
+
+    /// Run the interceptors for an event, in priority order, each seeing
+    /// the event as left by the previous one.
+    ///
+    /// Interceptors that time out or return a replacement of a different
+    /// event type are skipped with a warning, unless they fail closed.
+    pub async fn intercept(&self, event: HookEvent) -> InterceptOutcome {
+        let event_type = event_type_name(&event);
+        let chain: Vec<Interceptor> = {
+            let interceptors = self.interceptors.read().await;
+            let mut chain: Vec<Interceptor> = [event_type, "*"]
+                .iter()
+                .filter_map(|t| interceptors.get(*t))
+                .flatten()
+                .cloned()
+                .collect();
+            // Stable, so registration order is kept within a priority
+            chain.sort_by_key(|i| std::cmp::Reverse(i.options.priority));
+            chain
+        };
+
+        let mut event = event;
+        for interceptor in chain {
+            let InterceptorOptions {
+                name,
+                timeout,
+                fail_closed,
+                ..
+            } = &interceptor.options;
+            let failure =
+                match tokio::time::timeout(*timeout, (interceptor.handler)(event.clone())).await {
+                    Ok(HookDecision::Continue) => continue,
+                    Ok(HookDecision::Modify(replacement))
+                        if event_type_name(&replacement) == event_type =>
+                    {
+                        event = *replacement;
+                        continue;
+                    }
+                    Ok(HookDecision::Reject { reason }) => {
+                        return InterceptOutcome::Rejected {
+                            interceptor: name.clone(),
+                            reason,
+                        };
+                    }
+                    Ok(HookDecision::Modify(_)) => {
+                        format!("replaced a {event_type} event with a different event type")
+                    }
+                    Err(_) => format!("timed out after {}ms", timeout.as_millis()),
+                };
+
+            warn!(interceptor = %name, "Interceptor {failure}");
+            if *fail_closed {
+                return InterceptOutcome::Rejected {
+                    interceptor: name.clone(),
+                    reason: format!("interceptor {failure}"),
+                };
             }
         }
+        InterceptOutcome::Continue(event)
     }
 }
 
@@ -78,7 +272,7 @@ impl Default for HookRegistry {
 }
 
 /// Get the event type name for routing.
-fn event_type_name(event: &HookEvent) -> &'static str {
+pub(crate) fn event_type_name(event: &HookEvent) -> &'static str {
     match event {
         HookEvent::GatewayStartup => "gateway_startup",
         HookEvent::GatewayShutdown => "gateway_shutdown",
@@ -145,4 +339,242 @@ mod tests {
         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
         assert_eq!(counter.load(Ordering::SeqCst), 2);
     }
+
+    #[tokio::test]
+    async fn test_remove_named_hooks() {
+        let registry = HookRegistry::new();
+        let counter = Arc::new(AtomicU32::new(0));
+
+        for name in ["audit", "other"] {
+            let c = counter.clone();
+            registry
+                .on_named(
+                    "gateway_startup",
+                    name,
+                    Arc::new(move |_event| {
+                        let c = c.clone();
+                        Box::pin(async move {
+                            c.fetch_add(1, Ordering::SeqCst);
+                        })
+                    }),
+                )
+                .await;
+        }
+        registry
+            .intercept_with(
+                "tool_call_before",
+                options("audit", 0),
+                Arc::new(|_event| {
+                    Box::pin(async {
+                        HookDecision::Reject {
+                            reason: "no".to_string(),
+                        }
+                    })
+                }),
+            )
+            .await;
+
+        registry.remove("audit").await;
+        registry.emit_and_wait(HookEvent::GatewayStartup).await;
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert!(!registry.has_interceptors("tool_call_before").await);
+    }
+
+    #[tokio::test]
+    async fn test_emit_and_wait() {
+        let registry = HookRegistry::new();
+        let counter = Arc::new(AtomicU32::new(0));
+
+        let c = counter.clone();
+        registry
+            .on_many(
+                &["session_end", "*"],
+                Arc::new(move |_event| {
+                    let c = c.clone();
+                    Box::pin(async move {
+                        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+                        c.fetch_add(1, Ordering::SeqCst);
+                    })
+                }),
+            )
+            .await;
+
+        registry
+            .emit_and_wait(HookEvent::SessionEnd {
+                session_key: "main".to_string(),
+                agent_id: "default".to_string(),
+            })
+            .await;
+        // Both the typed and the wildcard registration ran to completion
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    fn tool_call(tool_name: &str) -> HookEvent {
+        HookEvent::ToolCallBefore {
+            session_key: "main".to_string(),
+            agent_id: "default".to_string(),
+            tool_name: tool_name.to_string(),
+            params: serde_json::json!({}),
+        }
+    }
+
+    fn options(name: &str, priority: i32) -> InterceptorOptions {
+        InterceptorOptions {
+            name: name.to_string(),
+            priority,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interceptors_run_in_priority_order() {
+        let registry = HookRegistry::new();
+        // Each interceptor appends its name to the tool name
+        for (name, priority) in [("low", -1), ("high", 10), ("mid", 0)] {
+            registry
+                .intercept_with(
+                    "tool_call_before",
+                    options(name, priority),
+                    Arc::new(move |event| {
+                        Box::pin(async move {
+                            let HookEvent::ToolCallBefore {
+                                session_key,
+                                agent_id,
+                                tool_name,
+                                params,
+                            } = event
+                            else {
+                                return HookDecision::Continue;
+                            };
+                            HookDecision::Modify(Box::new(HookEvent::ToolCallBefore {
+                                session_key,
+                                agent_id,
+                                tool_name: format!("{tool_name}>{name}"),
+                                params,
+                            }))
+                        })
+                    }),
+                )
+                .await;
+        }
+
+        let InterceptOutcome::Continue(HookEvent::ToolCallBefore { tool_name, .. }) =
+            registry.intercept(tool_call("bash")).await
+        else {
+            panic!("expected the tool call to continue");
+        };
+        assert_eq!(tool_name, "bash>high>mid>low");
+
+        // Other event types are not intercepted
+        assert!(matches!(
+            registry.intercept(HookEvent::GatewayStartup).await,
+            InterceptOutcome::Continue(HookEvent::GatewayStartup)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_rejects() {
+        let registry = HookRegistry::new();
+        let later = Arc::new(AtomicU32::new(0));
+        registry
+            .intercept_with(
+                "*",
+                options("deny-bash", 1),
+                Arc::new(|event| {
+                    Box::pin(async move {
+                        match event {
+                            HookEvent::ToolCallBefore { tool_name, .. } if tool_name == "bash" => {
+                                HookDecision::Reject {
+                                    reason: "bash is disabled".to_string(),
+                                }
+                            }
+                            _ => HookDecision::Continue,
+                        }
+                    })
+                }),
+            )
+            .await;
+        let c = later.clone();
+        registry
+            .intercept_with(
+                "tool_call_before",
+                options("counter", 0),
+                Arc::new(move |_event| {
+                    let c = c.clone();
+                    Box::pin(async move {
+                        c.fetch_add(1, Ordering::SeqCst);
+                        HookDecision::Continue
+                    })
+                }),
+            )
+            .await;
+
+        let InterceptOutcome::Rejected {
+            interceptor,
+            reason,
+        } = registry.intercept(tool_call("bash")).await
+        else {
+            panic!("expected the tool call to be rejected");
+        };
+        assert_eq!(interceptor, "deny-bash");
+        assert_eq!(reason, "bash is disabled");
+        assert_eq!(later.load(Ordering::SeqCst), 0);
+
+        assert!(matches!(
+            registry.intercept(tool_call("read")).await,
+            InterceptOutcome::Continue(_)
+        ));
+        assert_eq!(later.load(Ordering::SeqCst), 1);
+        assert!(registry.has_interceptors("message_sending").await);
+        assert!(
+            !HookRegistry::new()
+                .has_interceptors("message_sending")
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_timeout_and_invalid_replacement() {
+        let registry = HookRegistry::new();
+        let slow = |fail_closed| InterceptorOptions {
+            name: "slow".to_string(),
+            timeout: Duration::from_millis(20),
+            fail_closed,
+            ..Default::default()
+        };
+        let sleep: InterceptorHandler = Arc::new(|_event| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                HookDecision::Reject {
+                    reason: "too late".to_string(),
+                }
+            })
+        });
+        registry
+            .intercept_with("tool_call_before", slow(false), sleep.clone())
+            .await;
+        registry
+            .intercept_with(
+                "tool_call_before",
+                options("confused", -1),
+                Arc::new(|_event| {
+                    Box::pin(async { HookDecision::Modify(Box::new(HookEvent::GatewayStartup)) })
+                }),
+            )
+            .await;
+
+        // Both are skipped
+        assert!(matches!(
+            registry.intercept(tool_call("bash")).await,
+            InterceptOutcome::Continue(HookEvent::ToolCallBefore { .. })
+        ));
+
+        registry
+            .intercept_with("tool_call_before", slow(true), sleep)
+            .await;
+        assert!(matches!(
+            registry.intercept(tool_call("bash")).await,
+            InterceptOutcome::Rejected { .. }
+        ));
+    }
 }
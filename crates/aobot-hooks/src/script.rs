@@ -0,0 +1,527 @@
+//! File-based hooks: external executables subscribed to events through a
+//! manifest.
+//!
+//! Each hook lives in its own subdirectory of a hook directory, next to a
+//! `hook.json` manifest:
+//!
+//! ```json
+//! {
+//!   "name": "audit",
+//!   "events": ["tool_call_before", "session_start"],
+//!   "command": "./audit.sh",
+//!   "timeout_ms": 5000
+//! }
+//! ```
+//!
+//! The command runs once per event, in the hook's directory, with the
+//! [`HookEvent`] JSON on stdin. For intercepted event types (see
+//! [`INTERCEPTED_EVENTS`]) the hook runs as an interceptor and may print a
+//! decision on stdout:
+//!
+//! ```json
+//! {"decision": "continue"}
+//! {"decision": "modify", "event": {"type": "tool_call_before", ...}}
+//! {"decision": "reject", "reason": "..."}
+//! ```
+//!
+//! No output means continue. For other event types the hook only observes
+//! and its output is ignored.
+//!
+//! Hooks are registered in the [`HookRegistry`] as `script:<name>`, so they
+//! never clash with hooks registered in code.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{debug, info, warn};
+
+use crate::events::HookEvent;
+use crate::registry::{
+    HookDecision, HookRegistry, INTERCEPTED_EVENTS, InterceptorOptions, event_type_name,
+};
+
+/// Manifest file name inside a hook directory.
+pub const MANIFEST_FILE: &str = "hook.json";
+
+/// Default number of hook processes that may run at once.
+pub const DEFAULT_MAX_CONCURRENT: usize = 4;
+
+/// Time a hook may wait for one of the concurrent slots. Not counted in
+/// its `timeout_ms`.
+const PERMIT_WAIT: Duration = Duration::from_secs(10);
+
+/// Prefix of the registry names of file-based hooks.
+pub const REGISTRY_PREFIX: &str = "script:";
+
+/// Slack on top of a hook's own time limits for the registry's interceptor
+/// timeout, so the hook's timeout and its `fail_closed` handling apply
+/// first.
+const INTERCEPTOR_TIMEOUT_HEADROOM: Duration = Duration::from_secs(1);
+
+fn default_timeout_ms() -> u64 {
+    5000
+}
+
+/// A hook's `hook.json` manifest.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HookManifest {
+    /// Hook name, used in logs and rejection messages. Defaults to the
+    /// directory name.
+    #[serde(default)]
+    pub name: String,
+    /// Human-readable description.
+    #[serde(default)]
+    pub description: String,
+    /// Event types to subscribe to ("*" for all).
+    pub events: Vec<String>,
+    /// Executable to run, relative to the hook directory or looked up in
+    /// `PATH`.
+    pub command: String,
+    /// Arguments passed to the command.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Time the command may take before it is killed.
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Interceptor priority; higher runs first.
+    #[serde(default)]
+    pub priority: i32,
+    /// Reject intercepted events when the hook fails, instead of letting
+    /// them through.
+    #[serde(default)]
+    pub fail_closed: bool,
+}
+
+/// A loaded file-based hook.
+#[derive(Debug, Clone)]
+pub struct ScriptHook {
+    /// The hook's manifest, with the name filled in.
+    pub manifest: HookManifest,
+    /// Directory containing the manifest.
+    pub dir: PathBuf,
+}
+
+/// Decision printed by a hook on stdout.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+enum ScriptDecision {
+    Continue,
+    Modify { event: Box<HookEvent> },
+    Reject { reason: String },
+}
+
+impl From<ScriptDecision> for HookDecision {
+    fn from(decision: ScriptDecision) -> Self {
+        match decision {
+            ScriptDecision::Continue => HookDecision::Continue,
+            ScriptDecision::Modify { event } => HookDecision::Modify(event),
+            ScriptDecision::Reject { reason } => HookDecision::Reject { reason },
+        }
+    }
+}
+
+/// Load hooks from multiple directories.
+///
+/// Later directories have higher priority — if a hook name appears in
+/// multiple directories, the later one wins.
+pub fn load_script_hooks(dirs: &[PathBuf]) -> Vec<ScriptHook> {
+    let mut hooks = HashMap::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.join(MANIFEST_FILE).is_file() {
+                continue;
+            }
+            match load_script_hook(&path) {
+                Ok(hook) => {
+                    debug!(hook = %hook.manifest.name, "Loaded hook");
+                    hooks.insert(hook.manifest.name.clone(), hook);
+                }
+                Err(e) => warn!(path = %path.display(), "Failed to load hook: {e:#}"),
+            }
+        }
+    }
+    hooks.into_values().collect()
+}
+
+/// Load the hook in a single directory.
+fn load_script_hook(dir: &Path) -> anyhow::Result<ScriptHook> {
+    let content = std::fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    let mut manifest: HookManifest = serde_json::from_str(&content).context("invalid manifest")?;
+    if manifest.name.is_empty() {
+        manifest.name = dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unnamed")
+            .to_string();
+    }
+    if manifest.events.is_empty() {
+        bail!("manifest subscribes to no events");
+    }
+    Ok(ScriptHook {
+        manifest,
+        dir: dir.to_path_buf(),
+    })
+}
+
+impl ScriptHook {
+    /// Run the hook for an event, waiting for one of `permits`, and parse
+    /// its decision.
+    async fn run(
+        &self,
+        event: &HookEvent,
+        permits: &Semaphore,
+    ) -> anyhow::Result<Option<HookDecision>> {
+        let _permit = tokio::time::timeout(PERMIT_WAIT, permits.acquire())
+            .await
+            .map_err(|_| anyhow::anyhow!("no free hook slot after {}s", PERMIT_WAIT.as_secs()))??;
+        let timeout = Duration::from_millis(self.manifest.timeout_ms);
+        let output = tokio::time::timeout(timeout, self.spawn(event))
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out after {}ms", timeout.as_millis()))??;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("exited with {}: {}", output.status, stderr.trim());
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if stdout.trim().is_empty() {
+            return Ok(None);
+        }
+        let decision: ScriptDecision =
+            serde_json::from_str(stdout.trim()).context("invalid decision")?;
+        Ok(Some(decision.into()))
+    }
+
+    async fn spawn(&self, event: &HookEvent) -> anyhow::Result<std::process::Output> {
+        let local = self.dir.join(&self.manifest.command);
+        let program = if local.is_file() {
+            local.as_os_str().to_owned()
+        } else {
+            self.manifest.command.clone().into()
+        };
+        let mut child = Command::new(program)
+            .args(&self.manifest.args)
+            .current_dir(&self.dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // Dropped on timeout
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to run {}", self.manifest.command))?;
+
+        let input = serde_json::to_vec(event)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // Hooks that don't read the event may exit before it is written
+            let _ = stdin.write_all(&input).await;
+        }
+        Ok(child.wait_with_output().await?)
+    }
+}
+
+/// The file-based hooks registered in a [`HookRegistry`].
+pub struct ScriptHooks {
+    registry: Arc<HookRegistry>,
+    /// Limits how many hook processes run at once.
+    permits: Arc<Semaphore>,
+    /// Registry names of the hooks currently registered.
+    loaded: Mutex<Vec<String>>,
+}
+
+impl ScriptHooks {
+    pub fn new(registry: Arc<HookRegistry>, max_concurrent: usize) -> Self {
+        Self {
+            registry,
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            loaded: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Replace the registered hooks with those found in `dirs`. Returns the
+    /// number of hooks loaded.
+    pub async fn reload(&self, dirs: &[PathBuf]) -> usize {
+        let hooks = load_script_hooks(dirs);
+        let mut loaded = self.loaded.lock().await;
+        for name in loaded.drain(..) {
+            self.registry.remove(&name).await;
+        }
+        for hook in hooks {
+            let name = format!("{REGISTRY_PREFIX}{}", hook.manifest.name);
+            self.register(&name, Arc::new(hook)).await;
+            loaded.push(name);
+        }
+        info!("Loaded {} hooks", loaded.len());
+        loaded.len()
+    }
+
+    async fn register(&self, name: &str, hook: Arc<ScriptHook>) {
+        let manifest = &hook.manifest;
+        for event_type in &manifest.events {
+            let wildcard = event_type == "*";
+            if wildcard || INTERCEPTED_EVENTS.contains(&event_type.as_str()) {
+                let options = InterceptorOptions {
+                    name: name.to_string(),
+                    priority: manifest.priority,
+                    timeout: Duration::from_millis(manifest.timeout_ms)
+                        + PERMIT_WAIT
+                        + INTERCEPTOR_TIMEOUT_HEADROOM,
+                    fail_closed: manifest.fail_closed,
+                };
+                let (hook, permits) = (hook.clone(), self.permits.clone());
+                self.registry
+                    .intercept_with(
+                        event_type,
+                        options,
+                        Arc::new(move |event| {
+                            let (hook, permits) = (hook.clone(), permits.clone());
+                            Box::pin(async move { intercept(&hook, &event, &permits).await })
+                        }),
+                    )
+                    .await;
+            }
+            if wildcard || !INTERCEPTED_EVENTS.contains(&event_type.as_str()) {
+                let (hook, permits) = (hook.clone(), self.permits.clone());
+                self.registry
+                    .on_named(
+                        event_type,
+                        name,
+                        Arc::new(move |event| {
+                            let (hook, permits) = (hook.clone(), permits.clone());
+                            Box::pin(async move { observe(&hook, &event, &permits).await })
+                        }),
+                    )
+                    .await;
+            }
+        }
+    }
+}
+
+async fn intercept(hook: &ScriptHook, event: &HookEvent, permits: &Semaphore) -> HookDecision {
+    match hook.run(event, permits).await {
+        Ok(decision) => decision.unwrap_or(HookDecision::Continue),
+        Err(e) => {
+            warn!(hook = %hook.manifest.name, "Hook failed: {e:#}");
+            if hook.manifest.fail_closed {
+                HookDecision::Reject {
+                    reason: format!("hook failed: {e:#}"),
+                }
+            } else {
+                HookDecision::Continue
+            }
+        }
+    }
+}
+
+async fn observe(hook: &ScriptHook, event: &HookEvent, permits: &Semaphore) {
+    // Intercepted events already reached a wildcard hook as an interceptor
+    if INTERCEPTED_EVENTS.contains(&event_type_name(event)) {
+        return;
+    }
+    if let Err(e) = hook.run(event, permits).await {
+        warn!(hook = %hook.manifest.name, "Hook failed: {e:#}");
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::registry::InterceptOutcome;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn write_hook(root: &Path, name: &str, manifest: serde_json::Value, script: &str) {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+        let path = dir.join("hook.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn tool_call() -> HookEvent {
+        HookEvent::ToolCallBefore {
+            session_key: "main".to_string(),
+            agent_id: "default".to_string(),
+            tool_name: "bash".to_string(),
+            params: serde_json::json!({ "command": "ls" }),
+        }
+    }
+
+    #[test]
+    fn test_load_script_hooks() {
+        let root = tempfile::tempdir().unwrap();
+        write_hook(
+            root.path(),
+            "audit",
+            serde_json::json!({ "events": ["session_start"], "command": "./hook.sh" }),
+            "exit 0",
+        );
+        write_hook(
+            root.path(),
+            "broken",
+            serde_json::json!({ "command": "./hook.sh" }),
+            "exit 0",
+        );
+
+        let hooks = load_script_hooks(&[root.path().to_path_buf()]);
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].manifest.name, "audit");
+        assert_eq!(hooks[0].manifest.timeout_ms, 5000);
+    }
+
+    #[tokio::test]
+    async fn test_script_decisions() {
+        let root = tempfile::tempdir().unwrap();
+        // Rewrites the command it was given
+        write_hook(
+            root.path(),
+            "rewrite",
+            serde_json::json!({
+                "events": ["tool_call_before"],
+                "command": "./hook.sh",
+                "priority": 1,
+            }),
+            r#"sed 's/"ls"/"ls -la"/' | sed 's/^/{"decision":"modify","event":/; s/$/}/'"#,
+        );
+        write_hook(
+            root.path(),
+            "guard",
+            serde_json::json!({ "events": ["tool_call_before"], "command": "./hook.sh" }),
+            r#"grep -q '"ls -la"' && echo '{"decision":"reject","reason":"listing"}'"#,
+        );
+
+        let registry = Arc::new(HookRegistry::new());
+        // A hook registered in code under the same name as a script
+        registry
+            .intercept_with(
+                "tool_call_before",
+                InterceptorOptions {
+                    name: "guard".to_string(),
+                    priority: -1,
+                    ..Default::default()
+                },
+                Arc::new(|_| {
+                    Box::pin(async {
+                        HookDecision::Reject {
+                            reason: "native".to_string(),
+                        }
+                    })
+                }),
+            )
+            .await;
+        let hooks = ScriptHooks::new(registry.clone(), DEFAULT_MAX_CONCURRENT);
+        assert_eq!(hooks.reload(&[root.path().to_path_buf()]).await, 2);
+
+        match registry.intercept(tool_call()).await {
+            InterceptOutcome::Rejected {
+                interceptor,
+                reason,
+            } => {
+                assert_eq!(interceptor, "script:guard");
+                assert_eq!(reason, "listing");
+            }
+            other => panic!("expected a rejection, got {other:?}"),
+        }
+
+        // Reloading drops hooks that were removed from disk, but not the
+        // one registered in code
+        std::fs::remove_dir_all(root.path().join("guard")).unwrap();
+        assert_eq!(hooks.reload(&[root.path().to_path_buf()]).await, 1);
+        match registry.intercept(tool_call()).await {
+            InterceptOutcome::Rejected {
+                interceptor,
+                reason,
+            } => {
+                assert_eq!(interceptor, "guard");
+                assert_eq!(reason, "native");
+            }
+            other => panic!("expected a rejection, got {other:?}"),
+        }
+        registry.remove("guard").await;
+        let InterceptOutcome::Continue(HookEvent::ToolCallBefore { params, .. }) =
+            registry.intercept(tool_call()).await
+        else {
+            panic!("expected the call to continue");
+        };
+        assert_eq!(params["command"], "ls -la");
+    }
+
+    #[tokio::test]
+    async fn test_script_failures() {
+        let root = tempfile::tempdir().unwrap();
+        write_hook(
+            root.path(),
+            "slow",
+            serde_json::json!({
+                "events": ["tool_call_before"],
+                "command": "./hook.sh",
+                "timeout_ms": 100,
+                "fail_closed": true,
+            }),
+            "sleep 5",
+        );
+        let registry = Arc::new(HookRegistry::new());
+        let hooks = ScriptHooks::new(registry.clone(), 1);
+        hooks.reload(&[root.path().to_path_buf()]).await;
+        assert!(matches!(
+            registry.intercept(tool_call()).await,
+            InterceptOutcome::Rejected { .. }
+        ));
+
+        let hook = load_script_hooks(&[root.path().to_path_buf()]).remove(0);
+        let crashing = ScriptHook {
+            manifest: HookManifest {
+                args: vec!["-c".to_string(), "echo oops >&2; exit 3".to_string()],
+                command: "sh".to_string(),
+                ..hook.manifest
+            },
+            ..hook
+        };
+        let err = crashing
+            .run(&tool_call(), &Semaphore::new(1))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("oops"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_waiting_for_a_slot_is_not_timed() {
+        let root = tempfile::tempdir().unwrap();
+        write_hook(
+            root.path(),
+            "guard",
+            serde_json::json!({
+                "events": ["tool_call_before"],
+                "command": "./hook.sh",
+                "timeout_ms": 1000,
+            }),
+            r#"sleep 0.6; echo '{"decision":"reject","reason":"no"}'"#,
+        );
+        let registry = Arc::new(HookRegistry::new());
+        let hooks = ScriptHooks::new(registry.clone(), 1);
+        hooks.reload(&[root.path().to_path_buf()]).await;
+
+        // The second call waits for the first, outlasting `timeout_ms` overall
+        let (first, second) = tokio::join!(
+            registry.intercept(tool_call()),
+            registry.intercept(tool_call())
+        );
+        for outcome in [first, second] {
+            assert!(
+                matches!(outcome, InterceptOutcome::Rejected { .. }),
+                "{outcome:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,530 @@
+//! Outbound webhooks: a built-in hook handler that POSTs events to
+//! configured URLs.
+//!
+//! Every matching event is queued in SQLite for each webhook and sent by a
+//! background task as the JSON-serialized [`HookEvent`], with headers:
+//!
+//! - `X-Aobot-Event`: the event type
+//! - `X-Aobot-Delivery`: the delivery ID, stable across retries
+//! - `X-Aobot-Timestamp`: Unix time of the attempt, in seconds
+//! - `X-Aobot-Signature`: `sha256=<hex>`, the HMAC-SHA256 of
+//!   `"{timestamp}.{body}"` keyed with the webhook's secret (only when a
+//!   secret is configured)
+//!
+//! Any 2xx response counts as delivered. Other responses and connection
+//! errors are retried with exponential backoff until the webhook's
+//! `max_attempts` is reached. Pending deliveries survive restarts.
+//!
+//! Each webhook is delivered to by its own task, in order, so a slow or
+//! unreachable endpoint doesn't hold up the others.
+
+pub mod store;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use aobot_config::WebhookConfig;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+use crate::events::HookEvent;
+use crate::registry::{HookRegistry, event_type_name};
+pub use store::WebhookStore;
+
+/// Delay before the first retry; doubled on each further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+
+/// Longest delay between retries.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(600);
+
+/// How often the queue is checked when nothing is pending.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Deliveries fetched from the queue at a time.
+const DELIVERY_BATCH: usize = 50;
+
+/// State of a webhook delivery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Waiting for its first attempt or a retry.
+    Pending,
+    /// The webhook accepted the event.
+    Delivered,
+    /// Every attempt failed.
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Parse a stored status; unknown values are treated as failed.
+    fn from_str_lossy(s: &str) -> Self {
+        match s {
+            "pending" => Self::Pending,
+            "delivered" => Self::Delivered,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// An event queued for, or sent to, a webhook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    /// Delivery ID.
+    pub id: i64,
+    /// Name of the webhook.
+    pub webhook: String,
+    /// Type of the event, e.g. "session_start".
+    pub event_type: String,
+    /// Serialized event sent as the request body.
+    #[serde(skip)]
+    pub payload: String,
+    /// Delivery state.
+    pub status: DeliveryStatus,
+    /// Attempts made so far.
+    pub attempts: u32,
+    /// When the next attempt is due (pending deliveries only).
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    /// Error from the last failed attempt.
+    pub last_error: Option<String>,
+    /// When the event was queued.
+    pub created_at: DateTime<Utc>,
+    /// When the delivery succeeded or was given up on.
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Sends hook events to the configured webhooks.
+pub struct WebhookSink {
+    webhooks: HashMap<String, WebhookConfig>,
+    store: WebhookStore,
+    client: reqwest::Client,
+    /// Pending deliveries kept at most; the oldest are dropped beyond this.
+    queue_limit: usize,
+    /// Wakes a webhook's delivery task when an event is queued for it.
+    wake: HashMap<String, Notify>,
+}
+
+impl WebhookSink {
+    pub fn new(webhooks: Vec<WebhookConfig>, store: WebhookStore, queue_limit: usize) -> Self {
+        Self {
+            wake: webhooks
+                .iter()
+                .map(|webhook| (webhook.name.clone(), Notify::new()))
+                .collect(),
+            webhooks: webhooks
+                .into_iter()
+                .map(|webhook| (webhook.name.clone(), webhook))
+                .collect(),
+            store,
+            client: reqwest::Client::new(),
+            queue_limit: queue_limit.max(1),
+        }
+    }
+
+    /// Subscribe the sink to every event in `registry`.
+    pub async fn register(self: &Arc<Self>, registry: &HookRegistry) {
+        let sink = self.clone();
+        registry
+            .on(
+                "*",
+                Arc::new(move |event| {
+                    let sink = sink.clone();
+                    Box::pin(async move { sink.enqueue(&event) })
+                }),
+            )
+            .await;
+    }
+
+    /// Queue an event for every webhook subscribed to its type.
+    pub fn enqueue(&self, event: &HookEvent) {
+        let event_type = event_type_name(event);
+        let matching: Vec<&WebhookConfig> = self
+            .webhooks
+            .values()
+            .filter(|w| w.events.is_empty() || w.events.iter().any(|e| e == "*" || e == event_type))
+            .collect();
+        if matching.is_empty() {
+            return;
+        }
+
+        let payload = match serde_json::to_string(event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize {event_type} event for webhooks: {e}");
+                return;
+            }
+        };
+        for webhook in matching {
+            match self
+                .store
+                .enqueue(&webhook.name, event_type, &payload, self.queue_limit)
+            {
+                Ok(0) => {}
+                Ok(dropped) => warn!("Webhook queue full, dropped {dropped} oldest deliveries"),
+                Err(e) => warn!(webhook = %webhook.name, "Failed to queue webhook delivery: {e}"),
+            }
+            if let Some(wake) = self.wake.get(&webhook.name) {
+                wake.notify_one();
+            }
+        }
+    }
+
+    /// Start delivering queued events in the background, one task per
+    /// webhook. Aborting the returned task stops them all.
+    pub fn start(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let sink = self.clone();
+        tokio::spawn(async move {
+            let names: Vec<&str> = sink.webhooks.keys().map(String::as_str).collect();
+            match sink
+                .store
+                .fail_pending_except(&names, "webhook is no longer configured")
+            {
+                Ok(0) => {}
+                Ok(failed) => warn!("Gave up on {failed} deliveries to removed webhooks"),
+                Err(e) => warn!("Failed to clean up webhook queue: {e}"),
+            }
+
+            // Dropping the set aborts the tasks
+            let mut tasks = tokio::task::JoinSet::new();
+            for name in sink.webhooks.keys() {
+                let (sink, name) = (sink.clone(), name.clone());
+                tasks.spawn(async move { sink.run(&sink.webhooks[&name]).await });
+            }
+            while tasks.join_next().await.is_some() {}
+        })
+    }
+
+    /// Deliver a webhook's queued events, forever.
+    async fn run(&self, webhook: &WebhookConfig) {
+        let wake = &self.wake[&webhook.name];
+        let mut store_failures = 0;
+        loop {
+            let next = match self.deliver_due(webhook).await {
+                Ok(()) => self.store.next_attempt_at(&webhook.name),
+                Err(e) => Err(e),
+            };
+            match next {
+                Ok(next) => {
+                    store_failures = 0;
+                    let wait = match next {
+                        Some(at) => (at - Utc::now()).to_std().unwrap_or_default(),
+                        None => IDLE_POLL_INTERVAL,
+                    };
+                    tokio::select! {
+                        _ = wake.notified() => {}
+                        _ = tokio::time::sleep(wait) => {}
+                    }
+                }
+                Err(e) => {
+                    // A delivery whose attempt wasn't recorded is still due;
+                    // retrying at once would resend it in a tight loop
+                    store_failures += 1;
+                    let delay = retry_delay(store_failures);
+                    warn!(
+                        webhook = %webhook.name,
+                        "Webhook queue unavailable, retrying in {}s: {e}",
+                        delay.as_secs()
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+
+    /// List recent deliveries, newest first.
+    pub fn deliveries(
+        &self,
+        webhook: Option<&str>,
+        status: Option<DeliveryStatus>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, String> {
+        self.store
+            .list(webhook, status, limit)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Count deliveries by status.
+    pub fn counts(&self, webhook: Option<&str>) -> Result<BTreeMap<String, i64>, String> {
+        self.store.counts(webhook).map_err(|e| e.to_string())
+    }
+
+    /// Attempt every delivery to `webhook` that is due, in order. Fails if
+    /// the queue can't be read or an attempt can't be recorded.
+    async fn deliver_due(&self, webhook: &WebhookConfig) -> anyhow::Result<()> {
+        loop {
+            let due = self.store.due(&webhook.name, Utc::now(), DELIVERY_BATCH)?;
+            if due.is_empty() {
+                return Ok(());
+            }
+            for delivery in due {
+                self.deliver(webhook, &delivery).await?;
+            }
+        }
+    }
+
+    /// Attempt a delivery and record the outcome.
+    async fn deliver(
+        &self,
+        webhook: &WebhookConfig,
+        delivery: &WebhookDelivery,
+    ) -> anyhow::Result<()> {
+        let (status, next_attempt_at, error) = match self.send(webhook, delivery).await {
+            Ok(()) => {
+                debug!(webhook = %webhook.name, id = delivery.id, "Webhook delivered");
+                (DeliveryStatus::Delivered, None, None)
+            }
+            Err(error) if delivery.attempts + 1 >= webhook.max_attempts => {
+                warn!(webhook = %webhook.name, id = delivery.id, "Webhook delivery failed, giving up: {error}");
+                (DeliveryStatus::Failed, None, Some(error))
+            }
+            Err(error) => {
+                let delay = retry_delay(delivery.attempts + 1);
+                warn!(
+                    webhook = %webhook.name,
+                    id = delivery.id,
+                    "Webhook delivery failed, retrying in {}s: {error}",
+                    delay.as_secs()
+                );
+                let next = Utc::now() + chrono::Duration::from_std(delay).unwrap_or_default();
+                (DeliveryStatus::Pending, Some(next), Some(error))
+            }
+        };
+        self.store
+            .record_attempt(delivery.id, status, next_attempt_at, error.as_deref())
+    }
+
+    /// POST a delivery to its webhook.
+    async fn send(
+        &self,
+        webhook: &WebhookConfig,
+        delivery: &WebhookDelivery,
+    ) -> Result<(), String> {
+        let timestamp = Utc::now().timestamp();
+        let mut request = self
+            .client
+            .post(&webhook.url)
+            .timeout(Duration::from_millis(webhook.timeout_ms))
+            .header("Content-Type", "application/json")
+            .header("X-Aobot-Event", &delivery.event_type)
+            .header("X-Aobot-Delivery", delivery.id.to_string())
+            .header("X-Aobot-Timestamp", timestamp.to_string());
+        if let Some(secret) = &webhook.secret {
+            request = request.header(
+                "X-Aobot-Signature",
+                sign(secret, timestamp, &delivery.payload),
+            );
+        }
+
+        let response = request
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(format!("HTTP {status}"))
+        }
+    }
+}
+
+/// The `X-Aobot-Signature` header value for a request body sent at
+/// `timestamp`.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delay before attempt `attempt + 1`, after `attempt` failures.
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(RETRY_MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn webhook(name: &str, url: &str, events: &[&str]) -> WebhookConfig {
+        WebhookConfig {
+            name: name.to_string(),
+            url: url.to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            secret: Some("s3cret".to_string()),
+            max_attempts: 3,
+            timeout_ms: 1000,
+        }
+    }
+
+    fn session_start() -> HookEvent {
+        HookEvent::SessionStart {
+            session_key: "main".to_string(),
+            agent_id: "default".to_string(),
+        }
+    }
+
+    /// Answer each connection with the next status, returning the requests.
+    async fn serve(statuses: &[u16]) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let statuses = statuses.to_vec();
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for status in statuses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                // Headers and the small body arrive before we answer
+                while !String::from_utf8_lossy(&request).contains("\"type\"") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let response = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+                requests.push(String::from_utf8_lossy(&request).to_lowercase());
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_sign() {
+        // echo -n '1700000000.{}' | openssl dgst -sha256 -hmac s3cret
+        assert_eq!(
+            sign("s3cret", 1_700_000_000, "{}"),
+            "sha256=97926816e98fbb41ccb1673225ff29a2f35369099990e1b1561651e7bd097ebf"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(8));
+        assert_eq!(retry_delay(30), RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_enqueue_filters_events() {
+        let sink = WebhookSink::new(
+            vec![
+                webhook("sessions", "http://localhost/", &["session_start"]),
+                webhook("all", "http://localhost/", &[]),
+            ],
+            WebhookStore::open_in_memory().unwrap(),
+            100,
+        );
+        sink.enqueue(&session_start());
+        sink.enqueue(&HookEvent::GatewayStartup);
+
+        let counts = |name| sink.counts(Some(name)).unwrap().get("pending").copied();
+        assert_eq!(counts("sessions"), Some(1));
+        assert_eq!(counts("all"), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_signed_and_retried() {
+        let (url, server) = serve(&[500, 200]).await;
+        let sink = Arc::new(WebhookSink::new(
+            vec![webhook("audit", &url, &[])],
+            WebhookStore::open_in_memory().unwrap(),
+            100,
+        ));
+        sink.enqueue(&session_start());
+
+        let webhook = &sink.webhooks["audit"];
+        sink.deliver_due(webhook).await.unwrap();
+        let [delivery] = sink.deliveries(None, None, 10).unwrap().try_into().unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(
+            delivery.last_error.as_deref(),
+            Some("HTTP 500 Internal Server Error")
+        );
+
+        // Retry now instead of after the backoff
+        sink.deliver(webhook, &delivery).await.unwrap();
+        let [delivery] = sink.deliveries(None, None, 10).unwrap().try_into().unwrap();
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 2);
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        let request = &requests[1];
+        assert!(request.contains("x-aobot-event: session_start"));
+        assert!(request.contains(&format!("x-aobot-delivery: {}", delivery.id)));
+        let timestamp: i64 = request
+            .lines()
+            .find_map(|l| l.strip_prefix("x-aobot-timestamp: "))
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        let body = serde_json::to_string(&session_start()).unwrap();
+        assert!(request.contains(&format!(
+            "x-aobot-signature: {}",
+            sign("s3cret", timestamp, &body)
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_slow_webhook_does_not_block_others() {
+        // Accepts connections but never answers
+        let stalled = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stalled_url = format!("http://{}/hook", stalled.local_addr().unwrap());
+        let _stalled = tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            loop {
+                sockets.push(stalled.accept().await.unwrap());
+            }
+        });
+        let (url, server) = serve(&[200, 200]).await;
+
+        let store = WebhookStore::open_in_memory().unwrap();
+        store
+            .enqueue("removed", "session_start", "{}", 100)
+            .unwrap();
+        let mut slow = webhook("slow", &stalled_url, &[]);
+        slow.timeout_ms = 30_000;
+        let sink = Arc::new(WebhookSink::new(
+            vec![slow, webhook("fast", &url, &[])],
+            store,
+            100,
+        ));
+        // Queued for both, with "slow" first in line
+        sink.enqueue(&session_start());
+        sink.enqueue(&session_start());
+        let handle = sink.start();
+
+        let requests = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("fast webhook was held up")
+            .unwrap();
+        assert_eq!(requests.len(), 2);
+        let counts = sink.counts(Some("slow")).unwrap();
+        assert_eq!(counts.get("pending"), Some(&2));
+        let counts = sink.counts(Some("removed")).unwrap();
+        assert_eq!(counts.get("failed"), Some(&1));
+        handle.abort();
+    }
+}
@@ -0,0 +1,339 @@
+//! SQLite-backed webhook delivery queue.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{Connection, Row};
+
+use super::{DeliveryStatus, WebhookDelivery};
+
+/// Columns selected for a `WebhookDelivery`, in `row_to_delivery` order.
+const DELIVERY_COLUMNS: &str = "id, webhook, event_type, payload, status, attempts, \
+     next_attempt_at, last_error, created_at, finished_at";
+
+/// Finished (delivered or failed) deliveries kept; older ones are pruned.
+const MAX_FINISHED_DELIVERIES: i64 = 1000;
+
+/// Persistent queue and history of webhook deliveries.
+pub struct WebhookStore {
+    conn: Mutex<Connection>,
+}
+
+impl WebhookStore {
+    /// Open or create a webhook store.
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        Self::init(conn)
+    }
+
+    /// Open an in-memory webhook store (for testing).
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 webhook TEXT NOT NULL,
+                 event_type TEXT NOT NULL,
+                 payload TEXT NOT NULL,
+                 status TEXT NOT NULL,
+                 attempts INTEGER NOT NULL DEFAULT 0,
+                 next_attempt_at TEXT,
+                 last_error TEXT,
+                 created_at TEXT NOT NULL,
+                 finished_at TEXT
+             );
+             CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_status
+                 ON webhook_deliveries(status, next_attempt_at);",
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Queue an event for a webhook, due immediately. Returns the number of
+    /// older pending deliveries dropped to keep at most `limit` pending.
+    pub fn enqueue(
+        &self,
+        webhook: &str,
+        event_type: &str,
+        payload: &str,
+        limit: usize,
+    ) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let now = timestamp(Utc::now());
+        conn.execute(
+            "INSERT INTO webhook_deliveries
+                 (webhook, event_type, payload, status, next_attempt_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            rusqlite::params![
+                webhook,
+                event_type,
+                payload,
+                DeliveryStatus::Pending.as_str(),
+                now
+            ],
+        )?;
+        let dropped = conn.execute(
+            "DELETE FROM webhook_deliveries WHERE status = ?1 AND id NOT IN
+                 (SELECT id FROM webhook_deliveries WHERE status = ?1 ORDER BY id DESC LIMIT ?2)",
+            rusqlite::params![DeliveryStatus::Pending.as_str(), limit as i64],
+        )?;
+        Ok(dropped)
+    }
+
+    /// Pending deliveries to a webhook due at `now`, oldest first.
+    pub fn due(
+        &self,
+        webhook: &str,
+        now: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries
+             WHERE status = ?1 AND webhook = ?2 AND next_attempt_at <= ?3
+             ORDER BY next_attempt_at, id LIMIT ?4"
+        ))?;
+        let deliveries = stmt
+            .query_map(
+                rusqlite::params![
+                    DeliveryStatus::Pending.as_str(),
+                    webhook,
+                    timestamp(now),
+                    limit as i64
+                ],
+                row_to_delivery,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(deliveries)
+    }
+
+    /// When the next pending delivery to a webhook is due.
+    pub fn next_attempt_at(&self, webhook: &str) -> Result<Option<DateTime<Utc>>> {
+        let conn = self.conn.lock().unwrap();
+        let next: Option<String> = conn.query_row(
+            "SELECT MIN(next_attempt_at) FROM webhook_deliveries
+             WHERE status = ?1 AND webhook = ?2",
+            rusqlite::params![DeliveryStatus::Pending.as_str(), webhook],
+            |row| row.get(0),
+        )?;
+        Ok(next.and_then(|s| s.parse().ok()))
+    }
+
+    /// Mark pending deliveries to webhooks other than `webhooks` as failed
+    /// with `error`. Returns the number of deliveries given up on.
+    pub fn fail_pending_except(&self, webhooks: &[&str], error: &str) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let failed = conn.execute(
+            "UPDATE webhook_deliveries
+             SET status = ?1, next_attempt_at = NULL, last_error = ?2, finished_at = ?3
+             WHERE status = ?4 AND webhook NOT IN (SELECT value FROM json_each(?5))",
+            rusqlite::params![
+                DeliveryStatus::Failed.as_str(),
+                error,
+                timestamp(Utc::now()),
+                DeliveryStatus::Pending.as_str(),
+                serde_json::to_string(webhooks)?,
+            ],
+        )?;
+        Ok(failed)
+    }
+
+    /// Record a delivery attempt: `Pending` with the time of the next
+    /// attempt, or a final `Delivered`/`Failed`.
+    ///
+    /// Finished deliveries beyond the newest `MAX_FINISHED_DELIVERIES` are
+    /// pruned.
+    pub fn record_attempt(
+        &self,
+        id: i64,
+        status: DeliveryStatus,
+        next_attempt_at: Option<DateTime<Utc>>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let finished_at = (status != DeliveryStatus::Pending).then(|| timestamp(Utc::now()));
+        conn.execute(
+            "UPDATE webhook_deliveries
+             SET status = ?1, attempts = attempts + 1, next_attempt_at = ?2,
+                 last_error = ?3, finished_at = ?4
+             WHERE id = ?5",
+            rusqlite::params![
+                status.as_str(),
+                next_attempt_at.map(timestamp),
+                error,
+                finished_at,
+                id,
+            ],
+        )?;
+        if finished_at.is_some() {
+            conn.execute(
+                "DELETE FROM webhook_deliveries WHERE status != ?1 AND id NOT IN
+                     (SELECT id FROM webhook_deliveries WHERE status != ?1
+                      ORDER BY id DESC LIMIT ?2)",
+                rusqlite::params![DeliveryStatus::Pending.as_str(), MAX_FINISHED_DELIVERIES],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// List recent deliveries, newest first, optionally for a single
+    /// webhook or status.
+    pub fn list(
+        &self,
+        webhook: Option<&str>,
+        status: Option<DeliveryStatus>,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {DELIVERY_COLUMNS} FROM webhook_deliveries
+             WHERE (?1 IS NULL OR webhook = ?1) AND (?2 IS NULL OR status = ?2)
+             ORDER BY id DESC LIMIT ?3"
+        ))?;
+        let deliveries = stmt
+            .query_map(
+                rusqlite::params![webhook, status.map(DeliveryStatus::as_str), limit as i64],
+                row_to_delivery,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(deliveries)
+    }
+
+    /// Count deliveries by status, optionally for a single webhook.
+    pub fn counts(&self, webhook: Option<&str>) -> Result<BTreeMap<String, i64>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT status, COUNT(*) FROM webhook_deliveries
+             WHERE ?1 IS NULL OR webhook = ?1
+             GROUP BY status",
+        )?;
+        let counts = stmt
+            .query_map(rusqlite::params![webhook], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(counts)
+    }
+}
+
+/// Fixed-width RFC3339 timestamp, so stored times compare as strings.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Map a row selected with `DELIVERY_COLUMNS` to a `WebhookDelivery`.
+fn row_to_delivery(row: &Row<'_>) -> rusqlite::Result<WebhookDelivery> {
+    Ok(WebhookDelivery {
+        id: row.get(0)?,
+        webhook: row.get(1)?,
+        event_type: row.get(2)?,
+        payload: row.get(3)?,
+        status: DeliveryStatus::from_str_lossy(&row.get::<_, String>(4)?),
+        attempts: row.get(5)?,
+        next_attempt_at: row
+            .get::<_, Option<String>>(6)?
+            .and_then(|s| s.parse().ok()),
+        last_error: row.get(7)?,
+        created_at: row
+            .get::<_, String>(8)?
+            .parse()
+            .unwrap_or_else(|_| Utc::now()),
+        finished_at: row
+            .get::<_, Option<String>>(9)?
+            .and_then(|s| s.parse().ok()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_queue_is_bounded() {
+        let store = WebhookStore::open_in_memory().unwrap();
+        for i in 0..3 {
+            let dropped = store
+                .enqueue("audit", "session_start", &format!("{{\"n\":{i}}}"), 2)
+                .unwrap();
+            assert_eq!(dropped, usize::from(i == 2));
+        }
+
+        let due = store.due("audit", Utc::now(), 10).unwrap();
+        let payloads: Vec<&str> = due.iter().map(|d| d.payload.as_str()).collect();
+        assert_eq!(payloads, vec!["{\"n\":1}", "{\"n\":2}"]);
+        assert_eq!(store.counts(None).unwrap()["pending"], 2);
+    }
+
+    #[test]
+    fn test_record_attempts() {
+        let store = WebhookStore::open_in_memory().unwrap();
+        store.enqueue("audit", "session_start", "{}", 10).unwrap();
+        store.enqueue("other", "session_end", "{}", 10).unwrap();
+        let [first] = store
+            .due("audit", Utc::now(), 10)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let [second] = store
+            .due("other", Utc::now(), 10)
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        // A retry is no longer due until its next attempt
+        let retry_at = Utc::now() + chrono::Duration::minutes(1);
+        store
+            .record_attempt(
+                first.id,
+                DeliveryStatus::Pending,
+                Some(retry_at),
+                Some("HTTP 500"),
+            )
+            .unwrap();
+        store
+            .record_attempt(second.id, DeliveryStatus::Delivered, None, None)
+            .unwrap();
+        assert!(store.due("audit", Utc::now(), 10).unwrap().is_empty());
+        let next = store.next_attempt_at("audit").unwrap().unwrap();
+        assert_eq!(next.timestamp_millis(), retry_at.timestamp_millis());
+        assert_eq!(store.next_attempt_at("other").unwrap(), None);
+
+        let [retried] = store
+            .list(Some("audit"), None, 10)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(retried.attempts, 1);
+        assert_eq!(retried.last_error.as_deref(), Some("HTTP 500"));
+        let delivered = store
+            .list(None, Some(DeliveryStatus::Delivered), 10)
+            .unwrap();
+        assert_eq!(delivered.len(), 1);
+        assert!(delivered[0].finished_at.is_some());
+    }
+
+    #[test]
+    fn test_fail_pending_except() {
+        let store = WebhookStore::open_in_memory().unwrap();
+        store.enqueue("audit", "session_start", "{}", 10).unwrap();
+        store.enqueue("removed", "session_start", "{}", 10).unwrap();
+
+        assert_eq!(store.fail_pending_except(&["audit"], "gone").unwrap(), 1);
+        assert_eq!(store.due("audit", Utc::now(), 10).unwrap().len(), 1);
+        let [failed] = store
+            .list(Some("removed"), None, 10)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(failed.status, DeliveryStatus::Failed);
+        assert_eq!(failed.last_error.as_deref(), Some("gone"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Approximate nearest-neighbor index for memory vectors.
+//!
+//! An inverted-file (IVF) index: chunk embeddings are clustered with
+//! spherical k-means, each chunk is assigned to its nearest centroid
+//! ("list"), and a query only scans the lists whose centroids are closest
+//! to it. Centroids are small enough to keep in memory; the list
+//! assignment lives next to each chunk in SQLite, so the index survives
+//! restarts without being rebuilt.
+
+/// Number of k-means iterations when training.
+const TRAIN_ITERATIONS: usize = 8;
+
+/// Training vectors sampled per list.
+pub const TRAIN_SAMPLES_PER_LIST: usize = 32;
+
+/// Trained IVF centroids.
+#[derive(Debug, Clone)]
+pub struct IvfIndex {
+    centroids: Vec<Vec<f32>>,
+}
+
+impl IvfIndex {
+    /// Create an index from existing centroids.
+    pub fn from_centroids(centroids: Vec<Vec<f32>>) -> Self {
+        Self { centroids }
+    }
+
+    /// Number of lists to use for `count` vectors (about √n, at least 1).
+    pub fn lists_for(count: usize) -> usize {
+        ((count as f64).sqrt() as usize / 2).clamp(1, 4096)
+    }
+
+    /// Train centroids on a sample of vectors with spherical k-means.
+    ///
+    /// Vectors whose size differs from the first vector are ignored.
+    /// Returns `None` if there is nothing to train on.
+    pub fn train(sample: &[Vec<f32>], n_lists: usize) -> Option<Self> {
+        let dimensions = sample.first()?.len();
+        let vectors: Vec<Vec<f32>> = sample
+            .iter()
+            .filter(|v| v.len() == dimensions)
+            .map(|v| normalized(v))
+            .collect();
+        let n_lists = n_lists.min(vectors.len()).max(1);
+
+        // Evenly spaced initial centroids; the sample is already in random order
+        let step = vectors.len() / n_lists;
+        let mut centroids: Vec<Vec<f32>> =
+            (0..n_lists).map(|i| vectors[i * step].clone()).collect();
+
+        for _ in 0..TRAIN_ITERATIONS {
+            let mut sums = vec![vec![0.0f32; dimensions]; n_lists];
+            let mut counts = vec![0usize; n_lists];
+            for v in &vectors {
+                let list = nearest(&centroids, v);
+                counts[list] += 1;
+                for (s, x) in sums[list].iter_mut().zip(v) {
+                    *s += x;
+                }
+            }
+            for (list, sum) in sums.into_iter().enumerate() {
+                // Keep the previous centroid for empty lists
+                if counts[list] > 0 {
+                    centroids[list] = normalized(&sum);
+                }
+            }
+        }
+
+        Some(Self { centroids })
+    }
+
+    /// Centroid vectors, indexed by list.
+    pub fn centroids(&self) -> &[Vec<f32>] {
+        &self.centroids
+    }
+
+    /// Vector size the index was trained on.
+    pub fn dimensions(&self) -> usize {
+        self.centroids.first().map_or(0, Vec::len)
+    }
+
+    /// List a vector belongs to, or `None` if its size doesn't match.
+    pub fn assign(&self, vector: &[f32]) -> Option<usize> {
+        (vector.len() == self.dimensions()).then(|| nearest(&self.centroids, vector))
+    }
+
+    /// The `n_probe` lists closest to `query`, best first.
+    pub fn probe(&self, query: &[f32], n_probe: usize) -> Vec<usize> {
+        if query.len() != self.dimensions() {
+            return vec![];
+        }
+        let mut scored: Vec<(usize, f32)> = self
+            .centroids
+            .iter()
+            .enumerate()
+            .map(|(list, c)| (list, dot(c, query)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored
+            .into_iter()
+            .take(n_probe)
+            .map(|(list, _)| list)
+            .collect()
+    }
+
+    /// Number of lists to scan per query (about √lists, at least 8).
+    pub fn default_probes(&self) -> usize {
+        ((self.centroids.len() as f64).sqrt().ceil() as usize).max(8)
+    }
+}
+
+/// Index of the centroid with the highest dot product.
+fn nearest(centroids: &[Vec<f32>], vector: &[f32]) -> usize {
+    centroids
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i, dot(c, vector)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(0, |(i, _)| i)
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn normalized(v: &[f32]) -> Vec<f32> {
+    let norm = dot(v, v).sqrt();
+    if norm == 0.0 {
+        return v.to_vec();
+    }
+    v.iter().map(|x| x / norm).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points scattered around the given axis directions.
+    fn clustered(axes: &[usize], per_axis: usize) -> Vec<Vec<f32>> {
+        let mut out = Vec::new();
+        for i in 0..per_axis {
+            for &axis in axes {
+                let mut v = vec![0.05 * (i % 3) as f32; 4];
+                v[axis] = 1.0;
+                out.push(v);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_train_separates_clusters() {
+        let sample = clustered(&[0, 1, 2], 10);
+        let index = IvfIndex::train(&sample, 3).unwrap();
+        assert_eq!(index.centroids().len(), 3);
+        assert_eq!(index.dimensions(), 4);
+
+        let a = index.assign(&[1.0, 0.0, 0.0, 0.0]).unwrap();
+        let b = index.assign(&[0.0, 1.0, 0.0, 0.0]).unwrap();
+        let c = index.assign(&[0.0, 0.0, 1.0, 0.0]).unwrap();
+        assert!(a != b && b != c && a != c);
+
+        assert_eq!(index.probe(&[0.9, 0.1, 0.0, 0.0], 1), vec![a]);
+        assert_eq!(index.assign(&[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_train_edge_cases() {
+        assert!(IvfIndex::train(&[], 4).is_none());
+        // More lists than vectors
+        let index = IvfIndex::train(&[vec![1.0, 0.0]], 4).unwrap();
+        assert_eq!(index.centroids().len(), 1);
+        assert_eq!(IvfIndex::lists_for(0), 1);
+        assert_eq!(IvfIndex::lists_for(40_000), 100);
+    }
+}
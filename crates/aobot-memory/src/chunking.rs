@@ -1,6 +1,17 @@
-//! Markdown-aware text chunking for memory indexing.
+//! Text chunking for memory indexing.
+//!
+//! Each supported file type has a [`Chunker`], selected by file extension
+//! with [`chunker_for_path`]:
+//!
+//! - Markdown: split at headings
+//! - Source code: split at top-level definitions (with their doc comments)
+//! - Plain text and PDF (extracted text): split at paragraphs
+//! - JSON and YAML: split at top-level keys
+//! - JSONL (e.g. session transcripts): one record per line
 
+use anyhow::Result;
 use sha2::{Digest, Sha256};
+use std::path::Path;
 
 /// A chunk of text from a source file.
 #[derive(Debug, Clone)]
@@ -90,6 +101,374 @@ pub fn chunk_markdown(
     chunks
 }
 
+/// A chunking strategy for one kind of file.
+pub trait Chunker: Send + Sync {
+    /// Name recorded as the source of each chunk (e.g. "markdown", "code").
+    fn name(&self) -> &'static str;
+
+    /// Extract indexable text from the raw file contents.
+    fn extract_text(&self, bytes: &[u8]) -> Result<String> {
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// Split text into chunks of at most `max_lines` lines, each repeating
+    /// up to `overlap_lines` lines from the end of the previous chunk.
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk>;
+}
+
+/// Markdown, split at headings.
+pub struct MarkdownChunker;
+
+/// Source code, split at top-level definitions.
+pub struct CodeChunker;
+
+/// Plain text, split at paragraphs.
+pub struct TextChunker;
+
+/// JSON, split at top-level keys (or array elements).
+pub struct JsonChunker;
+
+/// YAML, split at top-level keys and documents.
+pub struct YamlChunker;
+
+/// JSON Lines such as session transcripts, one record per line.
+pub struct JsonlChunker;
+
+/// PDF, chunked by paragraphs of its extracted text.
+pub struct PdfChunker;
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "mjs", "cjs", "ts", "tsx", "go", "java", "kt", "kts", "scala",
+    "swift", "c", "h", "cc", "cpp", "cxx", "hpp", "cs", "rb", "php", "lua", "sh", "bash", "zsh",
+    "sql", "ex", "exs", "dart", "vue", "svelte",
+];
+
+const TEXT_EXTENSIONS: &[&str] = &["txt", "text", "rst", "org", "adoc", "log"];
+
+/// The chunker for a file, by extension. `None` means the file type is not indexed.
+pub fn chunker_for_path(path: &Path) -> Option<&'static dyn Chunker> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let chunker: &'static dyn Chunker = match ext.as_str() {
+        "md" | "markdown" => &MarkdownChunker,
+        "json" => &JsonChunker,
+        "yaml" | "yml" => &YamlChunker,
+        "jsonl" | "ndjson" => &JsonlChunker,
+        "pdf" => &PdfChunker,
+        ext if CODE_EXTENSIONS.contains(&ext) => &CodeChunker,
+        ext if TEXT_EXTENSIONS.contains(&ext) => &TextChunker,
+        _ => return None,
+    };
+    Some(chunker)
+}
+
+impl Chunker for MarkdownChunker {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+        chunk_markdown(content, max_lines, overlap_lines)
+    }
+}
+
+impl Chunker for TextChunker {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+        chunk_paragraphs(content, max_lines, overlap_lines)
+    }
+}
+
+impl Chunker for PdfChunker {
+    fn name(&self) -> &'static str {
+        "pdf"
+    }
+
+    fn extract_text(&self, bytes: &[u8]) -> Result<String> {
+        pdf_extract::extract_text_from_mem(bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to extract PDF text: {e}"))
+    }
+
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+        chunk_paragraphs(content, max_lines, overlap_lines)
+    }
+}
+
+impl Chunker for CodeChunker {
+    fn name(&self) -> &'static str {
+        "code"
+    }
+
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+        let lines: Vec<&str> = content.lines().collect();
+        let starts: Vec<usize> = (0..lines.len())
+            .filter(|&i| is_definition(lines[i]))
+            .map(|i| attach_leading_comments(&lines, i, is_code_comment))
+            .collect();
+        pack_blocks(&lines, &starts, max_lines, overlap_lines)
+    }
+}
+
+impl Chunker for JsonChunker {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+        // Minified JSON: chunk a pretty-printed copy, all of it is line 1
+        if content.trim().lines().count() == 1
+            && let Ok(value) = serde_json::from_str::<serde_json::Value>(content)
+            && let Ok(pretty) = serde_json::to_string_pretty(&value)
+            && pretty.lines().count() > 1
+        {
+            let mut chunks = self.chunk(&pretty, max_lines, overlap_lines);
+            for chunk in &mut chunks {
+                chunk.start_line = 1;
+                chunk.end_line = 1;
+            }
+            return chunks;
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        let mut starts = Vec::new();
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        for (i, line) in lines.iter().enumerate() {
+            let trimmed = line.trim_start();
+            if depth == 1 && !in_string && trimmed.starts_with(['"', '{', '[']) {
+                starts.push(i);
+            }
+            for c in line.chars() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' if in_string => escaped = true,
+                    '"' => in_string = !in_string,
+                    '{' | '[' if !in_string => depth += 1,
+                    '}' | ']' if !in_string => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        }
+        pack_blocks(&lines, &starts, max_lines, overlap_lines)
+    }
+}
+
+impl Chunker for YamlChunker {
+    fn name(&self) -> &'static str {
+        "yaml"
+    }
+
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+        let lines: Vec<&str> = content.lines().collect();
+        let starts: Vec<usize> = (0..lines.len())
+            .filter(|&i| {
+                let line = lines[i];
+                !line.is_empty() && !line.starts_with([' ', '\t', '#'])
+            })
+            .map(|i| attach_leading_comments(&lines, i, |l| l.starts_with('#')))
+            .collect();
+        pack_blocks(&lines, &starts, max_lines, overlap_lines)
+    }
+}
+
+impl Chunker for JsonlChunker {
+    fn name(&self) -> &'static str {
+        "jsonl"
+    }
+
+    fn chunk(&self, content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+        // Keep one output line per record so line numbers still match the file
+        let lines: Vec<String> = content.lines().map(render_record).collect();
+        let starts: Vec<usize> = (0..lines.len()).collect();
+        pack_blocks(&lines, &starts, max_lines, overlap_lines)
+    }
+}
+
+/// Split plain text at blank-line-separated paragraphs.
+fn chunk_paragraphs(content: &str, max_lines: usize, overlap_lines: usize) -> Vec<MemoryChunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    let starts: Vec<usize> = (0..lines.len())
+        .filter(|&i| !lines[i].trim().is_empty() && (i == 0 || lines[i - 1].trim().is_empty()))
+        .collect();
+    pack_blocks(&lines, &starts, max_lines, overlap_lines)
+}
+
+/// Keywords that begin a top-level definition in common languages.
+const DEFINITION_PREFIXES: &[&str] = &[
+    "fn ",
+    "pub ",
+    "pub(",
+    "async ",
+    "unsafe ",
+    "impl ",
+    "impl<",
+    "struct ",
+    "enum ",
+    "trait ",
+    "mod ",
+    "type ",
+    "const ",
+    "static ",
+    "macro_rules!",
+    "def ",
+    "class ",
+    "function ",
+    "export ",
+    "interface ",
+    "func ",
+    "public ",
+    "private ",
+    "protected ",
+    "internal ",
+    "abstract ",
+    "final ",
+    "sealed ",
+    "data class ",
+    "object ",
+    "module ",
+    "defmodule ",
+    "local function ",
+    "CREATE ",
+    "create ",
+];
+
+/// Whether a line starts a top-level definition.
+fn is_definition(line: &str) -> bool {
+    if line.starts_with(char::is_whitespace) {
+        return false;
+    }
+    DEFINITION_PREFIXES.iter().any(|p| line.starts_with(p))
+        // C-style `int main(void) {`
+        || (line.contains('(') && line.trim_end().ends_with('{') && !is_code_comment(line))
+}
+
+fn is_code_comment(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    ["//", "#", "/*", "*", "@", "--"]
+        .iter()
+        .any(|p| trimmed.starts_with(p))
+}
+
+/// Move a block start up over directly preceding comments, attributes and
+/// decorators so they stay with the definition they document.
+fn attach_leading_comments(
+    lines: &[&str],
+    start: usize,
+    is_comment: impl Fn(&str) -> bool,
+) -> usize {
+    let mut i = start;
+    while i > 0 && !lines[i - 1].trim().is_empty() && is_comment(lines[i - 1]) {
+        i -= 1;
+    }
+    i
+}
+
+/// Readable form of one JSONL record: `role: text` for chat messages,
+/// otherwise the raw line.
+fn render_record(line: &str) -> String {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+        return line.to_string();
+    };
+    let message = value.get("message").unwrap_or(&value);
+    let mut texts = Vec::new();
+    collect_text(message, &mut texts);
+    if texts.is_empty() {
+        return line.to_string();
+    }
+    let text = texts.join(" ").replace('\n', " ");
+    match message.get("role").and_then(|r| r.as_str()) {
+        Some(role) => format!("{role}: {text}"),
+        None => text,
+    }
+}
+
+/// Collect the strings under `text` and `content` keys.
+fn collect_text(value: &serde_json::Value, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, v) in map {
+                match v {
+                    serde_json::Value::String(s) if key == "text" || key == "content" => {
+                        out.push(s.clone())
+                    }
+                    _ => collect_text(v, out),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_text(item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Pack consecutive blocks of lines into chunks.
+///
+/// `starts` are the 0-based line indices where blocks begin. Blocks are
+/// merged while they fit in `max_lines`, and larger blocks are split.
+/// Each chunk after the first repeats up to `overlap_lines` lines from
+/// the end of the previous one. Whitespace-only chunks are dropped.
+fn pack_blocks<S: AsRef<str>>(
+    lines: &[S],
+    starts: &[usize],
+    max_lines: usize,
+    overlap_lines: usize,
+) -> Vec<MemoryChunk> {
+    let max_lines = max_lines.max(1);
+    let overlap_lines = overlap_lines.min(max_lines - 1);
+
+    let mut bounds: Vec<usize> = starts
+        .iter()
+        .copied()
+        .filter(|&s| s < lines.len())
+        .collect();
+    bounds.push(0);
+    bounds.push(lines.len());
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut chunks = Vec::new();
+    let mut emit = |start: usize, end: usize| {
+        let text = lines[start..end]
+            .iter()
+            .map(AsRef::as_ref)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        if text.trim().is_empty() {
+            return;
+        }
+        let hash = hash_text(&text);
+        chunks.push(MemoryChunk {
+            text,
+            start_line: start + 1,
+            end_line: end,
+            hash,
+        });
+    };
+
+    let (mut start, mut end) = (0, 0);
+    for block in bounds.windows(2) {
+        if end > start && block[1] - start > max_lines {
+            emit(start, end);
+            start = end.saturating_sub(overlap_lines).max(start + 1);
+        }
+        end = block[1];
+        while end - start > max_lines {
+            emit(start, start + max_lines);
+            start += max_lines - overlap_lines;
+        }
+    }
+    if end > start {
+        emit(start, end);
+    }
+    chunks
+}
+
 fn hash_text(text: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(text.as_bytes());
@@ -123,6 +502,87 @@ mod tests {
         assert!(chunks.is_empty());
     }
 
+    fn ranges(chunks: &[MemoryChunk]) -> Vec<(usize, usize)> {
+        chunks.iter().map(|c| (c.start_line, c.end_line)).collect()
+    }
+
+    #[test]
+    fn test_chunker_for_path() {
+        let name = |p: &str| chunker_for_path(Path::new(p)).map(|c| c.name());
+        assert_eq!(name("notes/a.md"), Some("markdown"));
+        assert_eq!(name("src/main.RS"), Some("code"));
+        assert_eq!(name("readme.txt"), Some("text"));
+        assert_eq!(name("config.yml"), Some("yaml"));
+        assert_eq!(name("sessions/abc.jsonl"), Some("jsonl"));
+        assert_eq!(name("paper.pdf"), Some("pdf"));
+        assert_eq!(name("image.png"), None);
+        assert_eq!(name("Makefile"), None);
+    }
+
+    #[test]
+    fn test_pack_blocks_merges_and_splits() {
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+        // Blocks of 2, 2 and 6 lines
+        let chunks = pack_blocks(&lines, &[0, 2, 4], 4, 0);
+        assert_eq!(ranges(&chunks), vec![(1, 4), (5, 8), (9, 10)]);
+
+        let chunks = pack_blocks(&lines, &[0, 2, 4], 4, 1);
+        assert_eq!(ranges(&chunks), vec![(1, 4), (4, 7), (7, 10)]);
+    }
+
+    #[test]
+    fn test_code_chunks_on_definitions() {
+        let code = "use std::io;\n\n/// Adds.\n#[inline]\nfn add(a: i32) -> i32 {\n    a + 1\n}\n\nstruct Point {\n    x: i32,\n}";
+        let chunks = CodeChunker.chunk(code, 6, 0);
+        assert_eq!(ranges(&chunks), vec![(1, 2), (3, 8), (9, 11)]);
+        assert!(chunks[1].text.starts_with("/// Adds."));
+
+        let py = "import os\n\n@cache\ndef load():\n    pass\n\nclass Store:\n    def get(self):\n        pass";
+        let chunks = CodeChunker.chunk(py, 4, 0);
+        assert_eq!(ranges(&chunks), vec![(1, 2), (3, 6), (7, 9)]);
+    }
+
+    #[test]
+    fn test_text_chunks_on_paragraphs() {
+        let text =
+            "First paragraph\ncontinues here.\n\nSecond paragraph.\n\nThird\nparagraph\nis long.";
+        let chunks = TextChunker.chunk(text, 3, 0);
+        assert_eq!(ranges(&chunks), vec![(1, 3), (4, 5), (6, 8)]);
+    }
+
+    #[test]
+    fn test_json_and_yaml_chunk_on_top_level_keys() {
+        let json = "{\n  \"a\": 1,\n  \"b\": {\n    \"c\": \"}{\",\n    \"d\": [1, 2]\n  },\n  \"e\": true\n}";
+        let chunks = JsonChunker.chunk(json, 4, 0);
+        assert_eq!(ranges(&chunks), vec![(1, 2), (3, 6), (7, 8)]);
+
+        // Minified JSON is split too, but all of it is on line 1
+        let chunks = JsonChunker.chunk(r#"{"a": [1, 2, 3], "b": {"c": 1, "d": 2}}"#, 4, 0);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.start_line == 1 && c.end_line == 1));
+
+        let yaml = "# Service\nname: api\nports:\n  - 80\n  - 443\n---\nname: worker";
+        let chunks = YamlChunker.chunk(yaml, 3, 0);
+        assert_eq!(ranges(&chunks), vec![(1, 2), (3, 5), (6, 7)]);
+    }
+
+    #[test]
+    fn test_jsonl_renders_messages() {
+        let jsonl = concat!(
+            r#"{"type":"message","message":{"role":"user","content":[{"type":"text","text":"How do I deploy?"}]}}"#,
+            "\n",
+            r#"{"role":"assistant","content":"Run make deploy."}"#,
+            "\n",
+            "not json",
+        );
+        let chunks = JsonlChunker.chunk(jsonl, 10, 0);
+        assert_eq!(ranges(&chunks), vec![(1, 3)]);
+        assert_eq!(
+            chunks[0].text,
+            "user: How do I deploy?\nassistant: Run make deploy.\nnot json"
+        );
+    }
+
     #[test]
     fn test_chunk_hash_deterministic() {
         let chunks1 = chunk_markdown("Hello\nWorld", 100, 0);
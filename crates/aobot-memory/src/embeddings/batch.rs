@@ -0,0 +1,177 @@
+//! Request batching and rate-limit retries shared by the HTTP providers.
+
+use anyhow::Result;
+use std::ops::Range;
+use std::time::Duration;
+use tracing::warn;
+
+/// Maximum number of retries after a rate-limit response.
+const MAX_RETRIES: u32 = 5;
+
+/// Delay before the first retry when the server sends no `Retry-After`.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest wait before a retry, whatever the server asks for. Callers such
+/// as `memory_search` are waiting on the result.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Time an embedding request may take.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP client for the embedding providers.
+pub(crate) fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Rough token count of a text (about four characters per token).
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Split `texts` into consecutive batches of at most `max_items` texts
+/// and (estimated) `max_tokens` tokens. A single text larger than the
+/// token budget gets a batch of its own.
+pub(crate) fn token_batches(
+    texts: &[String],
+    max_tokens: usize,
+    max_items: usize,
+) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    for (i, text) in texts.iter().enumerate() {
+        let text_tokens = estimate_tokens(text);
+        let full = i - start >= max_items || tokens + text_tokens > max_tokens;
+        if i > start && full {
+            batches.push(start..i);
+            start = i;
+            tokens = 0;
+        }
+        tokens += text_tokens;
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+    batches
+}
+
+/// Send a JSON request, retrying with backoff on HTTP 429.
+///
+/// `build` is called once per attempt. Returns the response body of the
+/// first non-429 response, or an error naming `provider` with the API's
+/// error message.
+pub(crate) async fn send_with_retry(
+    provider: &str,
+    build: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<serde_json::Value> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        let resp = build().send().await?;
+        let status = resp.status();
+
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RETRIES {
+            let delay = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(backoff)
+                .min(MAX_RETRY_DELAY);
+            attempt += 1;
+            warn!(
+                "{provider} embedding rate limited, retrying in {delay:?} ({attempt}/{MAX_RETRIES})"
+            );
+            tokio::time::sleep(delay).await;
+            backoff *= 2;
+            continue;
+        }
+
+        let json: serde_json::Value = resp.json().await.unwrap_or_default();
+        if !status.is_success() {
+            let msg = json
+                .get("error")
+                .and_then(|e| e.get("message").or(Some(e)))
+                .or_else(|| json.get("detail"))
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown error");
+            return Err(anyhow::anyhow!(
+                "{provider} embedding error ({status}): {msg}"
+            ));
+        }
+        return Ok(json);
+    }
+}
+
+/// Parse a JSON array of numbers into a vector.
+pub(crate) fn parse_vector(value: Option<&serde_json::Value>) -> Result<Vec<f32>> {
+    Ok(value
+        .and_then(|e| e.as_array())
+        .ok_or_else(|| anyhow::anyhow!("Missing embedding array"))?
+        .iter()
+        .filter_map(|v| v.as_f64().map(|f| f as f32))
+        .collect())
+}
+
+/// Parse the `data` array of an OpenAI-style response into vectors in
+/// input order, placing each item by its `index` field where present.
+pub(crate) fn parse_indexed_vectors(
+    provider: &str,
+    json: &serde_json::Value,
+    expected: usize,
+) -> Result<Vec<Vec<f32>>> {
+    let data = json
+        .get("data")
+        .and_then(|d| d.as_array())
+        .ok_or_else(|| anyhow::anyhow!("Invalid embedding response format"))?;
+
+    let mut items = Vec::with_capacity(data.len());
+    for (position, item) in data.iter().enumerate() {
+        let index = item
+            .get("index")
+            .and_then(|i| i.as_u64())
+            .map_or(position, |i| i as usize);
+        items.push((index, parse_vector(item.get("embedding"))?));
+    }
+    items.sort_by_key(|(index, _)| *index);
+    let embeddings = items.into_iter().map(|(_, vector)| vector).collect();
+    check_count(provider, embeddings, expected)
+}
+
+/// Fail unless the API returned one vector per input text.
+pub(crate) fn check_count(
+    provider: &str,
+    embeddings: Vec<Vec<f32>>,
+    expected: usize,
+) -> Result<Vec<Vec<f32>>> {
+    if embeddings.len() != expected {
+        return Err(anyhow::anyhow!(
+            "{provider} returned {} embeddings for {expected} inputs",
+            embeddings.len()
+        ));
+    }
+    Ok(embeddings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_batches() {
+        // 40 chars ≈ 10 tokens each
+        let texts: Vec<String> = (0..5).map(|_| "x".repeat(40)).collect();
+        assert_eq!(token_batches(&texts, 25, 100), vec![0..2, 2..4, 4..5]);
+        assert_eq!(token_batches(&texts, 1000, 3), vec![0..3, 3..5]);
+
+        // Oversized text still gets sent on its own
+        let texts = vec!["x".repeat(400), "y".to_string()];
+        assert_eq!(token_batches(&texts, 25, 100), vec![0..1, 1..2]);
+
+        assert!(token_batches(&[], 25, 100).is_empty());
+    }
+}
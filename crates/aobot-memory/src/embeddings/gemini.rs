@@ -0,0 +1,133 @@
+//! Google Gemini embedding provider.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::EmbeddingProvider;
+use super::batch::{check_count, http_client, parse_vector, send_with_retry, token_batches};
+
+const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Token budget per request.
+const MAX_BATCH_TOKENS: usize = 20_000;
+
+/// Maximum inputs per `batchEmbedContents` request.
+const MAX_BATCH_ITEMS: usize = 100;
+
+/// Gemini embedding provider (`batchEmbedContents`).
+pub struct GeminiEmbedding {
+    api_key: String,
+    base_url: String,
+    model: String,
+    /// Requested output size; sent as `outputDimensionality` when set.
+    requested_dimensions: Option<usize>,
+    dimensions: usize,
+    client: reqwest::Client,
+}
+
+impl GeminiEmbedding {
+    pub fn new(api_key: String) -> Self {
+        Self::with_model(api_key, "gemini-embedding-001".to_string(), 3072)
+    }
+
+    pub fn with_model(api_key: String, model: String, dimensions: usize) -> Self {
+        Self {
+            api_key,
+            base_url: GEMINI_BASE_URL.to_string(),
+            model,
+            requested_dimensions: None,
+            dimensions,
+            client: http_client(),
+        }
+    }
+
+    /// Override the API base URL.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Request shortened vectors of the given size.
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.requested_dimensions = Some(dimensions);
+        self.dimensions = dimensions;
+        self
+    }
+
+    /// Embed one request's worth of texts with the given task type.
+    async fn embed_request(&self, texts: &[String], task_type: &str) -> Result<Vec<Vec<f32>>> {
+        let model = format!("models/{}", self.model);
+        let requests: Vec<serde_json::Value> = texts
+            .iter()
+            .map(|text| {
+                let mut request = serde_json::json!({
+                    "model": model,
+                    "content": { "parts": [{ "text": text }] },
+                    "taskType": task_type,
+                });
+                if let Some(dimensions) = self.requested_dimensions {
+                    request["outputDimensionality"] = dimensions.into();
+                }
+                request
+            })
+            .collect();
+        let body = serde_json::json!({ "requests": requests });
+
+        let url = format!("{}/{model}:batchEmbedContents", self.base_url);
+        let json = send_with_retry("Gemini", || {
+            self.client
+                .post(&url)
+                .header("x-goog-api-key", &self.api_key)
+                .json(&body)
+        })
+        .await?;
+
+        let items = json
+            .get("embeddings")
+            .and_then(|d| d.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Invalid embedding response format"))?;
+
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for item in items {
+            embeddings.push(parse_vector(item.get("values"))?);
+        }
+        check_count("Gemini", embeddings, texts.len())
+    }
+
+    async fn embed_texts(&self, texts: &[String], task_type: &str) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for range in token_batches(texts, MAX_BATCH_TOKENS, MAX_BATCH_ITEMS) {
+            embeddings.extend(self.embed_request(&texts[range], task_type).await?);
+        }
+        Ok(embeddings)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for GeminiEmbedding {
+    fn id(&self) -> &str {
+        "gemini"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let batch = self
+            .embed_texts(&[text.to_string()], "RETRIEVAL_QUERY")
+            .await?;
+        batch
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Empty embedding result"))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_texts(texts, "RETRIEVAL_DOCUMENT").await
+    }
+}
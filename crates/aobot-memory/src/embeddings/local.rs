@@ -0,0 +1,135 @@
+//! Offline embedding provider based on feature hashing.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+use super::EmbeddingProvider;
+
+/// Default vector size of the local provider.
+const LOCAL_DIMENSIONS: usize = 512;
+
+/// Term weight of a character trigram relative to a whole word.
+const TRIGRAM_WEIGHT: f32 = 0.5;
+
+/// Offline embedding provider using feature hashing.
+///
+/// Words and character trigrams are hashed into a fixed number of buckets
+/// with sublinear term frequency, then L2-normalized. Needs no network or
+/// model files; matching quality is closer to fuzzy keyword search than to
+/// a neural model.
+pub struct LocalEmbedding {
+    model: String,
+    dimensions: usize,
+}
+
+impl LocalEmbedding {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            model: format!("local-hash-{dimensions}"),
+            dimensions,
+        }
+    }
+
+    /// Embed a text synchronously.
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let text = text.to_lowercase();
+        let mut features: HashMap<u64, f32> = HashMap::new();
+        for word in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            *features.entry(fnv1a(word.as_bytes())).or_default() += 1.0;
+            // Trigrams of the padded word let inflections ("deploy"/"deploys") overlap
+            let chars: Vec<char> = format!(" {word} ").chars().collect();
+            for trigram in chars.windows(3) {
+                let trigram: String = trigram.iter().collect();
+                *features
+                    .entry(fnv1a(format!("#{trigram}").as_bytes()))
+                    .or_default() += TRIGRAM_WEIGHT;
+            }
+        }
+
+        let mut vector = vec![0.0f32; self.dimensions];
+        for (hash, tf) in features {
+            let bucket = (hash % self.dimensions as u64) as usize;
+            // A hash bit picks the sign so bucket collisions tend to cancel out
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[bucket] += sign * (1.0 + tf.ln());
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for LocalEmbedding {
+    fn default() -> Self {
+        Self::new(LOCAL_DIMENSIONS)
+    }
+}
+
+/// 64-bit FNV-1a hash, stable across platforms and releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbedding {
+    fn id(&self) -> &str {
+        "local"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.embed(text))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|t| self.embed(t)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_embedding() {
+        let provider = LocalEmbedding::default();
+        assert_eq!(provider.id(), "local");
+        assert_eq!(provider.model(), "local-hash-512");
+
+        let query = provider.embed_query("How do we deploy?").await.unwrap();
+        let batch = provider
+            .embed_batch(&[
+                "Deploying the gateway: run the release script.".to_string(),
+                "Lunch options near the office.".to_string(),
+                String::new(),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(query.len(), 512);
+        let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+        assert!(dot(&query, &batch[0]) > dot(&query, &batch[1]));
+        assert!(batch[2].iter().all(|x| *x == 0.0));
+
+        // Deterministic across calls
+        assert_eq!(
+            provider.embed_query("How do we deploy?").await.unwrap(),
+            query
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Embedding providers for vector storage.
+
+mod batch;
+mod gemini;
+mod local;
+mod openai;
+mod voyage;
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+
+use aobot_config::{EmbeddingProviderConfig, GlobalMemoryConfig};
+
+pub use gemini::GeminiEmbedding;
+pub use local::LocalEmbedding;
+pub use openai::OpenAiEmbedding;
+pub use voyage::VoyageEmbedding;
+
+/// Trait for embedding text into vectors.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Provider identifier.
+    fn id(&self) -> &str;
+    /// Model name.
+    fn model(&self) -> &str;
+    /// Vector dimensions.
+    fn dimensions(&self) -> usize;
+    /// Embed a single query.
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>>;
+    /// Embed a batch of texts.
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Looks up an environment variable.
+type Env<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// The process environment.
+fn process_env(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Auto-select an embedding provider based on available API keys.
+pub fn auto_select_provider() -> Option<Box<dyn EmbeddingProvider>> {
+    let env: Env = &process_env;
+    openai_from_config(None, env)
+        .or_else(|_| gemini_from_config(None, env))
+        .or_else(|_| voyage_from_config(None, env))
+        .ok()
+}
+
+/// Select the embedding provider configured in `[memory]`.
+///
+/// `"auto"` picks the first provider whose API key is available and
+/// falls back to the offline `"local"` provider.
+pub fn provider_from_config(config: &GlobalMemoryConfig) -> Result<Box<dyn EmbeddingProvider>> {
+    provider_from_config_env(config, &process_env)
+}
+
+/// [`provider_from_config`] with API keys looked up in `env`.
+fn provider_from_config_env(
+    config: &GlobalMemoryConfig,
+    env: Env,
+) -> Result<Box<dyn EmbeddingProvider>> {
+    match config.provider.as_str() {
+        "auto" => Ok(openai_from_config(config.openai.as_ref(), env)
+            .or_else(|_| gemini_from_config(config.gemini.as_ref(), env))
+            .or_else(|_| voyage_from_config(config.voyage.as_ref(), env))
+            .unwrap_or_else(|_| Box::new(LocalEmbedding::default()))),
+        "local" => Ok(Box::new(LocalEmbedding::default())),
+        "openai" => openai_from_config(config.openai.as_ref(), env),
+        "gemini" => gemini_from_config(config.gemini.as_ref(), env),
+        "voyage" => voyage_from_config(config.voyage.as_ref(), env),
+        "openai-compatible" => compatible_from_config(config.openai_compatible.as_ref(), env),
+        other => bail!("Unsupported embedding provider '{other}'"),
+    }
+}
+
+/// Read a provider's API key from its configured (or default) environment variable.
+fn api_key(
+    config: Option<&EmbeddingProviderConfig>,
+    env: Env,
+    default_env: &str,
+    provider: &str,
+) -> Result<String> {
+    let key_env = config
+        .and_then(|c| c.api_key_env.as_deref())
+        .unwrap_or(default_env);
+    env(key_env)
+        .ok_or_else(|| anyhow::anyhow!("{provider} embeddings need an API key in ${key_env}"))
+}
+
+/// Build the OpenAI provider from its optional config section.
+fn openai_from_config(
+    config: Option<&EmbeddingProviderConfig>,
+    env: Env,
+) -> Result<Box<dyn EmbeddingProvider>> {
+    let api_key = api_key(config, env, "OPENAI_API_KEY", "OpenAI")?;
+    let mut provider = match config.and_then(|c| c.model.clone()) {
+        Some(model) => {
+            let dimensions = if model == "text-embedding-3-large" {
+                3072
+            } else {
+                1536
+            };
+            OpenAiEmbedding::with_model(api_key, model, dimensions)
+        }
+        None => OpenAiEmbedding::new(api_key),
+    };
+    if let Some(base_url) = config.and_then(|c| c.base_url.clone()) {
+        provider = provider.with_base_url(base_url);
+    }
+    if let Some(dimensions) = config.and_then(|c| c.dimensions) {
+        provider = provider.with_dimensions(dimensions);
+    }
+    Ok(Box::new(provider))
+}
+
+/// Build the Gemini provider from its optional config section.
+fn gemini_from_config(
+    config: Option<&EmbeddingProviderConfig>,
+    env: Env,
+) -> Result<Box<dyn EmbeddingProvider>> {
+    let api_key = api_key(config, env, "GEMINI_API_KEY", "Gemini")?;
+    let mut provider = match config.and_then(|c| c.model.clone()) {
+        Some(model) => {
+            let dimensions = if model == "gemini-embedding-001" {
+                3072
+            } else {
+                768
+            };
+            GeminiEmbedding::with_model(api_key, model, dimensions)
+        }
+        None => GeminiEmbedding::new(api_key),
+    };
+    if let Some(base_url) = config.and_then(|c| c.base_url.clone()) {
+        provider = provider.with_base_url(base_url);
+    }
+    if let Some(dimensions) = config.and_then(|c| c.dimensions) {
+        provider = provider.with_dimensions(dimensions);
+    }
+    Ok(Box::new(provider))
+}
+
+/// Build the Voyage provider from its optional config section.
+fn voyage_from_config(
+    config: Option<&EmbeddingProviderConfig>,
+    env: Env,
+) -> Result<Box<dyn EmbeddingProvider>> {
+    let api_key = api_key(config, env, "VOYAGE_API_KEY", "Voyage")?;
+    let mut provider = match config.and_then(|c| c.model.clone()) {
+        Some(model) => VoyageEmbedding::with_model(api_key, model, 1024),
+        None => VoyageEmbedding::new(api_key),
+    };
+    if let Some(base_url) = config.and_then(|c| c.base_url.clone()) {
+        provider = provider.with_base_url(base_url);
+    }
+    if let Some(dimensions) = config.and_then(|c| c.dimensions) {
+        provider = provider.with_dimensions(dimensions);
+    }
+    Ok(Box::new(provider))
+}
+
+/// Build an OpenAI-compatible provider; `base_url` and `model` are required,
+/// the API key only if `api_key_env` is set.
+fn compatible_from_config(
+    config: Option<&EmbeddingProviderConfig>,
+    env: Env,
+) -> Result<Box<dyn EmbeddingProvider>> {
+    let Some(config) = config else {
+        bail!("provider = \"openai-compatible\" needs a [memory.openai_compatible] section");
+    };
+    let (Some(base_url), Some(model)) = (config.base_url.clone(), config.model.clone()) else {
+        bail!("[memory.openai_compatible] needs both base_url and model");
+    };
+    let api_key = match &config.api_key_env {
+        Some(_) => Some(api_key(Some(config), env, "", "OpenAI-compatible")?),
+        None => None,
+    };
+    let mut provider = OpenAiEmbedding::compatible(base_url, api_key, model);
+    if let Some(dimensions) = config.dimensions {
+        provider = provider.with_dimensions(dimensions);
+    }
+    Ok(Box::new(provider))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory_config(
+        provider: &str,
+        openai: Option<EmbeddingProviderConfig>,
+    ) -> GlobalMemoryConfig {
+        GlobalMemoryConfig {
+            enabled: true,
+            provider: provider.to_string(),
+            dirs: vec![],
+            chunk_size: 500,
+            chunk_overlap: 50,
+            sync_on_start: false,
+            openai,
+            gemini: None,
+            voyage: None,
+            openai_compatible: None,
+            search: Default::default(),
+            sessions: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_openai_from_config_env() {
+        let openai = EmbeddingProviderConfig {
+            api_key_env: Some("AOBOT_TEST_EMBEDDING_KEY".to_string()),
+            model: Some("text-embedding-3-large".to_string()),
+            ..Default::default()
+        };
+        // Key variable unset
+        let config = memory_config("openai", Some(openai.clone()));
+        let err = provider_from_config_env(&config, &|_| None).err().unwrap();
+        assert!(err.to_string().contains("AOBOT_TEST_EMBEDDING_KEY"));
+
+        let env: Env = &|name| (name == "AOBOT_TEST_EMBEDDING_KEY").then(|| "sk-test".to_string());
+        let provider = provider_from_config_env(&config, env).unwrap();
+        assert_eq!(provider.id(), "openai");
+        assert_eq!(provider.model(), "text-embedding-3-large");
+        assert_eq!(provider.dimensions(), 3072);
+
+        // Gemini and Voyage read the same config shape
+        let mut config = memory_config("gemini", None);
+        config.gemini = Some(EmbeddingProviderConfig {
+            dimensions: Some(768),
+            ..openai.clone()
+        });
+        let provider = provider_from_config_env(&config, env).unwrap();
+        assert_eq!(provider.id(), "gemini");
+        assert_eq!(provider.dimensions(), 768);
+
+        let mut config = memory_config("voyage", None);
+        config.voyage = Some(openai);
+        assert_eq!(
+            provider_from_config_env(&config, env).unwrap().id(),
+            "voyage"
+        );
+    }
+
+    #[test]
+    fn test_openai_compatible_from_config() {
+        let mut config = memory_config("openai-compatible", None);
+        assert!(provider_from_config(&config).is_err());
+
+        config.openai_compatible = Some(EmbeddingProviderConfig {
+            base_url: Some("http://localhost:11434/v1".to_string()),
+            model: Some("nomic-embed-text".to_string()),
+            dimensions: Some(768),
+            ..Default::default()
+        });
+        let provider = provider_from_config(&config).unwrap();
+        assert_eq!(provider.id(), "openai-compatible");
+        assert_eq!(provider.model(), "nomic-embed-text");
+        assert_eq!(provider.dimensions(), 768);
+    }
+
+    #[test]
+    fn test_unknown_provider() {
+        let config = memory_config("carrier-pigeon", None);
+        let err = provider_from_config(&config).err().unwrap();
+        assert!(err.to_string().contains("Unsupported embedding provider"));
+    }
+}
@@ -0,0 +1,232 @@
+//! OpenAI and OpenAI-compatible (`/embeddings`) providers.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::EmbeddingProvider;
+use super::batch::{http_client, parse_indexed_vectors, send_with_retry, token_batches};
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Token budget per request (the API allows up to 300k).
+const MAX_BATCH_TOKENS: usize = 100_000;
+
+/// Maximum inputs per request.
+const MAX_BATCH_ITEMS: usize = 2048;
+
+/// OpenAI embedding provider.
+///
+/// Also serves OpenAI-compatible servers (Ollama, vLLM, LM Studio, ...)
+/// through [`OpenAiEmbedding::compatible`].
+pub struct OpenAiEmbedding {
+    id: &'static str,
+    api_key: Option<String>,
+    base_url: String,
+    model: String,
+    /// Requested output size; sent to the API when set.
+    requested_dimensions: Option<usize>,
+    /// Known vector size (0 until the first response if not configured).
+    dimensions: AtomicUsize,
+    client: reqwest::Client,
+}
+
+impl OpenAiEmbedding {
+    pub fn new(api_key: String) -> Self {
+        Self::with_model(api_key, "text-embedding-3-small".to_string(), 1536)
+    }
+
+    pub fn with_model(api_key: String, model: String, dimensions: usize) -> Self {
+        Self {
+            id: "openai",
+            api_key: Some(api_key),
+            base_url: OPENAI_BASE_URL.to_string(),
+            model,
+            requested_dimensions: None,
+            dimensions: AtomicUsize::new(dimensions),
+            client: http_client(),
+        }
+    }
+
+    /// Provider for an OpenAI-compatible server at `base_url`
+    /// (e.g. "http://localhost:11434/v1"). The API key is optional.
+    pub fn compatible(base_url: String, api_key: Option<String>, model: String) -> Self {
+        Self {
+            id: "openai-compatible",
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model,
+            requested_dimensions: None,
+            dimensions: AtomicUsize::new(0),
+            client: http_client(),
+        }
+    }
+
+    /// Override the API base URL.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Request shortened vectors of the given size.
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.requested_dimensions = Some(dimensions);
+        self.dimensions = AtomicUsize::new(dimensions);
+        self
+    }
+
+    /// Embed one request's worth of texts.
+    async fn embed_request(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "input": texts,
+        });
+        if let Some(dimensions) = self.requested_dimensions {
+            body["dimensions"] = dimensions.into();
+        }
+
+        let url = format!("{}/embeddings", self.base_url);
+        let json = send_with_retry(self.id, || {
+            let req = self.client.post(&url).json(&body);
+            match &self.api_key {
+                Some(key) => req.bearer_auth(key),
+                None => req,
+            }
+        })
+        .await?;
+
+        parse_indexed_vectors(self.id, &json, texts.len())
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbedding {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions.load(Ordering::Relaxed)
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let batch = self.embed_batch(&[text.to_string()]).await?;
+        batch
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Empty embedding result"))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for range in token_batches(texts, MAX_BATCH_TOKENS, MAX_BATCH_ITEMS) {
+            embeddings.extend(self.embed_request(&texts[range]).await?);
+        }
+        if let Some(first) = embeddings.first() {
+            self.dimensions.store(first.len(), Ordering::Relaxed);
+        }
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve canned HTTP responses, one per connection, in order.
+    async fn serve(responses: Vec<String>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 64 * 1024];
+                let _ = socket.read(&mut buf).await;
+                socket.write_all(response.as_bytes()).await.unwrap();
+                let _ = socket.shutdown().await;
+            }
+        });
+        format!("http://{addr}/v1")
+    }
+
+    fn http_response(status: &str, extra_headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n{extra_headers}\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[tokio::test]
+    async fn test_compatible_retries_rate_limit() {
+        let base_url = serve(vec![
+            http_response(
+                "429 Too Many Requests",
+                "retry-after: 0\r\n",
+                r#"{"error":{"message":"slow down"}}"#,
+            ),
+            http_response("200 OK", "", r#"{"data":[{"embedding":[0.5,0.25,0.0]}]}"#),
+        ])
+        .await;
+
+        let provider = OpenAiEmbedding::compatible(base_url, None, "nomic-embed-text".into());
+        assert_eq!(provider.id(), "openai-compatible");
+        assert_eq!(provider.dimensions(), 0);
+
+        let vector = provider.embed_query("hello").await.unwrap();
+        assert_eq!(vector, vec![0.5, 0.25, 0.0]);
+        assert_eq!(provider.dimensions(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_vectors_follow_index() {
+        let base_url = serve(vec![http_response(
+            "200 OK",
+            "",
+            r#"{"data":[{"index":1,"embedding":[2.0]},{"index":0,"embedding":[1.0]}]}"#,
+        )])
+        .await;
+
+        let provider = OpenAiEmbedding::compatible(base_url, None, "m".into());
+        let vectors = provider
+            .embed_batch(&["a".to_string(), "b".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![1.0], vec![2.0]]);
+    }
+
+    #[tokio::test]
+    async fn test_short_response_is_rejected() {
+        let base_url = serve(vec![http_response(
+            "200 OK",
+            "",
+            r#"{"data":[{"index":0,"embedding":[1.0]}]}"#,
+        )])
+        .await;
+
+        let provider = OpenAiEmbedding::compatible(base_url, None, "m".into());
+        let err = provider
+            .embed_batch(&["a".to_string(), "b".to_string()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("1 embeddings for 2 inputs"));
+    }
+
+    #[tokio::test]
+    async fn test_error_message() {
+        let base_url = serve(vec![http_response(
+            "401 Unauthorized",
+            "",
+            r#"{"error":{"message":"bad key"}}"#,
+        )])
+        .await;
+
+        let provider = OpenAiEmbedding::new("sk-test".into()).with_base_url(base_url);
+        let err = provider.embed_query("hello").await.unwrap_err();
+        assert!(err.to_string().contains("bad key"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Voyage AI embedding provider.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::EmbeddingProvider;
+use super::batch::{http_client, parse_indexed_vectors, send_with_retry, token_batches};
+
+const VOYAGE_BASE_URL: &str = "https://api.voyageai.com/v1";
+
+/// Token budget per request (the smallest limit across current models is 120k).
+const MAX_BATCH_TOKENS: usize = 100_000;
+
+/// Maximum inputs per request.
+const MAX_BATCH_ITEMS: usize = 1000;
+
+/// Voyage AI embedding provider.
+pub struct VoyageEmbedding {
+    api_key: String,
+    base_url: String,
+    model: String,
+    /// Requested output size; sent as `output_dimension` when set.
+    requested_dimensions: Option<usize>,
+    dimensions: usize,
+    client: reqwest::Client,
+}
+
+impl VoyageEmbedding {
+    pub fn new(api_key: String) -> Self {
+        Self::with_model(api_key, "voyage-3.5".to_string(), 1024)
+    }
+
+    pub fn with_model(api_key: String, model: String, dimensions: usize) -> Self {
+        Self {
+            api_key,
+            base_url: VOYAGE_BASE_URL.to_string(),
+            model,
+            requested_dimensions: None,
+            dimensions,
+            client: http_client(),
+        }
+    }
+
+    /// Override the API base URL.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Request vectors of the given size.
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.requested_dimensions = Some(dimensions);
+        self.dimensions = dimensions;
+        self
+    }
+
+    /// Embed one request's worth of texts with the given input type.
+    async fn embed_request(&self, texts: &[String], input_type: &str) -> Result<Vec<Vec<f32>>> {
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "input": texts,
+            "input_type": input_type,
+        });
+        if let Some(dimensions) = self.requested_dimensions {
+            body["output_dimension"] = dimensions.into();
+        }
+
+        let url = format!("{}/embeddings", self.base_url);
+        let json = send_with_retry("Voyage", || {
+            self.client
+                .post(&url)
+                .bearer_auth(&self.api_key)
+                .json(&body)
+        })
+        .await?;
+
+        parse_indexed_vectors("Voyage", &json, texts.len())
+    }
+
+    async fn embed_texts(&self, texts: &[String], input_type: &str) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for range in token_batches(texts, MAX_BATCH_TOKENS, MAX_BATCH_ITEMS) {
+            embeddings.extend(self.embed_request(&texts[range], input_type).await?);
+        }
+        Ok(embeddings)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for VoyageEmbedding {
+    fn id(&self) -> &str {
+        "voyage"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let batch = self.embed_texts(&[text.to_string()], "query").await?;
+        batch
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Empty embedding result"))
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_texts(texts, "document").await
+    }
+}
@@ -2,14 +2,19 @@
 //!
 //! Provides:
 //! - SQLite-backed vector storage with FTS5 full-text search
-//! - Multiple embedding provider support (OpenAI, Gemini, Voyage)
+//! - Multiple embedding provider support (OpenAI, Gemini, Voyage, OpenAI-compatible
+//!   servers, offline local hashing)
 //! - Markdown-aware chunking with overlap
 //! - Incremental file sync (hash-based change detection)
 //! - Hybrid search (vector similarity + keyword matching)
+//! - IVF approximate-nearest-neighbor index for large stores
+//! - Agent-written notes kept as Markdown files
 
+pub mod ann;
 pub mod chunking;
 pub mod embeddings;
 pub mod manager;
+pub mod notes;
 pub mod search;
 pub mod store;
 pub mod sync;
@@ -1,20 +1,32 @@
 //! Memory manager — integrates storage, embedding, sync, and search.
 
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::embeddings::EmbeddingProvider;
+use aobot_config::{GlobalMemoryConfig, MemorySearchConfig, expand_home};
+
+use crate::chunking::{Chunker, TextChunker};
+use crate::embeddings::{EmbeddingProvider, provider_from_config};
+use crate::notes;
 use crate::search::{MemorySearchResult, hybrid_search};
-use crate::store::MemoryStore;
-use crate::sync::{SyncResult, sync_memory_files};
+use crate::store::{MemoryStore, SESSION_SOURCE, SearchFilter, StoredChunk};
+use crate::sync::{MemorySource, SyncResult, sync_memory_files, sync_paths};
+
+/// Directory for agent-written notes, inside the first memory directory.
+const NOTES_DIR: &str = "notes";
+
+/// Rough number of tokens in one line of Markdown, used to turn the
+/// token-based `[memory]` chunk settings into line counts.
+const TOKENS_PER_LINE: usize = 10;
 
 /// Unified memory manager.
 pub struct MemoryManager {
     store: MemoryStore,
     provider: Box<dyn EmbeddingProvider>,
-    dirs: Vec<PathBuf>,
+    sources: Vec<MemorySource>,
     chunk_max_lines: usize,
     chunk_overlap: usize,
+    search: MemorySearchConfig,
 }
 
 impl MemoryManager {
@@ -22,25 +34,51 @@ impl MemoryManager {
     pub fn new(
         store: MemoryStore,
         provider: Box<dyn EmbeddingProvider>,
-        dirs: Vec<PathBuf>,
+        sources: Vec<MemorySource>,
         chunk_max_lines: usize,
         chunk_overlap: usize,
     ) -> Self {
         Self {
             store,
             provider,
-            dirs,
+            sources,
             chunk_max_lines,
             chunk_overlap,
+            search: MemorySearchConfig::default(),
         }
     }
 
+    /// Set the hybrid search ranking options.
+    pub fn with_search_config(mut self, search: MemorySearchConfig) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Create a memory manager from `[memory]` config, storing the index at `db_path`.
+    pub fn from_config(config: &GlobalMemoryConfig, db_path: &Path) -> Result<Self> {
+        let provider = provider_from_config(config)?;
+        let store = MemoryStore::open(db_path)?;
+        let sources = config
+            .dirs
+            .iter()
+            .map(|d| MemorySource::with_filters(expand_home(&d.path), &d.include, &d.exclude))
+            .collect::<Result<Vec<_>>>()?;
+        let chunk_max_lines = (config.chunk_size / TOKENS_PER_LINE).max(1);
+        // Overlap must stay below the chunk size or chunking never advances
+        let chunk_overlap = (config.chunk_overlap / TOKENS_PER_LINE).min(chunk_max_lines - 1);
+        Ok(
+            Self::new(store, provider, sources, chunk_max_lines, chunk_overlap)
+                .with_search_config(config.search.clone()),
+        )
+    }
+
     /// Sync all configured memory directories.
     pub async fn sync(&self, force: bool) -> Result<SyncResult> {
         sync_memory_files(
             &self.store,
             self.provider.as_ref(),
-            &self.dirs,
+            &self.sources,
+            &|path| self.chunk_metadata(path),
             self.chunk_max_lines,
             self.chunk_overlap,
             force,
@@ -48,12 +86,29 @@ impl MemoryManager {
         .await
     }
 
-    /// Search memory using hybrid search.
+    /// Re-index changed paths inside the memory directories.
+    ///
+    /// Deleted paths are removed from the index.
+    pub async fn sync_paths(&self, paths: &[PathBuf]) -> Result<SyncResult> {
+        sync_paths(
+            &self.store,
+            self.provider.as_ref(),
+            &self.sources,
+            &|path| self.chunk_metadata(path),
+            paths,
+            self.chunk_max_lines,
+            self.chunk_overlap,
+        )
+        .await
+    }
+
+    /// Search memory using hybrid search, restricted to chunks passing `filter`.
     pub async fn search(
         &self,
         query: &str,
         max_results: usize,
         min_score: Option<f32>,
+        filter: &SearchFilter,
     ) -> Result<Vec<MemorySearchResult>> {
         hybrid_search(
             &self.store,
@@ -61,10 +116,77 @@ impl MemoryManager {
             query,
             max_results,
             min_score,
+            filter,
+            &self.search,
         )
         .await
     }
 
+    /// Index conversation text covering a whole gateway session, such as
+    /// its compaction summary, replacing what was indexed for the session
+    /// before.
+    ///
+    /// Chunks are stored with source `"session"` and `session_key`/`owner`
+    /// metadata, so searches can be limited to the owner's conversations.
+    /// Returns the number of chunks added.
+    pub async fn index_session(&self, session_key: &str, owner: &str, text: &str) -> Result<usize> {
+        self.index_session_text(session_key, owner, text, true)
+            .await
+    }
+
+    /// Index one turn of a gateway session, like
+    /// [`index_session`](Self::index_session) but keeping the earlier turns.
+    pub async fn index_session_turn(
+        &self,
+        session_key: &str,
+        owner: &str,
+        text: &str,
+    ) -> Result<usize> {
+        self.index_session_text(session_key, owner, text, false)
+            .await
+    }
+
+    async fn index_session_text(
+        &self,
+        session_key: &str,
+        owner: &str,
+        text: &str,
+        replace: bool,
+    ) -> Result<usize> {
+        let chunks = TextChunker.chunk(text, self.chunk_max_lines, self.chunk_overlap);
+        if chunks.is_empty() {
+            return Ok(0);
+        }
+        let texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+        let embeddings = self.provider.embed_batch(&texts).await?;
+
+        let now = chrono::Utc::now();
+        let path = format!("session:{session_key}");
+        if replace {
+            self.store.delete_chunks_for_path(&path)?;
+        }
+        let metadata = serde_json::json!({ "session_key": session_key, "owner": owner });
+        for (chunk, embedding) in chunks.iter().zip(embeddings) {
+            self.store.upsert_chunk(&StoredChunk {
+                // Identified by content, so repeating a turn doesn't
+                // duplicate it
+                id: format!("{path}::{}", chunk.hash),
+                path: path.clone(),
+                source: SESSION_SOURCE.to_string(),
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                hash: chunk.hash.clone(),
+                model: self.provider.model().to_string(),
+                text: chunk.text.clone(),
+                embedding,
+                updated_at: now.timestamp(),
+                metadata: Some(metadata.clone()),
+            })?;
+        }
+        self.store.maybe_rebuild_ann()?;
+        Ok(chunks.len())
+    }
+
     /// Get a chunk by ID.
     pub fn get_chunk(&self, id: &str) -> Result<Option<crate::store::StoredChunk>> {
         self.store.get_chunk(id)
@@ -74,4 +196,664 @@ impl MemoryManager {
     pub fn store(&self) -> &MemoryStore {
         &self.store
     }
+
+    /// Directory agent-written notes are stored in: `notes/` inside the
+    /// first configured memory directory. `None` if only files are configured.
+    pub fn notes_dir(&self) -> Option<PathBuf> {
+        self.sources
+            .iter()
+            .find(|s| !s.path.is_file())
+            .map(|s| s.path.join(NOTES_DIR))
+    }
+
+    /// Append a note to `file` (relative to [`notes_dir`](Self::notes_dir),
+    /// e.g. "users/alice.md") and index it. Returns the note id.
+    pub async fn write_note(&self, file: &str, text: &str) -> Result<String> {
+        let path = self.note_path(file)?;
+        if !self.should_index(&path) {
+            return Err(anyhow::anyhow!(
+                "Notes file {} is excluded by the memory directory filters",
+                path.display()
+            ));
+        }
+        let id = notes::append_note(&path, text)?;
+        self.sync_paths(&[path]).await?;
+        Ok(id)
+    }
+
+    /// Remove notes from the given notes files, by note id or by the id of
+    /// a chunk in one of those files (every note the chunk overlaps is
+    /// removed). Returns the removed note ids.
+    pub async fn forget_note(&self, files: &[String], id: &str) -> Result<Vec<String>> {
+        let chunk = self.store.get_chunk(id)?;
+        let mut removed = Vec::new();
+        for file in files {
+            let path = self.note_path(file)?;
+            let path_str = path.to_string_lossy();
+            let ids = notes::remove_notes(&path, |note| {
+                note.id == id
+                    || chunk.as_ref().is_some_and(|c| {
+                        c.path == path_str
+                            && note.start_line <= c.end_line
+                            && c.start_line <= note.end_line
+                    })
+            })?;
+            if !ids.is_empty() {
+                self.sync_paths(std::slice::from_ref(&path)).await?;
+                removed.extend(ids);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Metadata for the chunks of a memory file: user notes
+    /// (`notes/users/<stem>.md`) are tagged with their owner's file stem,
+    /// so searches filtered by owner only see the owner's notes.
+    fn chunk_metadata(&self, path: &Path) -> Option<serde_json::Value> {
+        let users_dir = self.notes_dir()?.join(notes::USER_NOTES_DIR);
+        let stem = user_notes_stem(&users_dir, path)?;
+        Some(serde_json::json!({ "owner": stem }))
+    }
+
+    fn note_path(&self, file: &str) -> Result<PathBuf> {
+        let dir = self
+            .notes_dir()
+            .ok_or_else(|| anyhow::anyhow!("No memory directory configured for notes"))?;
+        Ok(dir.join(file))
+    }
+
+    /// Open `path` for reading through memory tools by `owner`.
+    ///
+    /// Relative paths are looked up in the memory directories. After
+    /// resolving symlinks and `..`, the file must lie inside one of the
+    /// memory sources. Files the index knows about are also allowed (e.g.
+    /// from a directory since removed from the config) as long as their
+    /// path contains no symlinks. Per-user notes are only readable by their
+    /// owner. Every failure, including a missing file, yields the same
+    /// error so callers can't probe the filesystem.
+    ///
+    /// The returned handle is the file that was checked, even if the path
+    /// is replaced in the meantime.
+    pub fn open_for_read(&self, path: &str, owner: &str) -> Result<std::fs::File> {
+        let denied = || anyhow::anyhow!("Access denied: {path} is not a readable memory file");
+        let resolved = self.resolve_tool_path(path).ok_or_else(denied)?;
+        let canonical = std::fs::canonicalize(&resolved).map_err(|_| denied())?;
+        let checked = std::fs::metadata(&canonical).map_err(|_| denied())?;
+        if !checked.is_file() {
+            return Err(denied());
+        }
+
+        let users_dir = self
+            .notes_dir()
+            .and_then(|dir| std::fs::canonicalize(dir.join(notes::USER_NOTES_DIR)).ok());
+        if let Some(stem) = users_dir.and_then(|dir| user_notes_stem(&dir, &canonical))
+            && stem != notes::file_stem(owner)
+        {
+            return Err(denied());
+        }
+
+        let in_source = self
+            .sources
+            .iter()
+            .any(|s| std::fs::canonicalize(&s.path).is_ok_and(|root| canonical.starts_with(root)));
+        let indexed =
+            || -> Result<bool> {
+                Ok(canonical == resolved
+                    && self.store.get_file(&resolved.to_string_lossy())?.is_some())
+            };
+        if !in_source && !indexed()? {
+            return Err(denied());
+        }
+
+        let file = std::fs::File::open(&canonical).map_err(|_| denied())?;
+        let opened = file.metadata().map_err(|_| denied())?;
+        if !same_file(&checked, &opened) {
+            return Err(denied());
+        }
+        Ok(file)
+    }
+
+    /// A path given to a memory tool: absolute paths as they are, relative
+    /// ones in the first memory directory they exist in.
+    fn resolve_tool_path(&self, path: &str) -> Option<PathBuf> {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return Some(path.to_path_buf());
+        }
+        self.sources
+            .iter()
+            .filter(|s| s.path.is_dir())
+            .map(|s| s.path.join(path))
+            .find(|p| p.exists())
+    }
+
+    /// Directories (or files) indexed by this manager.
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        self.sources.iter().map(|s| s.path.clone()).collect()
+    }
+
+    /// Whether a changed file should be indexed by this manager.
+    pub fn should_index(&self, path: &Path) -> bool {
+        self.sources.iter().any(|s| s.matches(path))
+    }
+}
+
+/// Owner file stem of a file in the per-user notes directory `users_dir`:
+/// the stem of `users/<stem>.md`, or of the first directory below
+/// `users_dir`. `None` if `path` is outside `users_dir`.
+fn user_notes_stem(users_dir: &Path, path: &Path) -> Option<String> {
+    let first = path.strip_prefix(users_dir).ok()?.components().next()?;
+    let stem = Path::new(first.as_os_str()).file_stem()?;
+    Some(stem.to_string_lossy().to_string())
+}
+
+/// Whether two metadata describe the same file.
+#[cfg(unix)]
+fn same_file(a: &std::fs::Metadata, b: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Whether two metadata describe the same file. Without inode numbers,
+/// only the kind of file can be compared.
+#[cfg(not(unix))]
+fn same_file(a: &std::fs::Metadata, b: &std::fs::Metadata) -> bool {
+    a.file_type() == b.file_type()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::LocalEmbedding;
+
+    #[tokio::test]
+    async fn test_sync_and_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        std::fs::create_dir(&notes).unwrap();
+        std::fs::write(
+            notes.join("deploy.md"),
+            "# Deploy\n\nRun the release pipeline.\n\n# Rollback\n\nRevert the tag.",
+        )
+        .unwrap();
+
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(notes)],
+            50,
+            0,
+        );
+
+        let result = manager.sync(false).await.unwrap();
+        assert_eq!(result.files_updated, 1);
+        assert_eq!(result.chunks_added, 2);
+        assert!(result.fts_consistent);
+
+        let results = manager
+            .search("rollback", 5, None, &SearchFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(results[0].start_line, 5);
+        assert!(results[0].path.ends_with("deploy.md"));
+        assert!(results[0].text.contains("Revert"));
+        assert_eq!(results[0].source, crate::search::SearchSource::Hybrid);
+        assert_eq!(results[0].text_rank, Some(1));
+
+        // Any result count is accepted
+        let results = manager
+            .search("rollback", usize::MAX, None, &SearchFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sync_paths_tracks_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        std::fs::create_dir(&notes).unwrap();
+        let old = notes.join("old.md");
+        let new = notes.join("new.md");
+        std::fs::write(&old, "# Oncall\n\nPage the platform team.").unwrap();
+
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(notes.clone())],
+            50,
+            0,
+        );
+        manager.sync(false).await.unwrap();
+
+        // Unchanged files are skipped
+        let result = manager
+            .sync_paths(std::slice::from_ref(&old))
+            .await
+            .unwrap();
+        assert_eq!(result.files_updated, 0);
+
+        // Rename: the old path disappears, the new one is indexed
+        std::fs::rename(&old, &new).unwrap();
+        let result = manager
+            .sync_paths(&[old.clone(), new.clone()])
+            .await
+            .unwrap();
+        assert_eq!(result.files_removed, 1);
+        assert_eq!(result.files_updated, 1);
+        let store = manager.store();
+        assert!(store.get_file(&old.to_string_lossy()).unwrap().is_none());
+        let paths: Vec<String> = store
+            .all_chunks()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.path)
+            .collect();
+        assert_eq!(paths, vec![new.to_string_lossy().to_string()]);
+
+        // Files deleted while not watching are pruned by a full sync
+        std::fs::remove_file(&new).unwrap();
+        let result = manager.sync(false).await.unwrap();
+        assert_eq!(result.files_removed, 1);
+        assert!(manager.store().all_chunks().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sync_skips_failed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        std::fs::create_dir(&notes).unwrap();
+        std::fs::write(notes.join("broken.pdf"), "not a pdf").unwrap();
+        std::fs::write(notes.join("deploy.md"), "# Deploy\n\nRun the pipeline.").unwrap();
+
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(notes.clone())],
+            50,
+            0,
+        );
+
+        let result = manager.sync(false).await.unwrap();
+        assert_eq!(result.files_scanned, 2);
+        assert_eq!(result.files_failed, 1);
+        assert_eq!(result.files_updated, 1);
+        assert!(
+            manager
+                .store()
+                .get_file(&notes.join("broken.pdf").to_string_lossy())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_reembeds_after_provider_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        std::fs::create_dir(&notes).unwrap();
+        std::fs::write(notes.join("deploy.md"), "# Deploy\n\nRun the pipeline.").unwrap();
+        let db = dir.path().join("memory.db");
+        let manager = |dimensions| {
+            MemoryManager::new(
+                MemoryStore::open(&db).unwrap(),
+                Box::new(LocalEmbedding::new(dimensions)),
+                vec![MemorySource::new(notes.clone())],
+                50,
+                0,
+            )
+        };
+
+        manager(64).sync(false).await.unwrap();
+        assert_eq!(manager(64).sync(false).await.unwrap().files_updated, 0);
+
+        // Unchanged files are re-embedded with the new model
+        let switched = manager(128);
+        let result = switched.sync(false).await.unwrap();
+        assert_eq!(result.files_updated, 1);
+        let chunks = switched.store().all_chunks().unwrap();
+        assert!(!chunks.is_empty());
+        assert!(
+            chunks
+                .iter()
+                .all(|c| c.model == "local-hash-128" && c.embedding.len() == 128)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_applies_filters_and_chunkers() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::create_dir_all(repo.join("target/debug")).unwrap();
+        std::fs::write(repo.join("src/lib.rs"), "/// Entry.\npub fn run() {}\n").unwrap();
+        std::fs::write(repo.join("target/debug/build.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(repo.join("NOTES.md"), "# Notes\n\nShip it.").unwrap();
+        std::fs::write(repo.join("data.json"), "{\"a\": 1}").unwrap();
+
+        let source = MemorySource::with_filters(
+            repo.clone(),
+            &["*.rs".to_string(), "*.md".to_string()],
+            &["target".to_string()],
+        )
+        .unwrap();
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![source],
+            50,
+            0,
+        );
+
+        let result = manager.sync(false).await.unwrap();
+        assert_eq!(result.files_scanned, 2);
+        let mut sources: Vec<String> = manager
+            .store()
+            .all_chunks()
+            .unwrap()
+            .into_iter()
+            .map(|c| c.source)
+            .collect();
+        sources.sort();
+        assert_eq!(sources, vec!["code", "markdown"]);
+
+        assert!(manager.should_index(&repo.join("src/main.rs")));
+        assert!(!manager.should_index(&repo.join("target/debug/build.rs")));
+        assert!(!manager.should_index(&repo.join("data.json")));
+        assert!(!manager.should_index(&dir.path().join("elsewhere.md")));
+
+        // Invalid patterns are reported
+        assert!(MemorySource::with_filters(repo, &["[".to_string()], &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_index_session_is_scoped_to_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let manager = MemoryManager::new(store, Box::new(LocalEmbedding::default()), vec![], 50, 0);
+
+        let added = manager
+            .index_session(
+                "telegram:1",
+                "telegram:alice",
+                "user: where is the vpn config?\nassistant: In the ops vault.",
+            )
+            .await
+            .unwrap();
+        assert_eq!(added, 1);
+        manager
+            .index_session("slack:2", "slack:bob", "user: vpn is down again")
+            .await
+            .unwrap();
+        assert_eq!(
+            manager
+                .index_session("slack:2", "slack:bob", "")
+                .await
+                .unwrap(),
+            0
+        );
+
+        let filter = SearchFilter {
+            sources: vec![SESSION_SOURCE.to_string()],
+            owner: Some("telegram:alice".to_string()),
+        };
+        let results = manager.search("vpn", 5, None, &filter).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_source, SESSION_SOURCE);
+        assert_eq!(results[0].session_key.as_deref(), Some("telegram:1"));
+        assert!(results[0].text.contains("ops vault"));
+
+        // Without an owner every session is visible
+        let results = manager
+            .search("vpn", 5, None, &SearchFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_index_session_replaces_and_turns_append() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let manager = MemoryManager::new(store, Box::new(LocalEmbedding::default()), vec![], 50, 0);
+        let session_texts = |manager: &MemoryManager| -> Vec<String> {
+            let mut texts: Vec<String> = manager
+                .store()
+                .all_chunks()
+                .unwrap()
+                .into_iter()
+                .filter(|c| c.path == "session:main")
+                .map(|c| c.text)
+                .collect();
+            texts.sort();
+            texts
+        };
+
+        // Turns indexed back to back are all kept, without duplicates
+        for turn in ["user: one", "user: two", "user: one"] {
+            manager
+                .index_session_turn("main", "main", turn)
+                .await
+                .unwrap();
+        }
+        assert_eq!(session_texts(&manager), ["user: one", "user: two"]);
+
+        // A summary replaces everything indexed for the session
+        manager
+            .index_session("main", "main", "summary: one")
+            .await
+            .unwrap();
+        manager
+            .index_session("main", "main", "summary: one and two")
+            .await
+            .unwrap();
+        assert_eq!(session_texts(&manager), ["summary: one and two"]);
+    }
+
+    #[tokio::test]
+    async fn test_write_and_forget_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes");
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(dir.path().to_path_buf())],
+            50,
+            0,
+        );
+        assert_eq!(manager.notes_dir(), Some(notes.clone()));
+
+        let file = "users/alice.md".to_string();
+        let first = manager
+            .write_note(&file, "Prefers answers in Spanish.")
+            .await
+            .unwrap();
+        manager
+            .write_note(&file, "Deploys happen on Tuesdays.")
+            .await
+            .unwrap();
+        let results = manager
+            .search("spanish", 5, None, &SearchFilter::default())
+            .await
+            .unwrap();
+        assert!(results[0].path.ends_with("users/alice.md"));
+        let chunk_id = results[0].chunk_id.clone();
+
+        // Forgetting is limited to the given files
+        let other = vec!["users/bob.md".to_string()];
+        assert!(
+            manager
+                .forget_note(&other, &first)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let files = vec![file];
+        assert_eq!(
+            manager.forget_note(&files, &chunk_id).await.unwrap(),
+            vec![first]
+        );
+        let results = manager
+            .search("spanish", 5, None, &SearchFilter::default())
+            .await
+            .unwrap();
+        assert!(results.iter().all(|r| !r.text.contains("Spanish")));
+        assert!(results.iter().any(|r| r.text.contains("Tuesdays")));
+    }
+
+    #[tokio::test]
+    async fn test_user_notes_are_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let store = MemoryStore::open(&root.join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(root.clone())],
+            50,
+            0,
+        );
+        for (owner, text) in [
+            ("telegram:alice", "Alice's vpn password hint is the cat."),
+            ("slack:bob", "Bob's vpn is set up on the laptop."),
+        ] {
+            manager
+                .write_note(&notes::user_notes_file(owner), text)
+                .await
+                .unwrap();
+        }
+        manager
+            .write_note("agents/main.md", "The vpn runs on wireguard.")
+            .await
+            .unwrap();
+
+        let filter = SearchFilter {
+            owner: Some("telegram:alice".to_string()),
+            ..Default::default()
+        };
+        let results = manager.search("vpn", 10, None, &filter).await.unwrap();
+        let texts: Vec<&str> = results.iter().map(|r| r.text.as_str()).collect();
+        assert!(texts.iter().any(|t| t.contains("Alice")));
+        assert!(texts.iter().any(|t| t.contains("wireguard")));
+        assert!(texts.iter().all(|t| !t.contains("Bob")));
+
+        let users = root.join("notes/users");
+        let alice = root
+            .join("notes")
+            .join(notes::user_notes_file("telegram:alice"));
+        let bob = root.join("notes").join(notes::user_notes_file("slack:bob"));
+        let get = |p: &Path, owner| manager.open_for_read(&p.to_string_lossy(), owner);
+        assert!(get(&alice, "telegram:alice").is_ok());
+        assert!(get(&bob, "telegram:alice").is_err());
+        assert!(get(&users.join("../users/slack%3Abob.md"), "telegram:alice").is_err());
+        assert!(get(&bob, "slack:bob").is_ok());
+        assert!(get(&root.join("notes/agents/main.md"), "telegram:alice").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_similar_owners_stay_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let store = MemoryStore::open(&root.join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(root.clone())],
+            50,
+            0,
+        );
+        // Both owners would map to `web_a_b.com` if unsafe characters were
+        // simply replaced
+        let (first, second) = ("web:a@b.com", "web:a_b.com");
+        for (owner, text) in [
+            (first, "The first owner's locker code is 1234."),
+            (second, "The second owner's locker is on floor two."),
+        ] {
+            manager
+                .write_note(&notes::user_notes_file(owner), text)
+                .await
+                .unwrap();
+        }
+
+        let filter = SearchFilter {
+            owner: Some(first.to_string()),
+            ..Default::default()
+        };
+        let results = manager.search("locker", 10, None, &filter).await.unwrap();
+        assert!(results.iter().any(|r| r.text.contains("first")));
+        assert!(results.iter().all(|r| !r.text.contains("second")));
+
+        let second_file = root.join("notes").join(notes::user_notes_file(second));
+        assert!(
+            manager
+                .open_for_read(&second_file.to_string_lossy(), first)
+                .is_err()
+        );
+        assert!(
+            manager
+                .open_for_read(&second_file.to_string_lossy(), second)
+                .is_ok()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_open_for_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let notes = root.join("notes");
+        let removed = root.join("removed");
+        std::fs::create_dir(&notes).unwrap();
+        std::fs::create_dir(&removed).unwrap();
+        std::fs::write(notes.join("ok.md"), "# Ok").unwrap();
+        std::fs::write(removed.join("old.md"), "# Old").unwrap();
+        let secret = root.join("secret.toml");
+        std::fs::write(&secret, "token = \"x\"").unwrap();
+        std::os::unix::fs::symlink(&secret, notes.join("escape.md")).unwrap();
+
+        let store = MemoryStore::open(&root.join("memory.db")).unwrap();
+        let manager = MemoryManager::new(
+            store,
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(removed.clone())],
+            50,
+            0,
+        );
+        manager.sync(false).await.unwrap();
+        // The config now only lists `notes`; `removed` is still indexed
+        let manager = MemoryManager::new(
+            MemoryStore::open(&root.join("memory.db")).unwrap(),
+            Box::new(LocalEmbedding::default()),
+            vec![MemorySource::new(notes.clone())],
+            50,
+            0,
+        );
+
+        let allowed =
+            |p: &std::path::Path| manager.open_for_read(&p.to_string_lossy(), "telegram:1");
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut allowed(&notes.join("ok.md")).unwrap(), &mut content)
+            .unwrap();
+        assert_eq!(content, "# Ok");
+        assert!(allowed(&removed.join("old.md")).is_ok());
+        assert!(allowed(&secret).is_err());
+        assert!(allowed(&notes.join("escape.md")).is_err());
+        assert!(allowed(&notes.join("../secret.toml")).is_err());
+        assert!(allowed(&notes.join("missing.md")).is_err());
+        assert!(allowed(&notes).is_err());
+
+        // Relative paths are looked up in the memory directories, not the
+        // working directory
+        let relative = |p: &str| manager.open_for_read(p, "telegram:1");
+        assert!(relative("ok.md").is_ok());
+        assert!(relative("../secret.toml").is_err());
+        assert!(relative("Cargo.toml").is_err());
+        assert!(relative("missing.md").is_err());
+    }
 }
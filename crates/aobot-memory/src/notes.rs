@@ -0,0 +1,225 @@
+//! Agent-written notes stored as Markdown files.
+//!
+//! Notes are appended to a Markdown file as `## note-<id>` sections, so
+//! they are indexed like any other memory file and stay readable and
+//! editable by hand:
+//!
+//! ```markdown
+//! # Memory notes
+//!
+//! ## note-20250101120000000
+//!
+//! Prefers answers in Spanish.
+//! ```
+
+use anyhow::Result;
+use std::path::Path;
+
+/// Heading prefix that starts a note section.
+const NOTE_HEADING: &str = "## note-";
+
+/// First line of a new notes file.
+const FILE_HEADER: &str = "# Memory notes\n";
+
+/// A note section within a notes file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// Note id, e.g. "note-20250101120000000".
+    pub id: String,
+    /// Note body, without the heading.
+    pub text: String,
+    /// First line of the section (1-based, the heading).
+    pub start_line: usize,
+    /// Last line of the section (1-based, inclusive).
+    pub end_line: usize,
+}
+
+/// Parse the note sections of a notes file.
+pub fn parse_notes(content: &str) -> Vec<Note> {
+    let lines: Vec<&str> = content.lines().collect();
+    let starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.starts_with(NOTE_HEADING))
+        .map(|(i, _)| i)
+        .collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &start)| {
+            let end = starts.get(n + 1).copied().unwrap_or(lines.len());
+            Note {
+                id: lines[start][3..].trim().to_string(),
+                text: lines[start + 1..end].join("\n").trim().to_string(),
+                start_line: start + 1,
+                end_line: end,
+            }
+        })
+        .collect()
+}
+
+/// Append a note to the notes file at `path`, creating the file (and its
+/// parent directories) if needed. Returns the new note's id.
+pub fn append_note(path: &Path, text: &str) -> Result<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow::anyhow!("Note text is empty"));
+    }
+
+    let mut content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            FILE_HEADER.to_string()
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let existing: Vec<String> = parse_notes(&content).into_iter().map(|n| n.id).collect();
+    let base = format!("note-{}", chrono::Utc::now().format("%Y%m%d%H%M%S%3f"));
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while existing.contains(&id) {
+        id = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("\n## {id}\n\n{}\n", escape_headings(text)));
+    std::fs::write(path, content)?;
+    Ok(id)
+}
+
+/// Remove the notes selected by `remove` from the notes file at `path`.
+/// Returns the ids of the removed notes; the file is left untouched if
+/// nothing matched or it doesn't exist.
+pub fn remove_notes(path: &Path, remove: impl Fn(&Note) -> bool) -> Result<Vec<String>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let removed: Vec<Note> = parse_notes(&content).into_iter().filter(remove).collect();
+    if removed.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut kept: Vec<&str> = content
+        .lines()
+        .enumerate()
+        .filter(|(i, _)| {
+            !removed
+                .iter()
+                .any(|n| (n.start_line..=n.end_line).contains(&(i + 1)))
+        })
+        .map(|(_, l)| l)
+        .collect();
+    while kept.last().is_some_and(|l| l.trim().is_empty()) {
+        kept.pop();
+    }
+    std::fs::write(path, kept.join("\n") + "\n")?;
+    Ok(removed.into_iter().map(|n| n.id).collect())
+}
+
+/// Directory (relative to the notes directory) holding per-user notes.
+pub const USER_NOTES_DIR: &str = "users";
+
+/// Notes file (relative to the notes directory) for a memory owner.
+pub fn user_notes_file(owner: &str) -> String {
+    format!("{USER_NOTES_DIR}/{}.md", file_stem(owner))
+}
+
+/// Make a name safe to use as a notes file stem.
+///
+/// Bytes other than lowercase letters, digits, `-` and `_` are written as
+/// `%XX`, so distinct names get distinct stems, even on case-insensitive
+/// filesystems.
+pub fn file_stem(name: &str) -> String {
+    let mut stem = String::with_capacity(name.len());
+    for b in name.bytes() {
+        if b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_' {
+            stem.push(b as char);
+        } else {
+            stem.push_str(&format!("%{b:02X}"));
+        }
+    }
+    if stem.is_empty() {
+        stem.push('%');
+    }
+    stem
+}
+
+/// Escape lines in a note body that would start a new note section.
+fn escape_headings(text: &str) -> String {
+    text.lines()
+        .map(|l| {
+            if l.starts_with(NOTE_HEADING) {
+                format!("\\{l}")
+            } else {
+                l.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_parse_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users/alice.md");
+
+        let first = append_note(&path, "Prefers Spanish.").unwrap();
+        let second =
+            append_note(&path, "Works on the billing team.\n## note-x is not a note").unwrap();
+        assert_ne!(first, second);
+        assert!(append_note(&path, "  ").is_err());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(FILE_HEADER));
+        let notes = parse_notes(&content);
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].id, first);
+        assert_eq!(notes[0].text, "Prefers Spanish.");
+        assert!(notes[1].text.ends_with("\\## note-x is not a note"));
+        assert_eq!(
+            content.lines().nth(notes[1].start_line - 1).unwrap(),
+            format!("## {second}")
+        );
+
+        let removed = remove_notes(&path, |n| n.id == first).unwrap();
+        assert_eq!(removed, vec![first]);
+        let notes = parse_notes(&std::fs::read_to_string(&path).unwrap());
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].id, second);
+
+        assert!(remove_notes(&path, |_| false).unwrap().is_empty());
+        assert!(
+            remove_notes(&dir.path().join("missing.md"), |_| true)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_file_stem() {
+        assert_eq!(file_stem("telegram:12345"), "telegram%3A12345");
+        assert_eq!(file_stem("../etc/passwd"), "%2E%2E%2Fetc%2Fpasswd");
+        assert_eq!(file_stem(".."), "%2E%2E");
+        assert_eq!(file_stem(""), "%");
+        assert_eq!(file_stem("main"), "main");
+        assert_eq!(file_stem("Bob"), "%42ob");
+        // Names that differ only in characters that are escaped
+        assert_ne!(file_stem("web:a@b.com"), file_stem("web:a_b.com"));
+        assert_ne!(file_stem("web:a_b"), file_stem("web:a%5Fb"));
+    }
+}
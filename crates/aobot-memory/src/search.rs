@@ -1,106 +1,225 @@
 //! Hybrid search combining vector similarity and full-text search.
+//!
+//! The two rankings are merged with weighted reciprocal rank fusion (RRF)
+//! and can optionally be diversified with maximal marginal relevance (MMR).
 
 use anyhow::Result;
+use serde::Serialize;
 use std::collections::HashMap;
+use tracing::warn;
+
+use aobot_config::MemorySearchConfig;
 
 use crate::embeddings::EmbeddingProvider;
-use crate::store::MemoryStore;
+use crate::store::{MemoryStore, SearchFilter};
+
+/// Candidates fetched from each ranking per requested result.
+const CANDIDATES_PER_RESULT: usize = 4;
 
 /// A search result from hybrid search.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MemorySearchResult {
     pub chunk_id: String,
     pub path: String,
     pub start_line: usize,
     pub end_line: usize,
     pub text: String,
+    /// Fused score, normalized so 1.0 means ranked first by every signal.
     pub score: f32,
     pub source: SearchSource,
+    /// What produced the chunk: a chunker name ("markdown", "code", ...)
+    /// or "session" for conversation memory.
+    pub chunk_source: String,
+    /// Session a conversation chunk was recorded in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_key: Option<String>,
+    /// Cosine similarity to the query, if found by vector search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_score: Option<f32>,
+    /// 1-based rank in the vector ranking.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vector_rank: Option<usize>,
+    /// BM25 score (higher is better), if found by full-text search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_score: Option<f32>,
+    /// 1-based rank in the full-text ranking.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_rank: Option<usize>,
 }
 
 /// Source of the search result.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SearchSource {
     Vector,
     FullText,
     Hybrid,
 }
 
+/// Per-signal rank and score of a candidate.
+#[derive(Debug, Default)]
+struct Signals {
+    vector: Option<(usize, f32)>,
+    text: Option<(usize, f32)>,
+}
+
 /// Perform hybrid search: vector similarity + FTS5 keyword matching.
+///
+/// `min_score` applies to the fused score. Only chunks passing `filter`
+/// are considered.
 pub async fn hybrid_search(
     store: &MemoryStore,
     provider: &dyn EmbeddingProvider,
     query: &str,
     max_results: usize,
     min_score: Option<f32>,
+    filter: &SearchFilter,
+    options: &MemorySearchConfig,
 ) -> Result<Vec<MemorySearchResult>> {
     let min_score = min_score.unwrap_or(0.0);
+    let candidates = max_results.saturating_mul(CANDIDATES_PER_RESULT);
 
-    // Vector search
+    // Vector search (ANN candidates, or an exact scan for small stores)
     let query_embedding = provider.embed_query(query).await?;
-    let all_chunks = store.all_chunks()?;
+    let vector_results = store.vector_search(&query_embedding, candidates, filter)?;
 
-    let mut vector_scores: HashMap<String, f32> = HashMap::new();
-    for chunk in &all_chunks {
-        let score = cosine_similarity(&query_embedding, &chunk.embedding);
-        if score >= min_score {
-            vector_scores.insert(chunk.id.clone(), score);
+    // FTS search; FTS5 rank is BM25 negated (lower = better). A failing
+    // full-text query (e.g. bad advanced syntax) leaves vector results only.
+    let fts_results = match store.fts_search(query, options.text_mode, candidates, filter) {
+        Ok(results) => results,
+        Err(e) => {
+            warn!("Memory full-text search failed, using vector search only: {e}");
+            vec![]
         }
-    }
+    };
 
-    // FTS search
-    let fts_results = store.fts_search(query, max_results * 2)?;
-    let mut fts_scores: HashMap<String, f32> = HashMap::new();
-    for (id, rank) in &fts_results {
-        // FTS5 rank is negative (lower = better), normalize to 0..1
-        let score = 1.0 / (1.0 + rank.abs() as f32);
-        fts_scores.insert(id.clone(), score);
+    let mut signals: HashMap<String, Signals> = HashMap::new();
+    for (i, (id, score)) in vector_results.into_iter().enumerate() {
+        signals.entry(id).or_default().vector = Some((i + 1, score));
     }
-
-    // Merge results with reciprocal rank fusion
-    let mut combined: HashMap<String, f32> = HashMap::new();
-    for (id, score) in &vector_scores {
-        *combined.entry(id.clone()).or_default() += score * 0.7; // 70% weight to vector
-    }
-    for (id, score) in &fts_scores {
-        *combined.entry(id.clone()).or_default() += score * 0.3; // 30% weight to FTS
+    for (i, (id, rank)) in fts_results.into_iter().enumerate() {
+        signals.entry(id).or_default().text = Some((i + 1, -rank as f32));
     }
 
-    // Sort by combined score
-    let mut ranked: Vec<(String, f32)> = combined.into_iter().collect();
-    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-    ranked.truncate(max_results);
+    // Weighted reciprocal rank fusion, normalized to 0..1
+    let k = options.rrf_k.max(0.0);
+    let rrf = |rank: usize| 1.0 / (k + rank as f32);
+    let best = (options.vector_weight + options.text_weight) * rrf(1);
+    let mut ranked: Vec<(String, f32)> = signals
+        .iter()
+        .map(|(id, s)| {
+            let fused = s
+                .vector
+                .map_or(0.0, |(r, _)| options.vector_weight * rrf(r))
+                + s.text.map_or(0.0, |(r, _)| options.text_weight * rrf(r));
+            let score = if best > 0.0 { fused / best } else { 0.0 };
+            (id.clone(), score)
+        })
+        .filter(|(_, score)| *score >= min_score)
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if !options.mmr {
+        ranked.truncate(max_results);
+    }
 
     // Build results
     let mut results = Vec::new();
+    let mut embeddings = Vec::new();
     for (id, score) in ranked {
-        if let Some(chunk) = store.get_chunk(&id)? {
-            let source = match (
-                vector_scores.contains_key(&id),
-                fts_scores.contains_key(&id),
-            ) {
-                (true, true) => SearchSource::Hybrid,
-                (true, false) => SearchSource::Vector,
-                (false, true) => SearchSource::FullText,
-                (false, false) => continue,
-            };
-            results.push(MemorySearchResult {
-                chunk_id: id,
-                path: chunk.path,
-                start_line: chunk.start_line,
-                end_line: chunk.end_line,
-                text: chunk.text,
-                score,
-                source,
-            });
-        }
+        let Some(chunk) = store.get_chunk(&id)? else {
+            continue;
+        };
+        let s = &signals[&id];
+        let source = match (s.vector.is_some(), s.text.is_some()) {
+            (true, true) => SearchSource::Hybrid,
+            (true, false) => SearchSource::Vector,
+            _ => SearchSource::FullText,
+        };
+        results.push(MemorySearchResult {
+            chunk_id: id,
+            path: chunk.path,
+            start_line: chunk.start_line,
+            end_line: chunk.end_line,
+            text: chunk.text,
+            score,
+            source,
+            chunk_source: chunk.source,
+            session_key: chunk
+                .metadata
+                .as_ref()
+                .and_then(|m| m.get("session_key"))
+                .and_then(|k| k.as_str())
+                .map(str::to_string),
+            vector_score: s.vector.map(|(_, score)| score),
+            vector_rank: s.vector.map(|(rank, _)| rank),
+            text_score: s.text.map(|(_, score)| score),
+            text_rank: s.text.map(|(rank, _)| rank),
+        });
+        embeddings.push(chunk.embedding);
     }
 
+    if options.mmr {
+        results = mmr_select(results, &embeddings, max_results, options.mmr_lambda);
+    }
     Ok(results)
 }
 
+/// Greedy maximal marginal relevance selection.
+///
+/// Repeatedly picks the candidate maximizing
+/// `lambda * score - (1 - lambda) * max similarity to the picks so far`.
+/// Overlapping chunks of the same file count as fully similar.
+fn mmr_select(
+    candidates: Vec<MemorySearchResult>,
+    embeddings: &[Vec<f32>],
+    max_results: usize,
+    lambda: f32,
+) -> Vec<MemorySearchResult> {
+    let similarity = |a: usize, b: usize| {
+        let (x, y) = (&candidates[a], &candidates[b]);
+        let overlapping =
+            x.path == y.path && x.start_line <= y.end_line && y.start_line <= x.end_line;
+        if overlapping {
+            1.0
+        } else {
+            cosine_similarity(&embeddings[a], &embeddings[b])
+        }
+    };
+
+    let mut picked: Vec<usize> = Vec::new();
+    let mut remaining: Vec<usize> = (0..candidates.len()).collect();
+    while picked.len() < max_results && !remaining.is_empty() {
+        let (pos, _) = remaining
+            .iter()
+            .enumerate()
+            .map(|(pos, &i)| {
+                let redundancy = picked
+                    .iter()
+                    .map(|&j| similarity(i, j))
+                    .fold(0.0f32, f32::max);
+                (
+                    pos,
+                    lambda * candidates[i].score - (1.0 - lambda) * redundancy,
+                )
+            })
+            // First candidate wins ties, keeping fused order
+            .fold(
+                (0, f32::NEG_INFINITY),
+                |best, c| if c.1 > best.1 { c } else { best },
+            );
+        picked.push(remaining.remove(pos));
+    }
+
+    let mut candidates: Vec<Option<MemorySearchResult>> =
+        candidates.into_iter().map(Some).collect();
+    picked
+        .into_iter()
+        .filter_map(|i| candidates[i].take())
+        .collect()
+}
+
 /// Cosine similarity between two vectors.
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() || a.is_empty() {
         return 0.0;
     }
@@ -119,6 +238,189 @@ fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::embeddings::LocalEmbedding;
+    use crate::store::StoredChunk;
+    use aobot_config::FtsQueryMode;
+
+    async fn store_with(chunks: &[(&str, &str, usize, &str)]) -> (tempfile::TempDir, MemoryStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(&dir.path().join("memory.db")).unwrap();
+        let provider = LocalEmbedding::default();
+        for (id, path, start_line, text) in chunks {
+            store
+                .upsert_chunk(&StoredChunk {
+                    id: id.to_string(),
+                    path: path.to_string(),
+                    source: "local".to_string(),
+                    start_line: *start_line,
+                    end_line: start_line + 9,
+                    hash: "hash".to_string(),
+                    model: provider.model().to_string(),
+                    text: text.to_string(),
+                    embedding: provider.embed_query(text).await.unwrap(),
+                    updated_at: 0,
+                    metadata: None,
+                })
+                .unwrap();
+        }
+        (dir, store)
+    }
+
+    #[tokio::test]
+    async fn test_rrf_scores_and_signals() {
+        let (_dir, store) = store_with(&[
+            ("a", "/a.md", 1, "kafka consumer lag alerts"),
+            ("b", "/b.md", 1, "grafana dashboards for the platform"),
+        ])
+        .await;
+        let provider = LocalEmbedding::default();
+        let options = MemorySearchConfig::default();
+
+        let results = hybrid_search(
+            &store,
+            &provider,
+            "kafka lag",
+            5,
+            None,
+            &SearchFilter::default(),
+            &options,
+        )
+        .await
+        .unwrap();
+        let top = &results[0];
+        assert_eq!(top.chunk_id, "a");
+        assert_eq!(top.source, SearchSource::Hybrid);
+        assert_eq!((top.vector_rank, top.text_rank), (Some(1), Some(1)));
+        assert!(top.text_score.unwrap() > 0.0);
+        // First in both rankings
+        assert!((top.score - 1.0).abs() < 1e-6);
+
+        // min_score filters on the fused score, including FTS-only hits
+        let results = hybrid_search(
+            &store,
+            &provider,
+            "kafka lag",
+            5,
+            Some(0.9),
+            &SearchFilter::default(),
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Full-text only weighting
+        let options = MemorySearchConfig {
+            vector_weight: 0.0,
+            text_weight: 1.0,
+            ..Default::default()
+        };
+        let results = hybrid_search(
+            &store,
+            &provider,
+            "grafana",
+            5,
+            Some(0.01),
+            &SearchFilter::default(),
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk_id, "b");
+    }
+
+    #[tokio::test]
+    async fn test_mmr_skips_overlapping_chunks() {
+        let (_dir, store) = store_with(&[
+            (
+                "a::0",
+                "/a.md",
+                1,
+                "restart the ingest workers after deploy",
+            ),
+            (
+                "a::1",
+                "/a.md",
+                8,
+                "restart the ingest workers after deploy again",
+            ),
+            ("b::0", "/b.md", 1, "ingest backlog runbook"),
+        ])
+        .await;
+        let provider = LocalEmbedding::default();
+        let query = "restart ingest workers";
+
+        let plain = hybrid_search(
+            &store,
+            &provider,
+            query,
+            2,
+            None,
+            &SearchFilter::default(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert!(plain.iter().all(|r| r.path == "/a.md"));
+
+        let options = MemorySearchConfig {
+            mmr: true,
+            ..Default::default()
+        };
+        let diverse = hybrid_search(
+            &store,
+            &provider,
+            query,
+            2,
+            None,
+            &SearchFilter::default(),
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(diverse.len(), 2);
+        assert_eq!(diverse[0].chunk_id, plain[0].chunk_id);
+        assert_eq!(diverse[1].path, "/b.md");
+    }
+
+    #[tokio::test]
+    async fn test_fts_failure_falls_back_to_vector() {
+        let (_dir, store) = store_with(&[("a", "/a.md", 1, "kafka consumer lag alerts")]).await;
+        let provider = LocalEmbedding::default();
+        let options = MemorySearchConfig {
+            text_mode: FtsQueryMode::Advanced,
+            ..Default::default()
+        };
+
+        let results = hybrid_search(
+            &store,
+            &provider,
+            "kafka AND (",
+            5,
+            None,
+            &SearchFilter::default(),
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results[0].chunk_id, "a");
+        assert_eq!(results[0].source, SearchSource::Vector);
+
+        // The same query is fine in plain mode
+        let results = hybrid_search(
+            &store,
+            &provider,
+            "kafka AND (",
+            5,
+            None,
+            &SearchFilter::default(),
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(results[0].source, SearchSource::Hybrid);
+    }
 
     #[test]
     fn test_cosine_similarity_identical() {
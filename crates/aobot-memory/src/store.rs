@@ -1,13 +1,35 @@
 //! SQLite-backed vector storage for memory chunks.
 
 use anyhow::Result;
+use aobot_config::FtsQueryMode;
 use rusqlite::Connection;
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
+
+use crate::ann::{IvfIndex, TRAIN_SAMPLES_PER_LIST};
+use crate::search::cosine_similarity;
+
+/// `StoredChunk::source` of chunks indexed from gateway conversations.
+pub const SESSION_SOURCE: &str = "session";
+
+/// Columns read into a [`StoredChunk`], in `row_to_chunk` order.
+const CHUNK_COLUMNS: &str =
+    "id, path, source, start_line, end_line, hash, model, text, embedding, updated_at, metadata";
+
+/// Stores with fewer chunks than this are searched exhaustively and get
+/// no ANN index.
+pub const DEFAULT_EXACT_SCAN_LIMIT: usize = 10_000;
 
 /// Vector store backed by SQLite with FTS5.
+///
+/// Once the store grows past the exact-scan limit, chunk embeddings are
+/// also clustered into an IVF index (see [`crate::ann`]) so vector search
+/// only scans the clusters closest to the query.
 pub struct MemoryStore {
     conn: Mutex<Connection>,
+    /// Trained ANN centroids. Always locked after `conn`.
+    ann: RwLock<Option<IvfIndex>>,
+    exact_scan_limit: usize,
 }
 
 /// A stored memory chunk.
@@ -23,6 +45,46 @@ pub struct StoredChunk {
     pub text: String,
     pub embedding: Vec<f32>,
     pub updated_at: i64,
+    /// Extra JSON attributes, e.g. `session_key` and `owner` for session
+    /// chunks, or `owner` for user notes.
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Restricts which chunks a search may return.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    /// Only return chunks from these sources (e.g. "markdown", "session").
+    /// Empty means all sources.
+    pub sources: Vec<String>,
+    /// Only return session chunks and user notes belonging to this owner.
+    /// `None` returns every session's chunks and every user's notes.
+    pub owner: Option<String>,
+}
+
+impl SearchFilter {
+    /// SQL condition over the columns of `chunks` (as `alias`) and its parameters.
+    fn to_sql(&self, alias: &str) -> (String, Vec<rusqlite::types::Value>) {
+        let mut conditions = vec!["1".to_string()];
+        let mut params: Vec<rusqlite::types::Value> = Vec::new();
+        if !self.sources.is_empty() {
+            let placeholders = vec!["?"; self.sources.len()].join(", ");
+            conditions.push(format!("{alias}.source IN ({placeholders})"));
+            params.extend(self.sources.iter().cloned().map(Into::into));
+        }
+        if let Some(owner) = &self.owner {
+            // Session chunks carry the owner itself, user notes the owner's
+            // notes file stem. Stems never contain ':' while owners always
+            // do, so one owner's stem can't match another owner's sessions
+            conditions.push(format!(
+                "((json_extract({alias}.metadata, '$.owner') IS NULL AND {alias}.source != ?) \
+                 OR json_extract({alias}.metadata, '$.owner') IN (?, ?))"
+            ));
+            params.push(SESSION_SOURCE.to_string().into());
+            params.push(owner.clone().into());
+            params.push(crate::notes::file_stem(owner).into());
+        }
+        (conditions.join(" AND "), params)
+    }
 }
 
 /// File metadata record.
@@ -67,19 +129,81 @@ impl MemoryStore {
                  model TEXT NOT NULL,
                  text TEXT NOT NULL,
                  embedding BLOB NOT NULL,
-                 updated_at INTEGER NOT NULL
+                 updated_at INTEGER NOT NULL,
+                 ann_list INTEGER,
+                 metadata TEXT
              );
 
-             CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(
-                 text, id UNINDEXED, path UNINDEXED, source UNINDEXED
+             CREATE TABLE IF NOT EXISTS ann_centroids (
+                 list INTEGER PRIMARY KEY,
+                 centroid BLOB NOT NULL
              );",
         )?;
 
+        // Migration: add ANN list assignment to older databases
+        let _ = conn.execute_batch("ALTER TABLE chunks ADD COLUMN ann_list INTEGER;");
+        // Migration: add chunk metadata to older databases
+        let _ = conn.execute_batch("ALTER TABLE chunks ADD COLUMN metadata TEXT;");
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_chunks_ann ON chunks(ann_list);")?;
+
+        // Migration: older databases kept a standalone FTS table that was
+        // never cleaned up on delete. Replace it with the external-content one.
+        let fts_sql: Option<String> = conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'chunks_fts'",
+                [],
+                |row| row.get(0),
+            )
+            .ok();
+        let migrate_fts = fts_sql.is_some_and(|sql| !sql.contains("content="));
+        if migrate_fts {
+            conn.execute_batch("DROP TABLE chunks_fts;")?;
+        }
+
+        // Full-text index over `chunks`, kept in sync by triggers
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts USING fts5(
+                 text, id UNINDEXED, path UNINDEXED, source UNINDEXED,
+                 content = 'chunks', content_rowid = 'rowid'
+             );
+
+             CREATE TRIGGER IF NOT EXISTS chunks_fts_insert AFTER INSERT ON chunks BEGIN
+                 INSERT INTO chunks_fts (rowid, text, id, path, source)
+                 VALUES (new.rowid, new.text, new.id, new.path, new.source);
+             END;
+
+             CREATE TRIGGER IF NOT EXISTS chunks_fts_delete AFTER DELETE ON chunks BEGIN
+                 INSERT INTO chunks_fts (chunks_fts, rowid, text, id, path, source)
+                 VALUES ('delete', old.rowid, old.text, old.id, old.path, old.source);
+             END;
+
+             DROP TRIGGER IF EXISTS chunks_fts_update;
+             CREATE TRIGGER chunks_fts_update AFTER UPDATE OF id, path, source, text ON chunks BEGIN
+                 INSERT INTO chunks_fts (chunks_fts, rowid, text, id, path, source)
+                 VALUES ('delete', old.rowid, old.text, old.id, old.path, old.source);
+                 INSERT INTO chunks_fts (rowid, text, id, path, source)
+                 VALUES (new.rowid, new.text, new.id, new.path, new.source);
+             END;",
+        )?;
+        if migrate_fts {
+            conn.execute_batch("INSERT INTO chunks_fts (chunks_fts) VALUES ('rebuild');")?;
+        }
+
+        let ann = load_centroids(&conn)?;
+
         Ok(Self {
             conn: Mutex::new(conn),
+            ann: RwLock::new(ann),
+            exact_scan_limit: DEFAULT_EXACT_SCAN_LIMIT,
         })
     }
 
+    /// Set the chunk count below which no ANN index is built.
+    pub fn with_exact_scan_limit(mut self, limit: usize) -> Self {
+        self.exact_scan_limit = limit;
+        self
+    }
+
     /// Insert or replace a file record.
     pub fn upsert_file(&self, file: &FileRecord) -> Result<()> {
         let conn = self.conn.lock().unwrap();
@@ -111,25 +235,63 @@ impl MemoryStore {
         }
     }
 
-    /// Insert or replace a chunk.
+    /// Delete a file record. Returns true if it existed.
+    pub fn delete_file(&self, path: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let count = conn.execute("DELETE FROM files WHERE path = ?1", rusqlite::params![path])?;
+        Ok(count > 0)
+    }
+
+    /// List all file records.
+    pub fn list_files(&self) -> Result<Vec<FileRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT path, source, hash, mtime, size FROM files")?;
+        let files = stmt
+            .query_map([], |row| {
+                Ok(FileRecord {
+                    path: row.get(0)?,
+                    source: row.get(1)?,
+                    hash: row.get(2)?,
+                    mtime: row.get(3)?,
+                    size: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(files)
+    }
+
+    /// Insert or update a chunk.
+    ///
+    /// Uses an upsert rather than `INSERT OR REPLACE` so the FTS update
+    /// trigger fires (REPLACE deletes do not run triggers).
+    ///
+    /// The chunk is assigned to its nearest ANN list if an index exists.
     pub fn upsert_chunk(&self, chunk: &StoredChunk) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        let ann_list = self
+            .ann
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|index| index.assign(&chunk.embedding))
+            .map(|list| list as i64);
         let embedding_bytes = embedding_to_bytes(&chunk.embedding);
+        let metadata = chunk.metadata.as_ref().map(|m| m.to_string());
         conn.execute(
-            "INSERT OR REPLACE INTO chunks (id, path, source, start_line, end_line, hash, model, text, embedding, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "INSERT INTO chunks (id, path, source, start_line, end_line, hash, model, text, embedding, updated_at, ann_list, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+             ON CONFLICT(id) DO UPDATE SET
+                 path = excluded.path, source = excluded.source,
+                 start_line = excluded.start_line, end_line = excluded.end_line,
+                 hash = excluded.hash, model = excluded.model, text = excluded.text,
+                 embedding = excluded.embedding, updated_at = excluded.updated_at,
+                 ann_list = excluded.ann_list, metadata = excluded.metadata",
             rusqlite::params![
                 chunk.id, chunk.path, chunk.source, chunk.start_line, chunk.end_line,
-                chunk.hash, chunk.model, chunk.text, embedding_bytes, chunk.updated_at
+                chunk.hash, chunk.model, chunk.text, embedding_bytes, chunk.updated_at,
+                ann_list, metadata
             ],
         )?;
-        // Also update FTS index
-        conn.execute(
-            "INSERT OR REPLACE INTO chunks_fts (rowid, text, id, path, source) VALUES (
-                 (SELECT rowid FROM chunks WHERE id = ?1), ?2, ?1, ?3, ?4
-             )",
-            rusqlite::params![chunk.id, chunk.text, chunk.path, chunk.source],
-        )?;
         Ok(())
     }
 
@@ -146,64 +308,243 @@ impl MemoryStore {
     /// Get all chunks (for vector search).
     pub fn all_chunks(&self) -> Result<Vec<StoredChunk>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, path, source, start_line, end_line, hash, model, text, embedding, updated_at FROM chunks",
-        )?;
+        let mut stmt = conn.prepare(&format!("SELECT {CHUNK_COLUMNS} FROM chunks"))?;
         let chunks = stmt
-            .query_map([], |row| {
-                let embedding_bytes: Vec<u8> = row.get(8)?;
-                Ok(StoredChunk {
-                    id: row.get(0)?,
-                    path: row.get(1)?,
-                    source: row.get(2)?,
-                    start_line: row.get::<_, i64>(3)? as usize,
-                    end_line: row.get::<_, i64>(4)? as usize,
-                    hash: row.get(5)?,
-                    model: row.get(6)?,
-                    text: row.get(7)?,
-                    embedding: bytes_to_embedding(&embedding_bytes),
-                    updated_at: row.get(9)?,
-                })
-            })?
+            .query_map([], row_to_chunk)?
             .collect::<Result<Vec<_>, _>>()?;
         Ok(chunks)
     }
 
-    /// Full-text search using FTS5.
-    pub fn fts_search(&self, query: &str, limit: usize) -> Result<Vec<(String, f64)>> {
+    /// Chunks most similar to `query` by cosine similarity, best first.
+    ///
+    /// Uses the ANN index when one exists: only chunks in the lists
+    /// closest to the query (plus chunks without a list, e.g. embedded
+    /// with a different model) are scored. Otherwise every chunk is
+    /// scanned. Embeddings are decoded one row at a time.
+    pub fn vector_search(
+        &self,
+        query: &[f32],
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<(String, f32)>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, rank FROM chunks_fts WHERE chunks_fts MATCH ?1 ORDER BY rank LIMIT ?2",
+        let lists = self
+            .ann
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|index| index.probe(query, index.default_probes()));
+
+        let candidates = match lists {
+            Some(lists) if !lists.is_empty() => {
+                let lists: Vec<String> = lists.iter().map(usize::to_string).collect();
+                format!("(ann_list IN ({}) OR ann_list IS NULL)", lists.join(", "))
+            }
+            _ => "1".to_string(),
+        };
+        let (filter_sql, params) = filter.to_sql("chunks");
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, embedding FROM chunks WHERE {candidates} AND {filter_sql}"
+        ))?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+        let mut top: Vec<(String, f32)> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let embedding_bytes: Vec<u8> = row.get(1)?;
+            let score = cosine_similarity(query, &bytes_to_embedding(&embedding_bytes));
+            top.push((row.get(0)?, score));
+            // Keep memory bounded on large scans
+            if top.len() >= limit.saturating_mul(2).saturating_add(64) {
+                sort_and_truncate(&mut top, limit);
+            }
+        }
+        sort_and_truncate(&mut top, limit);
+        Ok(top)
+    }
+
+    /// Retrain the ANN index if it is missing or stale.
+    ///
+    /// The index is (re)built once the store reaches the exact-scan limit,
+    /// after it has more than doubled since training, or when most chunks
+    /// have no list (e.g. after switching embedding models). Returns true
+    /// if the index was rebuilt.
+    pub fn maybe_rebuild_ann(&self) -> Result<bool> {
+        let (count, unassigned, trained_count) = {
+            let conn = self.conn.lock().unwrap();
+            let count: i64 = conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
+            let unassigned: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM chunks WHERE ann_list IS NULL",
+                [],
+                |row| row.get(0),
+            )?;
+            let trained_count: Option<String> = conn
+                .query_row(
+                    "SELECT value FROM meta WHERE key = 'ann_trained_count'",
+                    [],
+                    |row| row.get(0),
+                )
+                .ok();
+            let trained_count = trained_count.and_then(|v| v.parse::<usize>().ok());
+            (count as usize, unassigned as usize, trained_count)
+        };
+
+        let stale = match trained_count {
+            Some(trained) if self.ann.read().unwrap().is_some() => {
+                count > trained * 2 || unassigned * 2 > count
+            }
+            _ => count >= self.exact_scan_limit.max(1),
+        };
+        if stale {
+            self.rebuild_ann()?;
+        }
+        Ok(stale)
+    }
+
+    /// Train the ANN index on a random sample of chunks and assign every
+    /// chunk to its nearest list. An empty store drops the index.
+    pub fn rebuild_ann(&self) -> Result<()> {
+        let (count, sample) = {
+            let conn = self.conn.lock().unwrap();
+            let count: i64 = conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
+            let sample_size = IvfIndex::lists_for(count as usize) * TRAIN_SAMPLES_PER_LIST;
+            let mut stmt = conn.prepare(
+                "SELECT embedding FROM chunks WHERE rowid IN
+                     (SELECT rowid FROM chunks ORDER BY random() LIMIT ?1)",
+            )?;
+            let sample = stmt
+                .query_map(rusqlite::params![sample_size as i64], |row| {
+                    let embedding_bytes: Vec<u8> = row.get(0)?;
+                    Ok(bytes_to_embedding(&embedding_bytes))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            (count as usize, sample)
+        };
+
+        // Training is the expensive part; run it without holding the connection
+        let index = IvfIndex::train(&sample, IvfIndex::lists_for(count));
+
+        let mut conn = self.conn.lock().unwrap();
+        let mut ann = self.ann.write().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM ann_centroids", [])?;
+        tx.execute("UPDATE chunks SET ann_list = NULL", [])?;
+        if let Some(index) = &index {
+            for (list, centroid) in index.centroids().iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO ann_centroids (list, centroid) VALUES (?1, ?2)",
+                    rusqlite::params![list as i64, embedding_to_bytes(centroid)],
+                )?;
+            }
+
+            let mut assignments = Vec::new();
+            {
+                let mut stmt = tx.prepare("SELECT rowid, embedding FROM chunks")?;
+                let mut rows = stmt.query([])?;
+                while let Some(row) = rows.next()? {
+                    let embedding_bytes: Vec<u8> = row.get(1)?;
+                    if let Some(list) = index.assign(&bytes_to_embedding(&embedding_bytes)) {
+                        assignments.push((row.get::<_, i64>(0)?, list as i64));
+                    }
+                }
+            }
+            let mut stmt = tx.prepare("UPDATE chunks SET ann_list = ?1 WHERE rowid = ?2")?;
+            for (rowid, list) in assignments {
+                stmt.execute(rusqlite::params![list, rowid])?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('ann_trained_count', ?1)",
+            rusqlite::params![count.to_string()],
         )?;
+        tx.commit()?;
+        *ann = index;
+        Ok(())
+    }
+
+    /// Whether an ANN index is currently loaded.
+    pub fn has_ann_index(&self) -> bool {
+        self.ann.read().unwrap().is_some()
+    }
+
+    /// Full-text search using FTS5.
+    ///
+    /// The query is converted to a match expression according to `mode`
+    /// (see [`fts_match_expression`]). Returns `(id, rank)` pairs, best
+    /// first; the rank is the negated BM25 score.
+    pub fn fts_search(
+        &self,
+        query: &str,
+        mode: FtsQueryMode,
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Result<Vec<(String, f64)>> {
+        let Some(query) = fts_match_expression(query, mode) else {
+            return Ok(vec![]);
+        };
+        let (filter_sql, filter_params) = filter.to_sql("c");
+        let mut params: Vec<rusqlite::types::Value> = vec![query.into()];
+        params.extend(filter_params);
+        params.push(i64::try_from(limit).unwrap_or(i64::MAX).into());
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT c.id, chunks_fts.rank FROM chunks_fts
+             JOIN chunks c ON c.rowid = chunks_fts.rowid
+             WHERE chunks_fts MATCH ? AND {filter_sql}
+             ORDER BY chunks_fts.rank LIMIT ?"
+        ))?;
         let results = stmt
-            .query_map(rusqlite::params![query, limit as i64], |row| {
+            .query_map(rusqlite::params_from_iter(params), |row| {
                 Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
             })?
             .collect::<Result<Vec<_>, _>>()?;
         Ok(results)
     }
 
+    /// Check that the FTS index matches the `chunks` table.
+    pub fn check_fts(&self) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        // rank = 1 also compares the index against the content table
+        match conn.execute(
+            "INSERT INTO chunks_fts (chunks_fts, rank) VALUES ('integrity-check', 1)",
+            [],
+        ) {
+            Ok(_) => Ok(true),
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == rusqlite::ErrorCode::DatabaseCorrupt =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Rebuild the FTS index from the `chunks` table.
+    pub fn rebuild_fts(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("INSERT INTO chunks_fts (chunks_fts) VALUES ('rebuild');")?;
+        Ok(())
+    }
+
+    /// Compact the database file.
+    ///
+    /// VACUUM may renumber the rowids the FTS index is keyed by, so the
+    /// index is rebuilt afterwards.
+    pub fn vacuum(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch(
+            "VACUUM;
+             INSERT INTO chunks_fts (chunks_fts) VALUES ('rebuild');",
+        )?;
+        Ok(())
+    }
+
     /// Get a chunk by ID.
     pub fn get_chunk(&self, id: &str) -> Result<Option<StoredChunk>> {
         let conn = self.conn.lock().unwrap();
-        let mut stmt = conn.prepare(
-            "SELECT id, path, source, start_line, end_line, hash, model, text, embedding, updated_at FROM chunks WHERE id = ?1",
-        )?;
-        let result = stmt.query_row(rusqlite::params![id], |row| {
-            let embedding_bytes: Vec<u8> = row.get(8)?;
-            Ok(StoredChunk {
-                id: row.get(0)?,
-                path: row.get(1)?,
-                source: row.get(2)?,
-                start_line: row.get::<_, i64>(3)? as usize,
-                end_line: row.get::<_, i64>(4)? as usize,
-                hash: row.get(5)?,
-                model: row.get(6)?,
-                text: row.get(7)?,
-                embedding: bytes_to_embedding(&embedding_bytes),
-                updated_at: row.get(9)?,
-            })
-        });
+        let mut stmt =
+            conn.prepare(&format!("SELECT {CHUNK_COLUMNS} FROM chunks WHERE id = ?1"))?;
+        let result = stmt.query_row(rusqlite::params![id], row_to_chunk);
         match result {
             Ok(c) => Ok(Some(c)),
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -212,6 +553,74 @@ impl MemoryStore {
     }
 }
 
+/// Build an FTS5 match expression from a search query.
+///
+/// In plain and prefix mode the query is split into words, each word is
+/// quoted so FTS5 operators and punctuation lose their meaning, and the
+/// words are OR-ed together (BM25 ranks chunks matching more words
+/// higher). Advanced mode passes the query through unchanged. Returns
+/// `None` if there is nothing to search for.
+pub fn fts_match_expression(query: &str, mode: FtsQueryMode) -> Option<String> {
+    if mode == FtsQueryMode::Advanced {
+        let query = query.trim();
+        return (!query.is_empty()).then(|| query.to_string());
+    }
+
+    let suffix = if mode == FtsQueryMode::Prefix {
+        "*"
+    } else {
+        ""
+    };
+    let mut terms: Vec<String> = Vec::new();
+    for word in query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+    {
+        let term = format!("\"{word}\"{suffix}");
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+/// Read a row selected with [`CHUNK_COLUMNS`].
+fn row_to_chunk(row: &rusqlite::Row) -> rusqlite::Result<StoredChunk> {
+    let embedding_bytes: Vec<u8> = row.get(8)?;
+    let metadata: Option<String> = row.get(10)?;
+    Ok(StoredChunk {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        source: row.get(2)?,
+        start_line: row.get::<_, i64>(3)? as usize,
+        end_line: row.get::<_, i64>(4)? as usize,
+        hash: row.get(5)?,
+        model: row.get(6)?,
+        text: row.get(7)?,
+        embedding: bytes_to_embedding(&embedding_bytes),
+        updated_at: row.get(9)?,
+        metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()),
+    })
+}
+
+/// Load persisted ANN centroids, if any.
+fn load_centroids(conn: &Connection) -> Result<Option<IvfIndex>> {
+    let mut stmt = conn.prepare("SELECT centroid FROM ann_centroids ORDER BY list")?;
+    let centroids = stmt
+        .query_map([], |row| {
+            let bytes: Vec<u8> = row.get(0)?;
+            Ok(bytes_to_embedding(&bytes))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((!centroids.is_empty()).then(|| IvfIndex::from_centroids(centroids)))
+}
+
+/// Sort scored IDs best first and keep the top `limit`.
+fn sort_and_truncate(scored: &mut Vec<(String, f32)>, limit: usize) {
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(limit);
+}
+
 fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
     embedding.iter().flat_map(|f| f.to_le_bytes()).collect()
 }
@@ -262,6 +671,7 @@ mod tests {
             text: "Hello world this is a test".to_string(),
             embedding: vec![0.1, 0.2, 0.3],
             updated_at: 1000,
+            metadata: None,
         };
         store.upsert_chunk(&chunk).unwrap();
 
@@ -269,4 +679,381 @@ mod tests {
         assert_eq!(loaded.text, "Hello world this is a test");
         assert_eq!(loaded.embedding, vec![0.1, 0.2, 0.3]);
     }
+
+    fn test_chunk(id: &str, path: &str, text: &str) -> StoredChunk {
+        StoredChunk {
+            id: id.to_string(),
+            path: path.to_string(),
+            source: "local".to_string(),
+            start_line: 1,
+            end_line: 1,
+            hash: "hash".to_string(),
+            model: "test".to_string(),
+            text: text.to_string(),
+            embedding: vec![1.0],
+            updated_at: 1000,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_fts_follows_updates_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(&dir.path().join("test_memory.db")).unwrap();
+
+        store
+            .upsert_chunk(&test_chunk("a::0", "/a.md", "kubernetes upgrade notes"))
+            .unwrap();
+        store
+            .upsert_chunk(&test_chunk("b::0", "/b.md", "kubernetes oncall rota"))
+            .unwrap();
+        // Re-sync of a.md with new text
+        store
+            .upsert_chunk(&test_chunk("a::0", "/a.md", "postgres upgrade notes"))
+            .unwrap();
+        assert_eq!(
+            store
+                .fts_search(
+                    "kubernetes",
+                    FtsQueryMode::Plain,
+                    10,
+                    &SearchFilter::default()
+                )
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .fts_search(
+                    "postgres",
+                    FtsQueryMode::Plain,
+                    10,
+                    &SearchFilter::default()
+                )
+                .unwrap()[0]
+                .0,
+            "a::0"
+        );
+
+        store.delete_chunks_for_path("/b.md").unwrap();
+        assert!(
+            store
+                .fts_search(
+                    "kubernetes",
+                    FtsQueryMode::Plain,
+                    10,
+                    &SearchFilter::default()
+                )
+                .unwrap()
+                .is_empty()
+        );
+        assert!(store.check_fts().unwrap());
+
+        store.vacuum().unwrap();
+        assert_eq!(
+            store
+                .fts_search(
+                    "postgres",
+                    FtsQueryMode::Plain,
+                    10,
+                    &SearchFilter::default()
+                )
+                .unwrap()[0]
+                .0,
+            "a::0"
+        );
+        assert!(store.check_fts().unwrap());
+    }
+
+    #[test]
+    fn test_check_and_rebuild_fts() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(&dir.path().join("test_memory.db")).unwrap();
+        store
+            .upsert_chunk(&test_chunk("a::0", "/a.md", "release train"))
+            .unwrap();
+
+        // Index entry without a backing chunk
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO chunks_fts (rowid, text, id, path, source) VALUES (99, 'stale', 'x', '/x.md', 'local')",
+                [],
+            )
+            .unwrap();
+        assert!(!store.check_fts().unwrap());
+
+        store.rebuild_fts().unwrap();
+        assert!(store.check_fts().unwrap());
+        assert!(
+            store
+                .fts_search("stale", FtsQueryMode::Plain, 10, &SearchFilter::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    /// Chunks spread over four clusters along the first axes.
+    fn clustered_chunk(i: usize) -> StoredChunk {
+        let mut embedding = vec![0.01 * (i % 7) as f32; 8];
+        embedding[i % 4] = 1.0;
+        StoredChunk {
+            embedding,
+            ..test_chunk(&format!("c::{i}"), "/c.md", "clustered")
+        }
+    }
+
+    #[test]
+    fn test_ann_index_build_search_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test_memory.db");
+        let store = MemoryStore::open(&db_path)
+            .unwrap()
+            .with_exact_scan_limit(100);
+
+        for i in 0..50 {
+            store.upsert_chunk(&clustered_chunk(i)).unwrap();
+        }
+        // Below the limit: exact scan only
+        assert!(!store.maybe_rebuild_ann().unwrap());
+        let query = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let exact = store
+            .vector_search(&query, 5, &SearchFilter::default())
+            .unwrap();
+        assert_eq!(exact.len(), 5);
+
+        for i in 50..200 {
+            store.upsert_chunk(&clustered_chunk(i)).unwrap();
+        }
+        assert!(store.maybe_rebuild_ann().unwrap());
+        assert!(store.has_ann_index());
+        assert!(!store.maybe_rebuild_ann().unwrap());
+
+        let results = store
+            .vector_search(&query, 5, &SearchFilter::default())
+            .unwrap();
+        assert_eq!(results.len(), 5);
+        for (id, score) in &results {
+            let i: usize = id.trim_start_matches("c::").parse().unwrap();
+            assert_eq!(i % 4, 2);
+            assert!(*score > 0.9);
+        }
+
+        // Centroids persist; new chunks are assigned on insert
+        drop(store);
+        let store = MemoryStore::open(&db_path).unwrap();
+        assert!(store.has_ann_index());
+        store.upsert_chunk(&clustered_chunk(202)).unwrap();
+        let unassigned: i64 = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) FROM chunks WHERE ann_list IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(unassigned, 0);
+        assert!(
+            store
+                .vector_search(&query, 300, &SearchFilter::default())
+                .unwrap()
+                .iter()
+                .any(|(id, _)| id == "c::202")
+        );
+
+        // Chunks from a different model are still searched
+        store
+            .upsert_chunk(&test_chunk("other::0", "/o.md", "other model"))
+            .unwrap();
+        assert_eq!(
+            store
+                .vector_search(&[1.0], 5, &SearchFilter::default())
+                .unwrap()[0]
+                .0,
+            "other::0"
+        );
+    }
+
+    #[test]
+    fn test_fts_match_expression() {
+        assert_eq!(
+            fts_match_expression(
+                "what's \"NEAR\" the on-call: rota (AND)?",
+                FtsQueryMode::Plain
+            )
+            .unwrap(),
+            r#""what" OR "s" OR "NEAR" OR "the" OR "on" OR "call" OR "rota" OR "AND""#
+        );
+        assert_eq!(
+            fts_match_expression("deploy deploy", FtsQueryMode::Prefix).unwrap(),
+            r#""deploy"*"#
+        );
+        assert_eq!(
+            fts_match_expression(" kafka NEAR lag ", FtsQueryMode::Advanced).unwrap(),
+            "kafka NEAR lag"
+        );
+        assert!(fts_match_expression("-- :: ()", FtsQueryMode::Plain).is_none());
+    }
+
+    #[test]
+    fn test_fts_search_modes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(&dir.path().join("test_memory.db")).unwrap();
+        store
+            .upsert_chunk(&test_chunk(
+                "a::0",
+                "/a.md",
+                "deployment checklist for on-call",
+            ))
+            .unwrap();
+
+        // Syntax that would be an FTS5 error when passed through raw
+        let query = "on-call \"checklist AND (";
+        assert_eq!(
+            store
+                .fts_search(query, FtsQueryMode::Plain, 10, &SearchFilter::default())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(
+            store
+                .fts_search(query, FtsQueryMode::Advanced, 10, &SearchFilter::default())
+                .is_err()
+        );
+
+        assert!(
+            store
+                .fts_search("deploy", FtsQueryMode::Plain, 10, &SearchFilter::default())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            store
+                .fts_search("deploy", FtsQueryMode::Prefix, 10, &SearchFilter::default())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .fts_search(
+                    "deploy* AND checklist",
+                    FtsQueryMode::Advanced,
+                    10,
+                    &SearchFilter::default()
+                )
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MemoryStore::open(&dir.path().join("test_memory.db")).unwrap();
+        store
+            .upsert_chunk(&test_chunk("a::0", "/a.md", "standup notes"))
+            .unwrap();
+        for (id, owner) in [("s1", "telegram:1"), ("s2", "telegram:2")] {
+            store
+                .upsert_chunk(&StoredChunk {
+                    source: SESSION_SOURCE.to_string(),
+                    metadata: Some(serde_json::json!({ "session_key": id, "owner": owner })),
+                    ..test_chunk(id, &format!("session:{id}"), "standup notes")
+                })
+                .unwrap();
+        }
+        for (id, stem) in [("n1", "telegram%3A1"), ("n2", "telegram%3A2")] {
+            store
+                .upsert_chunk(&StoredChunk {
+                    metadata: Some(serde_json::json!({ "owner": stem })),
+                    ..test_chunk(id, &format!("/notes/users/{stem}.md"), "standup notes")
+                })
+                .unwrap();
+        }
+        let ids = |filter: &SearchFilter| {
+            let mut fts: Vec<String> = store
+                .fts_search("standup", FtsQueryMode::Plain, 10, filter)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            let mut vector: Vec<String> = store
+                .vector_search(&[1.0], 10, filter)
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            fts.sort();
+            vector.sort();
+            assert_eq!(fts, vector);
+            fts
+        };
+
+        assert_eq!(ids(&SearchFilter::default()).len(), 5);
+        let own = SearchFilter {
+            owner: Some("telegram:1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&own), vec!["a::0", "n1", "s1"]);
+        let own_sessions = SearchFilter {
+            sources: vec![SESSION_SOURCE.to_string()],
+            ..own
+        };
+        assert_eq!(ids(&own_sessions), vec!["s1"]);
+
+        let loaded = store.get_chunk("s2").unwrap().unwrap();
+        assert_eq!(loaded.metadata.unwrap()["owner"], "telegram:2");
+    }
+
+    #[test]
+    fn test_migrates_standalone_fts() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test_memory.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE chunks (
+                     id TEXT PRIMARY KEY, path TEXT NOT NULL, source TEXT NOT NULL,
+                     start_line INTEGER, end_line INTEGER, hash TEXT NOT NULL,
+                     model TEXT NOT NULL, text TEXT NOT NULL, embedding BLOB NOT NULL,
+                     updated_at INTEGER NOT NULL
+                 );
+                 CREATE VIRTUAL TABLE chunks_fts USING fts5(
+                     text, id UNINDEXED, path UNINDEXED, source UNINDEXED
+                 );
+                 INSERT INTO chunks VALUES ('a::0', '/a.md', 'local', 1, 1, 'h', 'm', 'deploy checklist', x'', 0);
+                 INSERT INTO chunks_fts (rowid, text, id, path, source) VALUES (7, 'orphaned', 'gone::0', '/gone.md', 'local');",
+            )
+            .unwrap();
+        }
+
+        let store = MemoryStore::open(&db_path).unwrap();
+        assert!(
+            store
+                .fts_search(
+                    "orphaned",
+                    FtsQueryMode::Plain,
+                    10,
+                    &SearchFilter::default()
+                )
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            store
+                .fts_search("deploy", FtsQueryMode::Plain, 10, &SearchFilter::default())
+                .unwrap()[0]
+                .0,
+            "a::0"
+        );
+        assert!(store.check_fts().unwrap());
+    }
 }
@@ -1,141 +1,402 @@
 //! Incremental file sync for memory indexing.
 
 use anyhow::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::chunking::chunk_markdown;
+use crate::chunking::chunker_for_path;
 use crate::embeddings::EmbeddingProvider;
 use crate::store::{FileRecord, MemoryStore, StoredChunk};
 
+/// A file or directory indexed into memory, with optional glob filters.
+#[derive(Debug, Clone)]
+pub struct MemorySource {
+    pub path: PathBuf,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl MemorySource {
+    /// Index every supported file under `path`.
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            include: None,
+            exclude: None,
+        }
+    }
+
+    /// Index files under `path` matching any `include` pattern (all
+    /// supported files if empty) and no `exclude` pattern. Patterns are
+    /// matched against paths relative to `path`.
+    pub fn with_filters(path: PathBuf, include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            path,
+            include: glob_set(include)?,
+            exclude: glob_set(exclude)?,
+        })
+    }
+
+    /// Whether `file` lies in this source and should be indexed.
+    pub fn matches(&self, file: &Path) -> bool {
+        if chunker_for_path(file).is_none() {
+            return false;
+        }
+        let Some(relative) = self.relative(file) else {
+            return false;
+        };
+        self.include.as_ref().is_none_or(|g| g.is_match(relative)) && !self.is_excluded(relative)
+    }
+
+    /// Path relative to the source root, or the file name when the source
+    /// is the file itself. `None` if `path` is outside this source.
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        let relative = path.strip_prefix(&self.path).ok()?;
+        if relative.as_os_str().is_empty() {
+            return path.file_name().map(Path::new);
+        }
+        Some(relative)
+    }
+
+    /// Whether a relative path or one of its parent directories is excluded.
+    fn is_excluded(&self, relative: &Path) -> bool {
+        self.exclude.as_ref().is_some_and(|g| {
+            relative
+                .ancestors()
+                .filter(|p| !p.as_os_str().is_empty())
+                .any(|p| g.is_match(p))
+        })
+    }
+
+    /// Files to index at or below `path`, which must lie in this source.
+    fn collect_files(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        if path.is_file() {
+            return Ok(if self.matches(path) {
+                vec![path.to_path_buf()]
+            } else {
+                vec![]
+            });
+        }
+
+        if !path.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let entry_path = entry.path();
+            if entry_path.is_file() {
+                if self.matches(&entry_path) {
+                    files.push(entry_path);
+                }
+            } else if entry_path.is_dir()
+                && !self
+                    .relative(&entry_path)
+                    .is_some_and(|r| self.is_excluded(r))
+            {
+                files.extend(self.collect_files(&entry_path)?);
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            Glob::new(pattern)
+                .map_err(|e| anyhow::anyhow!("Invalid memory glob '{pattern}': {e}"))?,
+        );
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Extra metadata stored with every chunk of a file, by file path.
+pub type ChunkMetadataFn<'a> = dyn Fn(&Path) -> Option<serde_json::Value> + Send + Sync + 'a;
+
 /// Result of a sync operation.
 #[derive(Debug, Default)]
 pub struct SyncResult {
     pub files_scanned: usize,
     pub files_updated: usize,
+    pub files_removed: usize,
     pub chunks_added: usize,
     pub chunks_removed: usize,
+    /// Files that couldn't be read or have their text extracted. They keep
+    /// their previous chunks and are retried on the next sync.
+    pub files_failed: usize,
+    /// Whether the full-text index matched the chunks table after syncing.
+    /// When it did not, the index was rebuilt.
+    pub fts_consistent: bool,
 }
 
-/// Sync memory files from the given directories.
+/// Sync memory files from the given sources.
 ///
-/// Only re-indexes files whose content hash has changed.
+/// Only re-indexes files whose content, chunk metadata or embedding model
+/// changed, drops index
+/// entries for files that no longer exist (or no longer match the source
+/// filters), and verifies the full-text index.
 pub async fn sync_memory_files(
     store: &MemoryStore,
     provider: &dyn EmbeddingProvider,
-    dirs: &[PathBuf],
+    sources: &[MemorySource],
+    metadata: &ChunkMetadataFn<'_>,
     chunk_max_lines: usize,
     chunk_overlap: usize,
     force: bool,
 ) -> Result<SyncResult> {
     let mut result = SyncResult::default();
+    let indexer = Indexer {
+        store,
+        provider,
+        metadata,
+        chunk_max_lines,
+        chunk_overlap,
+    };
 
-    for dir in dirs {
-        let files = collect_memory_files(dir)?;
+    for source in sources {
+        let dir = &source.path;
+        let files = source.collect_files(dir)?;
         result.files_scanned += files.len();
 
         for file_path in &files {
-            let path_str = file_path.to_string_lossy().to_string();
-            let content = tokio::fs::read_to_string(file_path).await?;
-            let hash = hash_content(&content);
-
-            // Check if file has changed
-            if !force {
-                if let Some(existing) = store.get_file(&path_str)? {
-                    if existing.hash == hash {
-                        debug!(path = %path_str, "File unchanged, skipping");
-                        continue;
-                    }
-                }
-            }
+            indexer.index_file(file_path, force, &mut result).await?;
+        }
 
-            info!(path = %path_str, "Syncing file");
+        // Drop files that were deleted while nobody was watching
+        let on_disk: HashSet<String> = files
+            .iter()
+            .map(|f| f.to_string_lossy().to_string())
+            .collect();
+        for path in indexed_paths_under(store, dir)? {
+            if !on_disk.contains(&path) {
+                remove_file(store, &path, &mut result)?;
+            }
+        }
+    }
 
-            // Delete old chunks
-            let removed = store.delete_chunks_for_path(&path_str)?;
-            result.chunks_removed += removed;
+    result.fts_consistent = check_fts(store)?;
+    maybe_rebuild_ann(store)?;
+    Ok(result)
+}
 
-            // Chunk the content
-            let chunks = chunk_markdown(&content, chunk_max_lines, chunk_overlap);
+/// Re-index specific paths after a change on disk.
+///
+/// Existing files are re-indexed if their content changed, existing
+/// directories are scanned, and paths that no longer exist have their
+/// file records and chunks removed (including everything below a
+/// removed directory). Existing paths outside every source are ignored.
+pub async fn sync_paths(
+    store: &MemoryStore,
+    provider: &dyn EmbeddingProvider,
+    sources: &[MemorySource],
+    metadata: &ChunkMetadataFn<'_>,
+    paths: &[PathBuf],
+    chunk_max_lines: usize,
+    chunk_overlap: usize,
+) -> Result<SyncResult> {
+    let mut result = SyncResult::default();
+    let indexer = Indexer {
+        store,
+        provider,
+        metadata,
+        chunk_max_lines,
+        chunk_overlap,
+    };
 
-            // Embed all chunks in batch
-            let texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
-            let embeddings = if !texts.is_empty() {
-                provider.embed_batch(&texts).await?
-            } else {
-                vec![]
+    for path in paths {
+        if path.exists() {
+            let Some(source) = sources.iter().find(|s| path.starts_with(&s.path)) else {
+                continue;
             };
-
-            // Store chunks
-            let now = chrono::Utc::now().timestamp();
-            for (i, (chunk, embedding)) in chunks.iter().zip(embeddings.iter()).enumerate() {
-                let stored = StoredChunk {
-                    id: format!("{path_str}::{i}"),
-                    path: path_str.clone(),
-                    source: "local".to_string(),
-                    start_line: chunk.start_line,
-                    end_line: chunk.end_line,
-                    hash: chunk.hash.clone(),
-                    model: provider.model().to_string(),
-                    text: chunk.text.clone(),
-                    embedding: embedding.clone(),
-                    updated_at: now,
-                };
-                store.upsert_chunk(&stored)?;
-                result.chunks_added += 1;
+            let files = source.collect_files(path)?;
+            result.files_scanned += files.len();
+            for file_path in &files {
+                indexer.index_file(file_path, false, &mut result).await?;
+            }
+        } else {
+            for indexed in indexed_paths_under(store, path)? {
+                remove_file(store, &indexed, &mut result)?;
             }
-
-            // Update file record
-            let metadata = tokio::fs::metadata(file_path).await.ok();
-            store.upsert_file(&FileRecord {
-                path: path_str,
-                source: "local".to_string(),
-                hash,
-                mtime: metadata
-                    .as_ref()
-                    .and_then(|m| m.modified().ok())
-                    .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64),
-                size: metadata.map(|m| m.len() as i64),
-            })?;
-
-            result.files_updated += 1;
         }
     }
 
+    maybe_rebuild_ann(store)?;
     Ok(result)
 }
 
-/// Collect markdown files from a path (file or directory).
-fn collect_memory_files(path: &Path) -> Result<Vec<PathBuf>> {
-    if path.is_file() {
-        return Ok(vec![path.to_path_buf()]);
+/// Check the full-text index and rebuild it if it drifted from the chunks.
+fn check_fts(store: &MemoryStore) -> Result<bool> {
+    let consistent = store.check_fts()?;
+    if !consistent {
+        warn!("Memory full-text index is out of sync with chunks, rebuilding");
+        store.rebuild_fts()?;
     }
+    Ok(consistent)
+}
 
-    if !path.is_dir() {
-        return Ok(vec![]);
+/// Train or retrain the vector index if the store outgrew it.
+fn maybe_rebuild_ann(store: &MemoryStore) -> Result<()> {
+    if store.maybe_rebuild_ann()? {
+        info!("Rebuilt memory vector index");
     }
+    Ok(())
+}
 
-    let mut files = Vec::new();
-    for entry in std::fs::read_dir(path)? {
-        let entry = entry?;
-        let entry_path = entry.path();
-        if entry_path.is_file() {
-            if let Some(ext) = entry_path.extension() {
-                if ext == "md" || ext == "txt" || ext == "markdown" {
-                    files.push(entry_path);
-                }
+/// Settings shared by every file indexed in one sync run.
+struct Indexer<'a> {
+    store: &'a MemoryStore,
+    provider: &'a dyn EmbeddingProvider,
+    metadata: &'a ChunkMetadataFn<'a>,
+    chunk_max_lines: usize,
+    chunk_overlap: usize,
+}
+
+impl Indexer<'_> {
+    /// Chunk, embed, and store a single file if its content, chunk metadata
+    /// or the embedding model changed.
+    async fn index_file(
+        &self,
+        file_path: &Path,
+        force: bool,
+        result: &mut SyncResult,
+    ) -> Result<()> {
+        let Some(chunker) = chunker_for_path(file_path) else {
+            return Ok(());
+        };
+        let path_str = file_path.to_string_lossy().to_string();
+        let bytes = match tokio::fs::read(file_path).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(path = %path_str, "Failed to read memory file: {e}");
+                result.files_failed += 1;
+                return Ok(());
+            }
+        };
+        let chunk_metadata = (self.metadata)(file_path);
+        let hash = index_hash(&bytes, chunk_metadata.as_ref(), self.provider);
+
+        // Check if file has changed
+        if !force
+            && let Some(existing) = self.store.get_file(&path_str)?
+            && existing.hash == hash
+        {
+            debug!(path = %path_str, "File unchanged, skipping");
+            return Ok(());
+        }
+
+        info!(path = %path_str, "Syncing file");
+
+        let content = match chunker.extract_text(&bytes) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!(path = %path_str, "Failed to extract text from memory file: {e}");
+                result.files_failed += 1;
+                return Ok(());
             }
-        } else if entry_path.is_dir() {
-            files.extend(collect_memory_files(&entry_path)?);
+        };
+
+        // Delete old chunks
+        let removed = self.store.delete_chunks_for_path(&path_str)?;
+        result.chunks_removed += removed;
+
+        // Chunk the content
+        let chunks = chunker.chunk(&content, self.chunk_max_lines, self.chunk_overlap);
+
+        // Embed all chunks in batch
+        let texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+        let embeddings = if !texts.is_empty() {
+            self.provider.embed_batch(&texts).await?
+        } else {
+            vec![]
+        };
+
+        // Store chunks
+        let now = chrono::Utc::now().timestamp();
+        for (i, (chunk, embedding)) in chunks.iter().zip(embeddings.iter()).enumerate() {
+            let stored = StoredChunk {
+                id: format!("{path_str}::{i}"),
+                path: path_str.clone(),
+                source: chunker.name().to_string(),
+                start_line: chunk.start_line,
+                end_line: chunk.end_line,
+                hash: chunk.hash.clone(),
+                model: self.provider.model().to_string(),
+                text: chunk.text.clone(),
+                embedding: embedding.clone(),
+                updated_at: now,
+                metadata: chunk_metadata.clone(),
+            };
+            self.store.upsert_chunk(&stored)?;
+            result.chunks_added += 1;
         }
+
+        // Update file record
+        let fs_metadata = tokio::fs::metadata(file_path).await.ok();
+        self.store.upsert_file(&FileRecord {
+            path: path_str,
+            source: "local".to_string(),
+            hash,
+            mtime: fs_metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64),
+            size: fs_metadata.map(|m| m.len() as i64),
+        })?;
+
+        result.files_updated += 1;
+        Ok(())
+    }
+}
+
+/// Remove a file's record and chunks from the index.
+fn remove_file(store: &MemoryStore, path: &str, result: &mut SyncResult) -> Result<()> {
+    info!(path = %path, "Removing deleted file from memory");
+    result.chunks_removed += store.delete_chunks_for_path(path)?;
+    if store.delete_file(path)? {
+        result.files_removed += 1;
     }
+    Ok(())
+}
 
-    Ok(files)
+/// Indexed file paths equal to or below `path`.
+fn indexed_paths_under(store: &MemoryStore, path: &Path) -> Result<Vec<String>> {
+    let paths = store
+        .list_files()?
+        .into_iter()
+        .map(|f| f.path)
+        .filter(|p| Path::new(p).starts_with(path))
+        .collect();
+    Ok(paths)
 }
 
-fn hash_content(content: &str) -> String {
+/// Hash of what a file's chunks were built from: its content, the metadata
+/// they are stored with and the embedding model (and dimensions), so vectors
+/// from a previous provider are never kept next to the current one's.
+fn index_hash(
+    content: &[u8],
+    metadata: Option<&serde_json::Value>,
+    provider: &dyn EmbeddingProvider,
+) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
+    hasher.update(content);
+    if let Some(metadata) = metadata {
+        hasher.update(metadata.to_string());
+    }
+    hasher.update(format!(
+        "\0{}\0{}\0{}",
+        provider.id(),
+        provider.model(),
+        provider.dimensions()
+    ));
     hex::encode(hasher.finalize())
 }
@@ -32,6 +32,9 @@ pub struct SessionMetadata {
     /// pi-agent-rs session ID for JSONL history restoration.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pi_session_id: Option<String>,
+    /// Skill whose turn the session is running, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_skill: Option<String>,
 }
 
 /// SQLite-based storage for aobot gateway metadata.
@@ -70,6 +73,7 @@ impl AoBotStorage {
 
         // Migration: add pi_session_id column (ignore error if already exists)
         let _ = conn.execute_batch("ALTER TABLE gateway_sessions ADD COLUMN pi_session_id TEXT;");
+        let _ = conn.execute_batch("ALTER TABLE gateway_sessions ADD COLUMN active_skill TEXT;");
 
         tracing::info!("Storage opened: {}", path.display());
 
@@ -90,7 +94,8 @@ impl AoBotStorage {
                 last_active_at INTEGER NOT NULL,
                 message_count INTEGER DEFAULT 0,
                 is_active INTEGER DEFAULT 1,
-                pi_session_id TEXT
+                pi_session_id TEXT,
+                active_skill TEXT
             );
 
             CREATE TABLE IF NOT EXISTS channel_bindings (
@@ -116,15 +121,16 @@ impl AoBotStorage {
             let conn = conn.blocking_lock();
             conn.execute(
                 "INSERT INTO gateway_sessions
-                    (session_key, agent_name, model_id, created_at, last_active_at, message_count, is_active, pi_session_id)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                    (session_key, agent_name, model_id, created_at, last_active_at, message_count, is_active, pi_session_id, active_skill)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
                  ON CONFLICT(session_key) DO UPDATE SET
                     agent_name = excluded.agent_name,
                     model_id = excluded.model_id,
                     last_active_at = excluded.last_active_at,
                     message_count = excluded.message_count,
                     is_active = excluded.is_active,
-                    pi_session_id = COALESCE(excluded.pi_session_id, gateway_sessions.pi_session_id)",
+                    pi_session_id = COALESCE(excluded.pi_session_id, gateway_sessions.pi_session_id),
+                    active_skill = excluded.active_skill",
                 rusqlite::params![
                     meta.session_key,
                     meta.agent_name,
@@ -134,6 +140,7 @@ impl AoBotStorage {
                     meta.message_count,
                     meta.is_active as i32,
                     meta.pi_session_id,
+                    meta.active_skill,
                 ],
             )?;
             Ok(())
@@ -148,7 +155,7 @@ impl AoBotStorage {
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             let mut stmt = conn.prepare(
-                "SELECT session_key, agent_name, model_id, created_at, last_active_at, message_count, is_active, pi_session_id
+                "SELECT session_key, agent_name, model_id, created_at, last_active_at, message_count, is_active, pi_session_id, active_skill
                  FROM gateway_sessions WHERE session_key = ?1",
             )?;
             let result = stmt
@@ -162,6 +169,7 @@ impl AoBotStorage {
                         message_count: row.get(5)?,
                         is_active: row.get::<_, i32>(6)? != 0,
                         pi_session_id: row.get(7)?,
+                        active_skill: row.get(8)?,
                     })
                 })
                 .optional()?;
@@ -176,7 +184,7 @@ impl AoBotStorage {
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             let mut stmt = conn.prepare(
-                "SELECT session_key, agent_name, model_id, created_at, last_active_at, message_count, is_active, pi_session_id
+                "SELECT session_key, agent_name, model_id, created_at, last_active_at, message_count, is_active, pi_session_id, active_skill
                  FROM gateway_sessions WHERE is_active = 1 ORDER BY last_active_at DESC",
             )?;
             let rows = stmt
@@ -190,6 +198,7 @@ impl AoBotStorage {
                         message_count: row.get(5)?,
                         is_active: row.get::<_, i32>(6)? != 0,
                         pi_session_id: row.get(7)?,
+                        active_skill: row.get(8)?,
                     })
                 })?
                 .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -230,6 +239,23 @@ impl AoBotStorage {
         .await?
     }
 
+    /// Record the skill a session is running a turn for (`None` once the
+    /// turn is over).
+    pub async fn set_active_skill(&self, session_key: &str, skill: Option<&str>) -> Result<()> {
+        let conn = self.conn.clone();
+        let session_key = session_key.to_string();
+        let skill = skill.map(String::from);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE gateway_sessions SET active_skill = ?1 WHERE session_key = ?2",
+                rusqlite::params![skill, session_key],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
     /// Soft-delete a session (mark as inactive).
     pub async fn delete_session(&self, key: &str) -> Result<()> {
         let conn = self.conn.clone();
@@ -318,6 +344,7 @@ mod tests {
             message_count: 0,
             is_active: true,
             pi_session_id: None,
+            active_skill: None,
         };
         storage.save_session(&meta).await.unwrap();
 
@@ -349,6 +376,7 @@ mod tests {
                 message_count: 0,
                 is_active: true,
                 pi_session_id: None,
+                active_skill: None,
             };
             storage.save_session(&meta).await.unwrap();
         }
@@ -371,6 +399,7 @@ mod tests {
             message_count: 0,
             is_active: true,
             pi_session_id: None,
+            active_skill: None,
         };
         storage.save_session(&meta).await.unwrap();
 
@@ -380,6 +409,34 @@ mod tests {
         assert!(loaded.last_active_at > 1700000000000);
     }
 
+    #[tokio::test]
+    async fn test_set_active_skill() {
+        let storage = AoBotStorage::open_in_memory().unwrap();
+        let meta = SessionMetadata {
+            session_key: "sess-1".into(),
+            agent_name: "default".into(),
+            model_id: "test-model".into(),
+            created_at: 1700000000000,
+            last_active_at: 1700000000000,
+            message_count: 0,
+            is_active: true,
+            pi_session_id: None,
+            active_skill: None,
+        };
+        storage.save_session(&meta).await.unwrap();
+
+        storage
+            .set_active_skill("sess-1", Some("review-pr"))
+            .await
+            .unwrap();
+        let loaded = storage.get_session("sess-1").await.unwrap().unwrap();
+        assert_eq!(loaded.active_skill.as_deref(), Some("review-pr"));
+
+        storage.set_active_skill("sess-1", None).await.unwrap();
+        let loaded = storage.get_session("sess-1").await.unwrap().unwrap();
+        assert!(loaded.active_skill.is_none());
+    }
+
     #[tokio::test]
     async fn test_delete_session() {
         let storage = AoBotStorage::open_in_memory().unwrap();
@@ -392,6 +449,7 @@ mod tests {
             message_count: 0,
             is_active: true,
             pi_session_id: None,
+            active_skill: None,
         };
         storage.save_session(&meta).await.unwrap();
 
@@ -416,6 +474,7 @@ mod tests {
             message_count: 0,
             is_active: true,
             pi_session_id: None,
+            active_skill: None,
         };
         storage.save_session(&meta).await.unwrap();
 
@@ -445,6 +504,7 @@ mod tests {
             message_count: 0,
             is_active: true,
             pi_session_id: None,
+            active_skill: None,
         };
         storage.save_session(&meta).await.unwrap();
 
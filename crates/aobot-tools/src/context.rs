@@ -5,6 +5,9 @@ use tokio::sync::RwLock;
 
 use aobot_config::AoBotConfig;
 
+use crate::sandbox::SandboxPolicy;
+use crate::tools::process::BackgroundProcessRegistry;
+
 /// Shared context that gateway tools use to access the gateway system.
 ///
 /// This is passed to gateway tools at construction time so they can
@@ -17,6 +20,10 @@ pub struct GatewayToolContext {
     pub current_agent_id: String,
     /// Live configuration (hot-reloadable).
     pub config: Arc<RwLock<AoBotConfig>>,
+    /// Sandbox policy of the current agent.
+    pub sandbox: Arc<SandboxPolicy>,
+    /// Background processes started by the current session.
+    pub processes: Arc<BackgroundProcessRegistry>,
     /// Sender for dispatching gateway operations.
     /// Gateway tools send `GatewayOp` commands through this channel,
     /// which the gateway loop processes against the real SessionManager/ChannelManager.
@@ -73,6 +80,27 @@ pub enum GatewayOp {
     MemorySearch {
         query: String,
         max_results: usize,
+        /// Only return chunks from these sources (empty means all).
+        sources: Vec<String>,
+        /// Session the search runs in; conversation memory is limited to its owner.
+        session_key: String,
+        reply: tokio::sync::oneshot::Sender<GatewayOpResult>,
+    },
+    /// Append a note to memory and index it.
+    MemoryWrite {
+        content: String,
+        scope: MemoryNoteScope,
+        agent_id: String,
+        /// Session the note is written from; identifies the user for `User` notes.
+        session_key: String,
+        reply: tokio::sync::oneshot::Sender<GatewayOpResult>,
+    },
+    /// Remove notes by note id or chunk id, limited to the agent's and
+    /// the session user's notes.
+    MemoryForget {
+        id: String,
+        agent_id: String,
+        session_key: String,
         reply: tokio::sync::oneshot::Sender<GatewayOpResult>,
     },
     /// Get memory content by path and line range.
@@ -80,6 +108,8 @@ pub enum GatewayOp {
         path: String,
         start_line: Option<usize>,
         end_line: Option<usize>,
+        /// Session the read is made from, for auditing denied paths.
+        session_key: String,
         reply: tokio::sync::oneshot::Sender<GatewayOpResult>,
     },
     /// List cron jobs.
@@ -88,9 +118,20 @@ pub enum GatewayOp {
     },
     /// Add a cron job.
     CronAdd {
+        kind: aobot_cron::ScheduleKind,
+        /// Cron expression, RFC3339 time or interval, depending on `kind`.
         schedule: String,
+        timezone: Option<String>,
         task: String,
         agent_id: Option<String>,
+        /// Explicit delivery target for the job's output.
+        delivery: Option<aobot_cron::CronDelivery>,
+        /// Session that created the job; its channel route is the default delivery target.
+        origin_session_key: String,
+        missed_runs: aobot_cron::MissedRunPolicy,
+        overlap: aobot_cron::OverlapPolicy,
+        /// Delete one-shot jobs after they run.
+        delete_after_run: bool,
         reply: tokio::sync::oneshot::Sender<GatewayOpResult>,
     },
     /// Remove a cron job.
@@ -109,6 +150,21 @@ pub enum GatewayOp {
         job_id: String,
         reply: tokio::sync::oneshot::Sender<GatewayOpResult>,
     },
+    /// List recent cron job runs.
+    CronRuns {
+        job_id: Option<String>,
+        limit: usize,
+        reply: tokio::sync::oneshot::Sender<GatewayOpResult>,
+    },
+}
+
+/// Which notes file `memory_write` appends to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryNoteScope {
+    /// Notes about the user the current session belongs to.
+    User,
+    /// Notes shared by every session of the agent.
+    Agent,
 }
 
 /// Results from gateway operations.
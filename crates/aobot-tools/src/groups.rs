@@ -15,7 +15,15 @@ pub static TOOL_GROUPS: Lazy<HashMap<&'static str, &'static [&'static str]>> = L
     m.insert("fs", &["read", "write", "edit"][..]);
     m.insert("runtime", &["bash", "process"][..]);
     m.insert("web", &["web_search", "web_fetch"][..]);
-    m.insert("memory", &["memory_search", "memory_get"][..]);
+    m.insert(
+        "memory",
+        &[
+            "memory_search",
+            "memory_get",
+            "memory_write",
+            "memory_forget",
+        ][..],
+    );
     m.insert(
         "sessions",
         &[
@@ -65,6 +73,20 @@ mod tests {
         assert_eq!(expanded, vec!["read", "write", "edit"]);
     }
 
+    #[test]
+    fn test_expand_memory_group() {
+        let expanded = expand_name("group:memory");
+        assert_eq!(
+            expanded,
+            vec![
+                "memory_search",
+                "memory_get",
+                "memory_write",
+                "memory_forget"
+            ]
+        );
+    }
+
     #[test]
     fn test_expand_unknown_group() {
         let expanded = expand_name("group:nonexistent");
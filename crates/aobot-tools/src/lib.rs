@@ -5,9 +5,11 @@
 //! - Tool group definitions (fs, runtime, web, memory, sessions, messaging, etc.)
 //! - Gateway tools that can operate on sessions, channels, and config
 //! - Tool context for gateway tool access to shared state
+//! - Per-agent sandbox policy for file and shell tools
 
 pub mod context;
 pub mod gateway_tool;
 pub mod groups;
 pub mod policy;
+pub mod sandbox;
 pub mod tools;
@@ -146,6 +146,8 @@ mod tests {
             "web_fetch",
             "memory_search",
             "memory_get",
+            "memory_write",
+            "memory_forget",
             "sessions_list",
             "sessions_history",
             "sessions_send",
@@ -189,6 +191,7 @@ mod tests {
         assert!(effective.contains(&"read".to_string()));
         assert!(effective.contains(&"bash".to_string()));
         assert!(effective.contains(&"image".to_string()));
+        assert!(effective.contains(&"memory_write".to_string()));
         assert!(!effective.contains(&"message".to_string()));
     }
 
@@ -0,0 +1,331 @@
+//! Linux enforcement for sandboxed shell commands.
+//!
+//! Everything that allocates or can fail for a reason other than the
+//! kernel refusing (rulesets, seccomp programs, strings) is prepared in the
+//! parent. The `pre_exec` hooks only make raw syscalls, since nothing else
+//! is safe between `fork` and `exec`.
+//!
+//! The "linux" backend runs each command:
+//!
+//! - under CPU time, address space and process count limits,
+//! - in new user and mount namespaces (and optionally a network namespace
+//!   with no interfaces but loopback), mapped to the caller's uid/gid,
+//! - with a private tmpfs mounted on `/tmp`,
+//! - under the policy's Landlock write restrictions, if any,
+//! - with a seccomp filter denying syscalls that reconfigure the kernel or
+//!   the sandbox itself (mounts, namespaces, ptrace, modules, BPF, ...).
+
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::io;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+
+use landlock::{
+    ABI, AccessFs, BitFlags, CompatLevel, Compatible, Ruleset, RulesetAttr, RulesetCreatedAttr,
+    path_beneath_rules,
+};
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+    SeccompRule,
+};
+
+use super::Isolation;
+
+/// Kernel objects a prepared command refers to until it is spawned.
+pub(super) type Guard = OwnedFd;
+
+/// Syscalls isolated commands may not make.
+const DENIED_SYSCALLS: &[libc::c_long] = &[
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_chroot,
+    libc::SYS_unshare,
+    libc::SYS_setns,
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_kexec_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_reboot,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_acct,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_userfaultfd,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_open_by_handle_at,
+    libc::SYS_sethostname,
+    libc::SYS_setdomainname,
+    libc::SYS_settimeofday,
+    libc::SYS_clock_settime,
+];
+
+/// `struct landlock_path_beneath_attr` from the Landlock UAPI.
+#[repr(C, packed)]
+struct PathBeneathAttr {
+    allowed_access: u64,
+    parent_fd: libc::c_int,
+}
+
+const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+
+const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+
+/// `clone` flags that would create new namespaces.
+const NAMESPACE_FLAGS: &[libc::c_int] = &[
+    libc::CLONE_NEWUSER,
+    libc::CLONE_NEWNS,
+    libc::CLONE_NEWNET,
+    libc::CLONE_NEWPID,
+    libc::CLONE_NEWIPC,
+    libc::CLONE_NEWUTS,
+    libc::CLONE_NEWCGROUP,
+];
+
+/// Restrict the command's filesystem writes to `writable` (plus `/dev`)
+/// with a Landlock ruleset applied in the child before `exec`.
+pub(super) fn restrict_writes(
+    cmd: &mut tokio::process::Command,
+    writable: &[PathBuf],
+) -> io::Result<Guard> {
+    let fd = write_ruleset(writable, write_access()?)?;
+    let raw = fd.as_raw_fd();
+    // SAFETY: only async-signal-safe syscalls run between fork and exec.
+    unsafe {
+        cmd.pre_exec(move || restrict_self(raw));
+    }
+    Ok(fd)
+}
+
+/// Run the command with the "linux" backend. `writable` is `None` when the
+/// policy doesn't restrict writes.
+pub(super) fn isolate(
+    cmd: &mut tokio::process::Command,
+    isolation: &Isolation,
+    writable: Option<&[PathBuf]>,
+) -> io::Result<Option<Guard>> {
+    let (ruleset, write_access) = match writable {
+        Some(writable) => {
+            let access = write_access()?;
+            (Some(write_ruleset(writable, access)?), access.bits())
+        }
+        None => (None, 0),
+    };
+    let raw_ruleset = ruleset.as_ref().map(|fd| fd.as_raw_fd());
+    let filters = seccomp_filters()?;
+
+    let mut namespaces = libc::CLONE_NEWUSER | libc::CLONE_NEWNS;
+    if !isolation.network {
+        namespaces |= libc::CLONE_NEWNET;
+    }
+    // SAFETY: getuid and getgid can't fail.
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    let uid_map = format!("{uid} {uid} 1");
+    let gid_map = format!("{gid} {gid} 1");
+    let tmp_options = CString::new(format!("size={}m,mode=1777", isolation.tmp_mb))?;
+    let cpu = limit(isolation.cpu_seconds);
+    let memory = limit(isolation.memory_mb.saturating_mul(1024 * 1024));
+    let processes = limit(isolation.max_processes);
+
+    // SAFETY: only async-signal-safe syscalls run between fork and exec,
+    // on data prepared above.
+    unsafe {
+        cmd.pre_exec(move || {
+            // Process limits are counted per user namespace from here on
+            check(libc::setrlimit(libc::RLIMIT_CPU, &cpu))?;
+            check(libc::setrlimit(libc::RLIMIT_AS, &memory))?;
+            check(libc::setrlimit(libc::RLIMIT_NPROC, &processes))?;
+
+            check(libc::unshare(namespaces))?;
+            write_file(c"/proc/self/setgroups", b"deny")?;
+            write_file(c"/proc/self/uid_map", uid_map.as_bytes())?;
+            write_file(c"/proc/self/gid_map", gid_map.as_bytes())?;
+
+            // Keep the scratch mount out of the parent namespace
+            check(libc::mount(
+                std::ptr::null(),
+                c"/".as_ptr(),
+                std::ptr::null(),
+                libc::MS_REC | libc::MS_PRIVATE,
+                std::ptr::null(),
+            ))?;
+            check(libc::mount(
+                c"tmpfs".as_ptr(),
+                c"/tmp".as_ptr(),
+                c"tmpfs".as_ptr(),
+                libc::MS_NOSUID | libc::MS_NODEV,
+                tmp_options.as_ptr().cast(),
+            ))?;
+
+            // The scratch tmpfs only exists in the child, so it can't be
+            // added to the ruleset up front
+            if let Some(raw) = raw_ruleset {
+                add_write_rule(raw, c"/tmp", write_access)?;
+                restrict_self(raw)?;
+            }
+            for filter in &filters {
+                seccompiler::apply_filter(filter).map_err(|_| io::Error::last_os_error())?;
+            }
+            Ok(())
+        });
+    }
+    Ok(ruleset)
+}
+
+/// Write rights of the newest Landlock ABI the kernel supports, so rights
+/// added after V1 (`Refer`, `Truncate`, `IoctlDev`) are denied wherever the
+/// kernel can enforce them while older kernels still get the V1 rights.
+///
+/// Computed up front rather than left to the crate's best-effort mode: the
+/// `/tmp` rule added in the child must use exactly the handled rights.
+fn write_access() -> io::Result<BitFlags<AccessFs>> {
+    // SAFETY: querying the ABI version passes no memory to the kernel.
+    let version = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_create_ruleset,
+            std::ptr::null::<libc::c_void>(),
+            0,
+            LANDLOCK_CREATE_RULESET_VERSION,
+        )
+    };
+    let access = AccessFs::from_write(ABI::from(version as i32));
+    if access.is_empty() {
+        return Err(io::Error::other("Landlock is not supported by this kernel"));
+    }
+    Ok(access)
+}
+
+/// Build a Landlock ruleset denying `write` access outside `writable` and
+/// `/dev`.
+fn write_ruleset(writable: &[PathBuf], write: BitFlags<AccessFs>) -> io::Result<OwnedFd> {
+    let mut paths: Vec<&Path> = writable.iter().map(PathBuf::as_path).collect();
+    paths.push(Path::new("/dev"));
+    let ruleset = Ruleset::default()
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(write)
+        .and_then(|r| r.create())
+        .and_then(|r| r.add_rules(path_beneath_rules(&paths, write)))
+        .map_err(io::Error::other)?;
+    Option::from(ruleset)
+        .ok_or_else(|| io::Error::other("Landlock is not supported by this kernel"))
+}
+
+/// Seccomp programs for isolated commands, in the order they are applied.
+///
+/// `clone3` gets its own program returning `ENOSYS`: its flags can't be
+/// inspected, and libc falls back to `clone` on that error only.
+#[allow(clippy::unnecessary_cast)] // syscall numbers are i32 on 32-bit targets
+fn seccomp_filters() -> io::Result<Vec<BpfProgram>> {
+    let arch = std::env::consts::ARCH
+        .try_into()
+        .map_err(io::Error::other)?;
+
+    let mut denied: BTreeMap<i64, Vec<SeccompRule>> = DENIED_SYSCALLS
+        .iter()
+        .map(|&nr| (nr as i64, vec![]))
+        .collect();
+    let clone_rules = NAMESPACE_FLAGS
+        .iter()
+        .map(|&flag| {
+            let flag = flag as u64;
+            SeccompCondition::new(
+                0,
+                SeccompCmpArgLen::Qword,
+                SeccompCmpOp::MaskedEq(flag),
+                flag,
+            )
+            .and_then(|c| SeccompRule::new(vec![c]))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::other)?;
+    denied.insert(libc::SYS_clone as i64, clone_rules);
+    let clone3 = BTreeMap::from([(libc::SYS_clone3 as i64, vec![])]);
+
+    [(denied, libc::EPERM), (clone3, libc::ENOSYS)]
+        .into_iter()
+        .map(|(rules, errno)| {
+            SeccompFilter::new(
+                rules,
+                SeccompAction::Allow,
+                SeccompAction::Errno(errno as u32),
+                arch,
+            )
+            .and_then(BpfProgram::try_from)
+            .map_err(io::Error::other)
+        })
+        .collect()
+}
+
+fn limit(value: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: value,
+        rlim_max: value,
+    }
+}
+
+/// Apply a Landlock ruleset to the calling process.
+fn restrict_self(ruleset: libc::c_int) -> io::Result<()> {
+    // SAFETY: plain syscalls on an fd owned by the caller.
+    unsafe {
+        check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+        check(libc::syscall(libc::SYS_landlock_restrict_self, ruleset, 0) as libc::c_int)
+    }
+}
+
+/// Allow `access` beneath `path` in a Landlock ruleset, without allocating.
+fn add_write_rule(ruleset: libc::c_int, path: &CStr, access: u64) -> io::Result<()> {
+    // SAFETY: plain syscalls on a NUL-terminated path and a valid attr.
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_PATH | libc::O_CLOEXEC);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let attr = PathBeneathAttr {
+            allowed_access: access,
+            parent_fd: fd,
+        };
+        let result = check(libc::syscall(
+            libc::SYS_landlock_add_rule,
+            ruleset,
+            LANDLOCK_RULE_PATH_BENEATH,
+            &attr as *const PathBeneathAttr,
+            0,
+        ) as libc::c_int);
+        libc::close(fd);
+        result
+    }
+}
+
+/// Write `data` to the file at `path` without allocating.
+fn write_file(path: &CStr, data: &[u8]) -> io::Result<()> {
+    // SAFETY: plain syscalls on a NUL-terminated path and a valid buffer.
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let written = libc::write(fd, data.as_ptr().cast(), data.len());
+        let result = if written == data.len() as isize {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        };
+        libc::close(fd);
+        result
+    }
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
@@ -0,0 +1,562 @@
+//! Per-agent sandbox policy.
+//!
+//! Resolves an agent's [`SandboxConfig`] into checks the file and shell
+//! tools apply before touching the filesystem:
+//!
+//! - `none` — no restrictions.
+//! - `read-only` — no tool may write. Shell commands run under a Landlock
+//!   ruleset that denies every filesystem write (except to `/dev`).
+//! - `workspace` — file paths and working directories must lie inside
+//!   `allowed_dirs` (default: the gateway working directory). Shell
+//!   commands may only write inside those directories.
+//!
+//! Paths are resolved through symlinks before they are checked, so a link
+//! inside an allowed directory can't be used to escape it.
+//!
+//! Shell commands run on one of two backends:
+//!
+//! - `host` — a plain child process, with the write restrictions above.
+//! - `linux` — additionally isolated with user/mount namespaces, seccomp,
+//!   resource limits, a private tmpfs on `/tmp` and optionally no network.
+//!   The private `/tmp` hides the host's, so allowed directories under
+//!   `/tmp` are only reachable as the working directory.
+
+#[cfg(target_os = "linux")]
+mod linux;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use aobot_types::SandboxConfig;
+
+#[cfg(target_os = "linux")]
+use linux::{Guard, isolate, restrict_writes};
+
+/// Default CPU time limit for isolated commands, in seconds.
+const DEFAULT_CPU_SECONDS: u64 = 300;
+/// Default address space limit for isolated commands, in MiB.
+const DEFAULT_MEMORY_MB: u64 = 2048;
+/// Default process limit for isolated commands.
+const DEFAULT_MAX_PROCESSES: u64 = 256;
+/// Default size of the private `/tmp`, in MiB.
+const DEFAULT_TMP_MB: u64 = 64;
+
+/// How strictly an agent's tools are sandboxed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SandboxMode {
+    #[default]
+    None,
+    ReadOnly,
+    Workspace,
+}
+
+impl FromStr for SandboxMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "read-only" => Ok(Self::ReadOnly),
+            "workspace" => Ok(Self::Workspace),
+            other => Err(format!(
+                "Invalid sandbox mode '{other}' (expected none, read-only or workspace)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SandboxMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::ReadOnly => "read-only",
+            Self::Workspace => "workspace",
+        })
+    }
+}
+
+/// Where shell commands run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SandboxBackend {
+    #[default]
+    Host,
+    Linux,
+}
+
+impl FromStr for SandboxBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "host" => Ok(Self::Host),
+            "linux" if cfg!(target_os = "linux") => Ok(Self::Linux),
+            "linux" => Err("The linux sandbox backend requires Linux".to_string()),
+            other => Err(format!(
+                "Invalid sandbox backend '{other}' (expected host or linux)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SandboxBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Host => "host",
+            Self::Linux => "linux",
+        })
+    }
+}
+
+/// Isolation settings for the `linux` backend, with defaults applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Isolation {
+    network: bool,
+    cpu_seconds: u64,
+    memory_mb: u64,
+    max_processes: u64,
+    tmp_mb: u64,
+}
+
+impl Isolation {
+    fn from_config(config: &SandboxConfig) -> Self {
+        let limits = &config.limits;
+        Self {
+            network: config.network,
+            cpu_seconds: limits.cpu_seconds.unwrap_or(DEFAULT_CPU_SECONDS),
+            memory_mb: limits.memory_mb.unwrap_or(DEFAULT_MEMORY_MB),
+            max_processes: limits.max_processes.unwrap_or(DEFAULT_MAX_PROCESSES),
+            tmp_mb: limits.tmp_mb.unwrap_or(DEFAULT_TMP_MB),
+        }
+    }
+}
+
+impl Default for Isolation {
+    fn default() -> Self {
+        Self::from_config(&SandboxConfig {
+            network: true,
+            ..Default::default()
+        })
+    }
+}
+
+/// Kind of filesystem access a tool call needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAccess {
+    Read,
+    Write,
+}
+
+/// Resolved sandbox policy for one agent session.
+#[derive(Debug, Clone)]
+pub struct SandboxPolicy {
+    mode: SandboxMode,
+    backend: SandboxBackend,
+    /// Only used by the linux backend.
+    isolation: Isolation,
+    /// Canonical allowed directories (only used in workspace mode).
+    allowed_dirs: Vec<PathBuf>,
+    /// Directory relative paths resolve against.
+    working_dir: PathBuf,
+}
+
+impl SandboxPolicy {
+    /// A policy that allows everything.
+    pub fn unrestricted(working_dir: &Path) -> Self {
+        Self {
+            mode: SandboxMode::None,
+            backend: SandboxBackend::Host,
+            isolation: Isolation::default(),
+            allowed_dirs: vec![],
+            working_dir: working_dir.to_path_buf(),
+        }
+    }
+
+    /// Resolve an agent's sandbox config.
+    ///
+    /// In workspace mode the working directory becomes the first allowed
+    /// directory unless the gateway working directory is itself allowed.
+    /// Allowed directories that don't exist are an error.
+    pub fn from_config(config: Option<&SandboxConfig>, working_dir: &Path) -> Result<Self, String> {
+        let Some(config) = config else {
+            return Ok(Self::unrestricted(working_dir));
+        };
+        let base = Self {
+            mode: config.mode.parse()?,
+            backend: config.backend.parse()?,
+            isolation: Isolation::from_config(config),
+            ..Self::unrestricted(working_dir)
+        };
+        if base.mode != SandboxMode::Workspace {
+            return Ok(base);
+        }
+
+        let dirs: Vec<PathBuf> = if config.allowed_dirs.is_empty() {
+            vec![working_dir.to_path_buf()]
+        } else {
+            config
+                .allowed_dirs
+                .iter()
+                .map(|d| working_dir.join(d))
+                .collect()
+        };
+        let allowed_dirs = dirs
+            .iter()
+            .map(|d| {
+                std::fs::canonicalize(d)
+                    .map_err(|e| format!("Invalid sandbox directory {}: {e}", d.display()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let working_dir = match std::fs::canonicalize(working_dir) {
+            Ok(dir) if allowed_dirs.iter().any(|a| dir.starts_with(a)) => dir,
+            _ => allowed_dirs[0].clone(),
+        };
+        Ok(Self {
+            allowed_dirs,
+            working_dir,
+            ..base
+        })
+    }
+
+    pub fn mode(&self) -> SandboxMode {
+        self.mode
+    }
+
+    pub fn backend(&self) -> SandboxBackend {
+        self.backend
+    }
+
+    /// Short description of how shell commands are sandboxed, reported in
+    /// tool results.
+    pub fn describe(&self) -> String {
+        let mut text = format!("{} backend, {} mode", self.backend, self.mode);
+        if self.backend == SandboxBackend::Linux {
+            let i = &self.isolation;
+            text.push_str(&format!(
+                " (namespaces, seccomp, {}s CPU, {} MiB memory, {} processes, network {})",
+                i.cpu_seconds,
+                i.memory_mb,
+                i.max_processes,
+                if i.network { "on" } else { "off" }
+            ));
+        }
+        text
+    }
+
+    /// Directory tools start in and resolve relative paths against.
+    pub fn working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
+    /// Check that `path` may be accessed and return it resolved.
+    ///
+    /// Errors describe the violation and are meant to be shown to the model.
+    pub fn check_path(&self, path: &str, access: FileAccess) -> Result<PathBuf, String> {
+        let joined = self.working_dir.join(path);
+        match self.mode {
+            SandboxMode::None => Ok(joined),
+            SandboxMode::ReadOnly if access == FileAccess::Write => Err(format!(
+                "Sandbox violation: cannot write {path}, this agent's sandbox is read-only"
+            )),
+            SandboxMode::ReadOnly => Ok(joined),
+            SandboxMode::Workspace => {
+                let resolved = resolve(&joined).map_err(|e| format!("Invalid path {path}: {e}"))?;
+                if self.allowed_dirs.iter().any(|d| resolved.starts_with(d)) {
+                    Ok(resolved)
+                } else {
+                    Err(format!(
+                        "Sandbox violation: {path} is outside the allowed directories ({})",
+                        self.allowed_dirs_display()
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Build a `sh -c`-style command for `program`, running in
+    /// `working_dir` (default: the policy's working directory) on the
+    /// policy's backend, under its write restrictions.
+    pub fn shell_command(
+        &self,
+        program: &str,
+        command: &str,
+        working_dir: Option<&str>,
+    ) -> Result<ShellCommand, String> {
+        let dir = match working_dir {
+            Some(dir) => self.check_path(dir, FileAccess::Read)?,
+            None => self.working_dir.clone(),
+        };
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.arg("-c").arg(command).current_dir(dir);
+
+        let writable = match self.mode {
+            SandboxMode::None => None,
+            SandboxMode::ReadOnly => Some(vec![]),
+            SandboxMode::Workspace => Some(self.allowed_dirs.clone()),
+        };
+        let guard = match (self.backend, writable) {
+            (SandboxBackend::Host, None) => Ok(None),
+            (SandboxBackend::Host, Some(writable)) => {
+                restrict_writes(&mut cmd, &writable).map(Some)
+            }
+            (SandboxBackend::Linux, writable) => {
+                isolate(&mut cmd, &self.isolation, writable.as_deref())
+            }
+        }
+        .map_err(|e| format!("Sandbox unavailable, refusing to run command: {e}"))?;
+        Ok(ShellCommand {
+            command: cmd,
+            guard,
+        })
+    }
+
+    fn allowed_dirs_display(&self) -> String {
+        self.allowed_dirs
+            .iter()
+            .map(|d| d.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A shell command prepared by [`SandboxPolicy::shell_command`].
+///
+/// Keep it alive until the command has been spawned: it owns the kernel
+/// objects the child attaches to on start.
+pub struct ShellCommand {
+    pub command: tokio::process::Command,
+    #[allow(dead_code)]
+    guard: Option<Guard>,
+}
+
+/// Canonicalize a path that may not exist yet: the longest existing
+/// ancestor is resolved and the remaining components appended.
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        match std::fs::canonicalize(existing) {
+            Ok(mut resolved) => {
+                for component in rest.iter().rev() {
+                    resolved.push(component);
+                }
+                return Ok(resolved);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                // `..` after a missing directory can't be resolved safely,
+                // and has no file name
+                let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+                    return Err(e);
+                };
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+type Guard = ();
+
+#[cfg(not(target_os = "linux"))]
+fn restrict_writes(_cmd: &mut tokio::process::Command, _writable: &[PathBuf]) -> io::Result<Guard> {
+    Err(io::Error::other("write restrictions require Linux"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn isolate(
+    _cmd: &mut tokio::process::Command,
+    _isolation: &Isolation,
+    _writable: Option<&[PathBuf]>,
+) -> io::Result<Option<Guard>> {
+    Err(io::Error::other("process isolation requires Linux"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: &str, dirs: &[&Path]) -> SandboxConfig {
+        SandboxConfig {
+            mode: mode.to_string(),
+            allowed_dirs: dirs
+                .iter()
+                .map(|d| d.to_string_lossy().to_string())
+                .collect(),
+            backend: "host".to_string(),
+            network: true,
+            limits: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_mode_parsing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            SandboxPolicy::from_config(None, dir.path()).unwrap().mode(),
+            SandboxMode::None
+        );
+        let policy =
+            SandboxPolicy::from_config(Some(&config("read-only", &[])), dir.path()).unwrap();
+        assert_eq!(policy.mode(), SandboxMode::ReadOnly);
+        assert!(SandboxPolicy::from_config(Some(&config("strict", &[])), dir.path()).is_err());
+
+        assert_eq!(policy.backend(), SandboxBackend::Host);
+        let mut linux = config("none", &[]);
+        linux.backend = "linux".to_string();
+        linux.limits.cpu_seconds = Some(10);
+        let policy = SandboxPolicy::from_config(Some(&linux), dir.path()).unwrap();
+        assert_eq!(policy.backend(), SandboxBackend::Linux);
+        assert!(policy.describe().starts_with("linux backend, none mode"));
+        assert!(policy.describe().contains("10s CPU"));
+        linux.backend = "vm".to_string();
+        assert!(SandboxPolicy::from_config(Some(&linux), dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_read_only_denies_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy =
+            SandboxPolicy::from_config(Some(&config("read-only", &[])), dir.path()).unwrap();
+        assert!(policy.check_path("notes.md", FileAccess::Read).is_ok());
+        let err = policy
+            .check_path("notes.md", FileAccess::Write)
+            .unwrap_err();
+        assert!(err.contains("read-only"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_workspace_confines_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let workspace = root.join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::write(root.join("secret.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(root.join("secret.txt"), workspace.join("link.txt")).unwrap();
+
+        // The gateway working dir is outside the workspace, so tools start in it
+        let policy =
+            SandboxPolicy::from_config(Some(&config("workspace", &[&workspace])), &root).unwrap();
+        assert_eq!(policy.working_dir(), workspace);
+
+        assert_eq!(
+            policy.check_path("new/file.md", FileAccess::Write).unwrap(),
+            workspace.join("new/file.md")
+        );
+        assert!(
+            policy
+                .check_path(&root.join("secret.txt").to_string_lossy(), FileAccess::Read)
+                .is_err()
+        );
+        assert!(
+            policy
+                .check_path("../secret.txt", FileAccess::Read)
+                .is_err()
+        );
+        assert!(policy.check_path("link.txt", FileAccess::Read).is_err());
+        assert!(
+            policy
+                .check_path("missing/../../secret.txt", FileAccess::Read)
+                .is_err()
+        );
+
+        let err = policy
+            .shell_command("sh", "true", Some(&root.to_string_lossy()))
+            .err()
+            .unwrap();
+        assert!(err.contains("outside the allowed directories"));
+
+        // Missing allowed directories are reported
+        assert!(
+            SandboxPolicy::from_config(Some(&config("workspace", &[&root.join("nope")])), &root)
+                .is_err()
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    #[ignore = "needs Landlock (Linux 5.13+)"]
+    async fn test_shell_command_restricts_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let workspace = root.join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        let policy =
+            SandboxPolicy::from_config(Some(&config("workspace", &[&workspace])), &root).unwrap();
+
+        let script = format!(
+            "echo ok > inside.txt; echo no > {}/outside.txt",
+            root.display()
+        );
+        let mut shell = policy.shell_command("sh", &script, None).unwrap();
+        let output = shell.command.output().await.unwrap();
+        assert!(!output.status.success());
+        assert!(workspace.join("inside.txt").exists());
+        assert!(!root.join("outside.txt").exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    #[ignore = "needs Landlock ABI 3 (Linux 6.2+) and perl"]
+    async fn test_shell_command_denies_truncate() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let workspace = root.join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        let outside = root.join("outside.txt");
+        std::fs::write(&outside, "keep").unwrap();
+
+        for mode in ["read-only", "workspace"] {
+            let policy =
+                SandboxPolicy::from_config(Some(&config(mode, &[&workspace])), &root).unwrap();
+            // truncate(2) by path, which needs no write access to the file
+            let script = format!(
+                "perl -e 'truncate($ARGV[0], 0) or exit 1' {}",
+                outside.display()
+            );
+            let mut shell = policy.shell_command("sh", &script, None).unwrap();
+            let output = shell.command.output().await.unwrap();
+            assert_eq!(output.status.code(), Some(1), "{mode}");
+            assert_eq!(std::fs::read_to_string(&outside).unwrap(), "keep", "{mode}");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    #[ignore = "needs Landlock and unprivileged user namespaces"]
+    async fn test_linux_backend_isolates_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let mut config = config("read-only", &[]);
+        config.backend = "linux".to_string();
+        config.network = false;
+        config.limits.cpu_seconds = Some(7);
+        let policy = SandboxPolicy::from_config(Some(&config), &root).unwrap();
+
+        // Named after the tempdir so it can't collide on the host
+        let scratch = format!(
+            "/tmp/{}.scratch",
+            root.file_name().unwrap().to_string_lossy()
+        );
+        let script = format!(
+            "echo scratch > {scratch} && test -s {scratch} || exit 11; \
+             echo no > out.txt && exit 12; \
+             test \"$(ulimit -t)\" = 7 || exit 13; \
+             test \"$(grep -c : /proc/net/dev)\" = 1 || exit 14; \
+             grep -q '^Seccomp:.*2' /proc/self/status || exit 15"
+        );
+        let mut shell = policy.shell_command("sh", &script, None).unwrap();
+        let output = shell.command.output().await.unwrap();
+        assert_eq!(
+            output.status.code(),
+            Some(0),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(!root.join("out.txt").exists());
+        assert!(!Path::new(&scratch).exists());
+    }
+}
@@ -9,6 +9,9 @@ use tokio_util::sync::CancellationToken;
 use pi_agent_core::agent_types::{AgentTool, AgentToolResult};
 use pi_agent_core::types::{ContentBlock, TextContent, Tool};
 
+use aobot_cron::CronDelivery;
+use aobot_cron::schedule::ScheduleSpec;
+
 use crate::context::{GatewayOp, GatewayToolContext};
 
 pub struct CronTool {
@@ -20,19 +23,40 @@ impl CronTool {
     pub fn new(ctx: Arc<GatewayToolContext>) -> Self {
         let definition = Tool {
             name: "cron".to_string(),
-            description: "Manage scheduled cron jobs. Actions: list, add, remove, update, run."
-                .to_string(),
+            description:
+                "Manage scheduled cron jobs: recurring (cron expression or fixed interval) and one-shot (at a time or after a delay). Actions: list, add, remove, update, run, runs (run history)."
+                    .to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "action": {
                         "type": "string",
-                        "enum": ["list", "add", "remove", "update", "run"],
+                        "enum": ["list", "add", "remove", "update", "run", "runs"],
                         "description": "The action to perform."
                     },
                     "schedule": {
                         "type": "string",
-                        "description": "Cron expression (for add action, e.g. '0 * * * *')."
+                        "description": "Cron expression (for add action). Standard 5 fields, optional leading seconds field, or a macro like '@hourly'/'@daily'. E.g. '0 9 * * 1-5'. Set exactly one of schedule, at, in or every."
+                    },
+                    "at": {
+                        "type": "string",
+                        "description": "Run once at this RFC3339 time (for add action), e.g. '2025-06-01T09:00:00+02:00'."
+                    },
+                    "in": {
+                        "type": "string",
+                        "description": "Run once after this delay (for add action), e.g. '20m', '1h30m', '2d'."
+                    },
+                    "every": {
+                        "type": "string",
+                        "description": "Run repeatedly at this fixed interval (for add action), e.g. '30m', '6h'."
+                    },
+                    "delete_after_run": {
+                        "type": "boolean",
+                        "description": "Delete a one-shot job (at/in) after it runs instead of disabling it (for add action). Defaults to true."
+                    },
+                    "timezone": {
+                        "type": "string",
+                        "description": "IANA timezone the schedule is evaluated in (for add action, e.g. 'Europe/Berlin'). Defaults to UTC."
                     },
                     "task": {
                         "type": "string",
@@ -40,7 +64,7 @@ impl CronTool {
                     },
                     "job_id": {
                         "type": "string",
-                        "description": "Job ID (for remove/update/run actions)."
+                        "description": "Job ID (for remove/update/run actions; optional filter for runs)."
                     },
                     "enabled": {
                         "type": "boolean",
@@ -48,7 +72,33 @@ impl CronTool {
                     },
                     "agent_id": {
                         "type": "string",
-                        "description": "Agent to run the task (for add action)."
+                        "description": "Agent to run the task (for add action). Defaults to the current agent."
+                    },
+                    "channel_id": {
+                        "type": "string",
+                        "description": "Channel to deliver the task output to (for add action). Defaults to the channel of the current conversation."
+                    },
+                    "recipient_id": {
+                        "type": "string",
+                        "description": "Recipient on that channel (for add action). Defaults to the sender of the current conversation."
+                    },
+                    "metadata": {
+                        "type": "object",
+                        "description": "Platform-specific delivery metadata, e.g. {\"chat_id\": 123} for Telegram (for add action)."
+                    },
+                    "missed_runs": {
+                        "type": "string",
+                        "enum": ["skip", "run_once"],
+                        "description": "Runs missed while the gateway was down: skip them, or run once on startup (for add action). Defaults to skip."
+                    },
+                    "overlap": {
+                        "type": "string",
+                        "enum": ["skip", "queue", "concurrent"],
+                        "description": "When the job comes due while its previous run is still in progress: skip, queue one more run, or run concurrently (for add action). Defaults to skip."
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of runs to return (for runs action, default 20)."
                     }
                 },
                 "required": ["action"]
@@ -91,24 +141,63 @@ impl AgentTool for CronTool {
                 self.ctx.ops_tx.send(GatewayOp::CronList { reply: tx })?;
             }
             "add" => {
-                let schedule = params
-                    .get("schedule")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing required parameter: schedule")?
-                    .to_string();
+                let str_param = |key: &str| params.get(key).and_then(|v| v.as_str());
+                let timezone = str_param("timezone").map(String::from);
+                // Reject bad schedules before they reach the gateway; `in` becomes an `at` time
+                let (kind, schedule) = ScheduleSpec::from_params(
+                    str_param("schedule"),
+                    str_param("at"),
+                    str_param("in"),
+                    str_param("every"),
+                )
+                .and_then(|spec| spec.resolve(timezone.as_deref(), chrono::Utc::now()))
+                .map_err(|e| e.to_string())?;
+                let delete_after_run = params
+                    .get("delete_after_run")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
                 let task = params
                     .get("task")
                     .and_then(|v| v.as_str())
                     .ok_or("Missing required parameter: task")?
                     .to_string();
+                // Default to the agent that created the job
                 let agent_id = params
                     .get("agent_id")
                     .and_then(|v| v.as_str())
-                    .map(String::from);
+                    .map(String::from)
+                    .or_else(|| Some(self.ctx.current_agent_id.clone()));
+                // Explicit delivery target; otherwise the gateway defaults to
+                // the channel and sender of the current session
+                let delivery = match params.get("channel_id").and_then(|v| v.as_str()) {
+                    Some(channel_id) => Some(CronDelivery {
+                        channel_id: channel_id.to_string(),
+                        recipient_id: params
+                            .get("recipient_id")
+                            .and_then(|v| v.as_str())
+                            .ok_or("Missing required parameter: recipient_id (with channel_id)")?
+                            .to_string(),
+                        metadata: params
+                            .get("metadata")
+                            .and_then(|v| v.as_object())
+                            .map(|m| m.clone().into_iter().collect())
+                            .unwrap_or_default(),
+                    }),
+                    None => None,
+                };
+                let missed_runs = parse_policy(&params, "missed_runs")?;
+                let overlap = parse_policy(&params, "overlap")?;
                 self.ctx.ops_tx.send(GatewayOp::CronAdd {
+                    kind,
                     schedule,
+                    timezone,
                     task,
                     agent_id,
+                    delivery,
+                    origin_session_key: self.ctx.current_session_key.clone(),
+                    missed_runs,
+                    overlap,
+                    delete_after_run,
                     reply: tx,
                 })?;
             }
@@ -145,6 +234,18 @@ impl AgentTool for CronTool {
                     .ops_tx
                     .send(GatewayOp::CronRun { job_id, reply: tx })?;
             }
+            "runs" => {
+                let job_id = params
+                    .get("job_id")
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+                let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+                self.ctx.ops_tx.send(GatewayOp::CronRuns {
+                    job_id,
+                    limit,
+                    reply: tx,
+                })?;
+            }
             other => {
                 return Err(format!("Unknown cron action: {other}").into());
             }
@@ -166,3 +267,16 @@ impl AgentTool for CronTool {
         })
     }
 }
+
+/// Parse an optional policy parameter (e.g. `"run_once"`), using the default when absent.
+fn parse_policy<T: serde::de::DeserializeOwned + Default>(
+    params: &Value,
+    key: &str,
+) -> Result<T, String> {
+    match params.get(key) {
+        Some(v) if !v.is_null() => {
+            serde_json::from_value(v.clone()).map_err(|_| format!("Invalid value for {key}: {v}"))
+        }
+        _ => Ok(T::default()),
+    }
+}
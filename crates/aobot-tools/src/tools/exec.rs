@@ -10,6 +10,8 @@ use pi_agent_core::agent_types::{AgentTool, AgentToolResult};
 use pi_agent_core::types::{ContentBlock, TextContent, Tool};
 
 use crate::context::GatewayToolContext;
+use crate::sandbox::SandboxPolicy;
+use crate::tools::process::BackgroundProcessRegistry;
 
 /// Maximum output size in characters before truncation.
 const MAX_OUTPUT_CHARS: usize = 200_000;
@@ -18,15 +20,48 @@ const MAX_OUTPUT_CHARS: usize = 200_000;
 const DEFAULT_TIMEOUT_SECS: u64 = 120;
 
 pub struct ExecTool {
-    _ctx: Arc<GatewayToolContext>,
+    name: &'static str,
+    /// Shell that runs the command (`<shell> -c <command>`).
+    shell: &'static str,
+    sandbox: Arc<SandboxPolicy>,
+    /// Registry that owns background commands.
+    processes: Arc<BackgroundProcessRegistry>,
     definition: Tool,
 }
 
 impl ExecTool {
     pub fn new(ctx: Arc<GatewayToolContext>) -> Self {
+        Self::build(
+            "exec",
+            "sh",
+            ctx.sandbox.clone(),
+            ctx.processes.clone(),
+            "Execute a shell command with enhanced features: configurable timeout, output truncation, and background execution mode.",
+        )
+    }
+
+    /// A `bash` tool that runs commands under the agent's sandbox, used in
+    /// place of the unrestricted base tool when a sandbox is configured.
+    pub fn bash(sandbox: Arc<SandboxPolicy>, processes: Arc<BackgroundProcessRegistry>) -> Self {
+        Self::build(
+            "bash",
+            "bash",
+            sandbox,
+            processes,
+            "Execute a bash command in the agent's sandbox, with a configurable timeout and background execution mode.",
+        )
+    }
+
+    fn build(
+        name: &'static str,
+        shell: &'static str,
+        sandbox: Arc<SandboxPolicy>,
+        processes: Arc<BackgroundProcessRegistry>,
+        description: &str,
+    ) -> Self {
         let definition = Tool {
-            name: "exec".to_string(),
-            description: "Execute a shell command with enhanced features: configurable timeout, output truncation, and background execution mode.".to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
@@ -40,7 +75,7 @@ impl ExecTool {
                     },
                     "background": {
                         "type": "boolean",
-                        "description": "Run in background mode (default: false)."
+                        "description": "Run in background mode (default: false). Manage the process with the process tool."
                     },
                     "working_dir": {
                         "type": "string",
@@ -51,7 +86,10 @@ impl ExecTool {
             }),
         };
         Self {
-            _ctx: ctx,
+            name,
+            shell,
+            sandbox,
+            processes,
             definition,
         }
     }
@@ -60,11 +98,14 @@ impl ExecTool {
 #[async_trait]
 impl AgentTool for ExecTool {
     fn name(&self) -> &str {
-        "exec"
+        self.name
     }
 
     fn label(&self) -> &str {
-        "Exec"
+        match self.name {
+            "bash" => "Bash",
+            _ => "Exec",
+        }
     }
 
     fn definition(&self) -> &Tool {
@@ -95,40 +136,42 @@ impl AgentTool for ExecTool {
             .and_then(|v| v.as_str())
             .map(String::from);
 
-        let mut cmd = tokio::process::Command::new("sh");
-        cmd.arg("-c").arg(command);
+        // Sandbox violations (e.g. a working_dir outside the workspace) are
+        // returned to the model as tool errors
+        let mut shell = self
+            .sandbox
+            .shell_command(self.shell, command, working_dir.as_deref())?;
+        let cmd = &mut shell.command;
 
-        if let Some(dir) = &working_dir {
-            cmd.current_dir(dir);
+        if background {
+            // Background mode: hand the child to the session's registry and
+            // return immediately
+            let entry = self
+                .processes
+                .spawn(cmd, command)
+                .await
+                .map_err(|e| format!("Failed to spawn command: {e}"))?;
+            let text = json!({
+                "mode": "background",
+                "session_id": entry.session_id,
+                "pid": entry.pid,
+                "command": command,
+                "status": "started",
+                "sandbox": self.sandbox.describe()
+            })
+            .to_string();
+            return Ok(AgentToolResult {
+                content: vec![ContentBlock::Text(TextContent {
+                    text,
+                    text_signature: None,
+                })],
+                details: None,
+            });
         }
 
         cmd.stdout(std::process::Stdio::piped());
         cmd.stderr(std::process::Stdio::piped());
 
-        if background {
-            // Background mode: spawn and return immediately
-            match cmd.spawn() {
-                Ok(child) => {
-                    let pid = child.id().unwrap_or(0);
-                    let text = json!({
-                        "mode": "background",
-                        "pid": pid,
-                        "command": command,
-                        "status": "started"
-                    })
-                    .to_string();
-                    return Ok(AgentToolResult {
-                        content: vec![ContentBlock::Text(TextContent {
-                            text,
-                            text_signature: None,
-                        })],
-                        details: None,
-                    });
-                }
-                Err(e) => return Err(format!("Failed to spawn command: {e}").into()),
-            }
-        }
-
         // Foreground mode: execute with timeout
         let timeout = tokio::time::Duration::from_secs(timeout_secs);
         let output = match tokio::time::timeout(timeout, cmd.output()).await {
@@ -153,7 +196,10 @@ impl AgentTool for ExecTool {
             truncated = true;
         }
 
-        let mut result = format!("Exit code: {exit_code}\n");
+        let mut result = format!(
+            "Exit code: {exit_code}\nSandbox: {}\n",
+            self.sandbox.describe()
+        );
         if !stdout.is_empty() {
             result.push_str(&format!("\n--- stdout ---\n{stdout}\n"));
         }
@@ -0,0 +1,139 @@
+//! Hook interception and events for agent tools.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tokio_util::sync::CancellationToken;
+
+use aobot_hooks::events::HookEvent;
+use aobot_hooks::registry::{HookRegistry, InterceptOutcome};
+use pi_agent_core::agent_types::{AgentTool, AgentToolResult};
+use pi_agent_core::types::{ContentBlock, Tool};
+
+/// Wrap every tool of a session so its calls go through the hooks.
+pub fn apply_hooks(
+    tools: Vec<Arc<dyn AgentTool>>,
+    hooks: &Arc<HookRegistry>,
+    session_key: &str,
+    agent_id: &str,
+) -> Vec<Arc<dyn AgentTool>> {
+    tools
+        .into_iter()
+        .map(|tool| HookedTool::wrap(tool, hooks, session_key, agent_id))
+        .collect()
+}
+
+/// A tool whose calls go through the `tool_call_before` interceptors and
+/// emit `tool_call_before`/`tool_call_after` events.
+///
+/// Interceptors may rewrite the call's parameters or reject it; a rejection
+/// is returned to the model as a tool error. Changing the tool name has no
+/// effect. Rejected calls emit no events.
+pub struct HookedTool {
+    inner: Arc<dyn AgentTool>,
+    hooks: Arc<HookRegistry>,
+    session_key: String,
+    agent_id: String,
+}
+
+impl HookedTool {
+    pub fn wrap(
+        inner: Arc<dyn AgentTool>,
+        hooks: &Arc<HookRegistry>,
+        session_key: &str,
+        agent_id: &str,
+    ) -> Arc<dyn AgentTool> {
+        Arc::new(Self {
+            inner,
+            hooks: hooks.clone(),
+            session_key: session_key.to_string(),
+            agent_id: agent_id.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl AgentTool for HookedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn label(&self) -> &str {
+        self.inner.label()
+    }
+
+    fn definition(&self) -> &Tool {
+        self.inner.definition()
+    }
+
+    async fn execute(
+        &self,
+        tool_call_id: &str,
+        params: Value,
+        cancel: CancellationToken,
+        on_update: Option<Box<dyn Fn(AgentToolResult) + Send + Sync>>,
+    ) -> Result<AgentToolResult, Box<dyn std::error::Error + Send + Sync>> {
+        let tool_name = self.inner.name().to_string();
+        let event = HookEvent::ToolCallBefore {
+            session_key: self.session_key.clone(),
+            agent_id: self.agent_id.clone(),
+            tool_name: tool_name.clone(),
+            params,
+        };
+        let params = match self.hooks.intercept(event).await {
+            InterceptOutcome::Continue(HookEvent::ToolCallBefore { params, .. }) => params,
+            InterceptOutcome::Continue(_) => unreachable!("interceptors keep the event type"),
+            InterceptOutcome::Rejected {
+                interceptor,
+                reason,
+            } => {
+                tracing::info!(tool = %tool_name, %interceptor, "Tool call rejected: {reason}");
+                return Err(format!("Tool call rejected by hook {interceptor}: {reason}").into());
+            }
+        };
+
+        self.hooks
+            .emit(HookEvent::ToolCallBefore {
+                session_key: self.session_key.clone(),
+                agent_id: self.agent_id.clone(),
+                tool_name: tool_name.clone(),
+                params: params.clone(),
+            })
+            .await;
+
+        let outcome = self
+            .inner
+            .execute(tool_call_id, params, cancel, on_update)
+            .await;
+
+        let (result, is_error) = match &outcome {
+            Ok(result) => (json!({ "text": result_text(result) }), false),
+            Err(e) => (json!({ "error": e.to_string() }), true),
+        };
+        self.hooks
+            .emit(HookEvent::ToolCallAfter {
+                session_key: self.session_key.clone(),
+                agent_id: self.agent_id.clone(),
+                tool_name,
+                result,
+                is_error,
+            })
+            .await;
+
+        outcome
+    }
+}
+
+/// The text blocks of a tool result, joined by newlines.
+fn result_text(result: &AgentToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,89 @@
+//! `memory_forget` tool — remove notes saved with `memory_write`.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tokio_util::sync::CancellationToken;
+
+use pi_agent_core::agent_types::{AgentTool, AgentToolResult};
+use pi_agent_core::types::{ContentBlock, TextContent, Tool};
+
+use crate::context::{GatewayOp, GatewayToolContext};
+
+pub struct MemoryForgetTool {
+    ctx: Arc<GatewayToolContext>,
+    definition: Tool,
+}
+
+impl MemoryForgetTool {
+    pub fn new(ctx: Arc<GatewayToolContext>) -> Self {
+        let definition = Tool {
+            name: "memory_forget".to_string(),
+            description: "Delete notes saved with memory_write, by note id or by a chunk_id from memory_search results. Only the current user's and this agent's notes can be deleted.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "id": {
+                        "type": "string",
+                        "description": "Note id (from memory_write) or chunk id (from memory_search)."
+                    }
+                },
+                "required": ["id"]
+            }),
+        };
+        Self { ctx, definition }
+    }
+}
+
+#[async_trait]
+impl AgentTool for MemoryForgetTool {
+    fn name(&self) -> &str {
+        "memory_forget"
+    }
+
+    fn label(&self) -> &str {
+        "Memory Forget"
+    }
+
+    fn definition(&self) -> &Tool {
+        &self.definition
+    }
+
+    async fn execute(
+        &self,
+        _tool_call_id: &str,
+        params: Value,
+        _cancel: CancellationToken,
+        _on_update: Option<Box<dyn Fn(AgentToolResult) + Send + Sync>>,
+    ) -> Result<AgentToolResult, Box<dyn std::error::Error + Send + Sync>> {
+        let id = params
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing required parameter: id")?
+            .to_string();
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.ctx.ops_tx.send(GatewayOp::MemoryForget {
+            id,
+            agent_id: self.ctx.current_agent_id.clone(),
+            session_key: self.ctx.current_session_key.clone(),
+            reply: tx,
+        })?;
+
+        let result = rx.await?;
+        let text = match result {
+            crate::context::GatewayOpResult::Json(v) => serde_json::to_string_pretty(&v)?,
+            crate::context::GatewayOpResult::Text(t) => t,
+            crate::context::GatewayOpResult::Error(e) => return Err(e.into()),
+        };
+
+        Ok(AgentToolResult {
+            content: vec![ContentBlock::Text(TextContent {
+                text,
+                text_signature: None,
+            })],
+            details: None,
+        })
+    }
+}
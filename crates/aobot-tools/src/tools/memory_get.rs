@@ -21,14 +21,14 @@ impl MemoryGetTool {
         let definition = Tool {
             name: "memory_get".to_string(),
             description:
-                "Read content from a memory file, optionally within a specific line range."
+                "Read content from a memory file (a path returned by memory_search), optionally within a specific line range."
                     .to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "path": {
                         "type": "string",
-                        "description": "Path of the memory file to read."
+                        "description": "Path of the memory file to read, absolute or relative to a memory directory."
                     },
                     "start_line": {
                         "type": "integer",
@@ -86,6 +86,7 @@ impl AgentTool for MemoryGetTool {
             path,
             start_line,
             end_line,
+            session_key: self.ctx.current_session_key.clone(),
             reply: tx,
         })?;
 
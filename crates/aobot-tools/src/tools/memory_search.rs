@@ -11,6 +11,9 @@ use pi_agent_core::types::{ContentBlock, TextContent, Tool};
 
 use crate::context::{GatewayOp, GatewayToolContext};
 
+/// Most results a single search may return.
+const MAX_RESULTS: u64 = 100;
+
 pub struct MemorySearchTool {
     ctx: Arc<GatewayToolContext>,
     definition: Tool,
@@ -20,7 +23,7 @@ impl MemorySearchTool {
     pub fn new(ctx: Arc<GatewayToolContext>) -> Self {
         let definition = Tool {
             name: "memory_search".to_string(),
-            description: "Search memory files using hybrid vector + keyword search. Returns relevant passages with file paths, line numbers, and relevance scores.".to_string(),
+            description: "Search memory files and past conversations using hybrid vector + keyword search. Returns relevant passages with file paths, line numbers, and relevance scores.".to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
@@ -30,7 +33,12 @@ impl MemorySearchTool {
                     },
                     "max_results": {
                         "type": "integer",
-                        "description": "Maximum number of results to return (default: 10)."
+                        "description": "Maximum number of results to return (default: 10, at most 100)."
+                    },
+                    "sources": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Only search these sources, e.g. [\"session\"] for past conversations or [\"markdown\", \"code\"] for files (default: all)."
                     }
                 },
                 "required": ["query"]
@@ -69,12 +77,24 @@ impl AgentTool for MemorySearchTool {
         let max_results = params
             .get("max_results")
             .and_then(|v| v.as_u64())
-            .unwrap_or(10) as usize;
+            .unwrap_or(10)
+            .min(MAX_RESULTS) as usize;
+        let sources = params
+            .get("sources")
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|s| s.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default();
 
         let (tx, rx) = tokio::sync::oneshot::channel();
         self.ctx.ops_tx.send(GatewayOp::MemorySearch {
             query,
             max_results,
+            sources,
+            session_key: self.ctx.current_session_key.clone(),
             reply: tx,
         })?;
 
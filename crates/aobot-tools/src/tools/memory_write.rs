@@ -0,0 +1,100 @@
+//! `memory_write` tool — save a note to memory.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tokio_util::sync::CancellationToken;
+
+use pi_agent_core::agent_types::{AgentTool, AgentToolResult};
+use pi_agent_core::types::{ContentBlock, TextContent, Tool};
+
+use crate::context::{GatewayOp, GatewayToolContext, MemoryNoteScope};
+
+pub struct MemoryWriteTool {
+    ctx: Arc<GatewayToolContext>,
+    definition: Tool,
+}
+
+impl MemoryWriteTool {
+    pub fn new(ctx: Arc<GatewayToolContext>) -> Self {
+        let definition = Tool {
+            name: "memory_write".to_string(),
+            description: "Save a note to long-term memory, e.g. a user's preferences or a fact worth remembering. Notes are indexed immediately and found by memory_search. Returns the note id, which memory_forget accepts.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "content": {
+                        "type": "string",
+                        "description": "The note to save (Markdown)."
+                    },
+                    "scope": {
+                        "type": "string",
+                        "enum": ["user", "agent"],
+                        "description": "\"user\" (default) saves to the current user's notes; \"agent\" saves to notes shared by everyone talking to this agent."
+                    }
+                },
+                "required": ["content"]
+            }),
+        };
+        Self { ctx, definition }
+    }
+}
+
+#[async_trait]
+impl AgentTool for MemoryWriteTool {
+    fn name(&self) -> &str {
+        "memory_write"
+    }
+
+    fn label(&self) -> &str {
+        "Memory Write"
+    }
+
+    fn definition(&self) -> &Tool {
+        &self.definition
+    }
+
+    async fn execute(
+        &self,
+        _tool_call_id: &str,
+        params: Value,
+        _cancel: CancellationToken,
+        _on_update: Option<Box<dyn Fn(AgentToolResult) + Send + Sync>>,
+    ) -> Result<AgentToolResult, Box<dyn std::error::Error + Send + Sync>> {
+        let content = params
+            .get("content")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing required parameter: content")?
+            .to_string();
+        let scope = match params.get("scope").and_then(|v| v.as_str()) {
+            None | Some("user") => MemoryNoteScope::User,
+            Some("agent") => MemoryNoteScope::Agent,
+            Some(other) => return Err(format!("Invalid scope: {other}").into()),
+        };
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.ctx.ops_tx.send(GatewayOp::MemoryWrite {
+            content,
+            scope,
+            agent_id: self.ctx.current_agent_id.clone(),
+            session_key: self.ctx.current_session_key.clone(),
+            reply: tx,
+        })?;
+
+        let result = rx.await?;
+        let text = match result {
+            crate::context::GatewayOpResult::Json(v) => serde_json::to_string_pretty(&v)?,
+            crate::context::GatewayOpResult::Text(t) => t,
+            crate::context::GatewayOpResult::Error(e) => return Err(e.into()),
+        };
+
+        Ok(AgentToolResult {
+            content: vec![ContentBlock::Text(TextContent {
+                text,
+                text_signature: None,
+            })],
+            details: None,
+        })
+    }
+}
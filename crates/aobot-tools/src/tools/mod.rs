@@ -7,11 +7,15 @@ pub mod agents_list;
 pub mod cron;
 pub mod exec;
 pub mod gateway;
+pub mod hooked;
 pub mod image;
+pub mod memory_forget;
 pub mod memory_get;
 pub mod memory_search;
+pub mod memory_write;
 pub mod message;
 pub mod process;
+pub mod sandboxed;
 pub mod session_status;
 pub mod sessions_history;
 pub mod sessions_list;
@@ -40,6 +44,8 @@ pub fn create_gateway_tools(ctx: Arc<GatewayToolContext>) -> HashMap<String, Arc
         Arc::new(image::ImageTool::new(ctx.clone())),
         Arc::new(memory_search::MemorySearchTool::new(ctx.clone())),
         Arc::new(memory_get::MemoryGetTool::new(ctx.clone())),
+        Arc::new(memory_write::MemoryWriteTool::new(ctx.clone())),
+        Arc::new(memory_forget::MemoryForgetTool::new(ctx.clone())),
         Arc::new(process::ProcessTool::new(ctx.clone())),
         Arc::new(exec::ExecTool::new(ctx.clone())),
         Arc::new(tts::TtsTool::new(ctx.clone())),
@@ -2,10 +2,14 @@
 //!
 //! Provides actions to list, poll, log, write, kill, and remove background processes.
 
+use std::collections::VecDeque;
+use std::io;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use async_trait::async_trait;
 use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio_util::sync::CancellationToken;
 
 use pi_agent_core::agent_types::{AgentTool, AgentToolResult};
@@ -13,13 +17,32 @@ use pi_agent_core::types::{ContentBlock, TextContent, Tool};
 
 use crate::context::GatewayToolContext;
 
-/// Registry of background processes managed by the process tool.
+/// Output kept per stream and process; older output is dropped.
+const MAX_BUFFERED_BYTES: usize = 1024 * 1024;
+
+/// Bytes returned by `log` when no limit is given.
+const DEFAULT_LOG_LIMIT: usize = 64 * 1024;
+
+/// Bytes of each stream included in `poll` results.
+const POLL_TAIL_BYTES: usize = 2 * 1024;
+
+/// How often the process group of an exited process is checked for
+/// remaining members.
+const GROUP_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Registry of background processes started by one session.
+///
+/// The registry owns its processes: their output is captured into bounded
+/// buffers, and those still running are killed when it is dropped (i.e.
+/// when the session is deleted).
 pub struct BackgroundProcessRegistry {
-    processes: tokio::sync::RwLock<Vec<ProcessEntry>>,
+    processes: tokio::sync::RwLock<Vec<Arc<BackgroundProcess>>>,
+    next_id: AtomicU64,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct ProcessEntry {
+    /// Process session ID, e.g. "proc-1".
     pub session_id: String,
     pub pid: u32,
     pub command: String,
@@ -27,32 +50,279 @@ pub struct ProcessEntry {
     pub status: ProcessStatus,
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 pub enum ProcessStatus {
     Running,
     Exited(i32),
+    /// Terminated by a signal.
+    Signaled(i32),
+}
+
+impl From<std::process::ExitStatus> for ProcessStatus {
+    fn from(status: std::process::ExitStatus) -> Self {
+        #[cfg(unix)]
+        if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+            return Self::Signaled(signal);
+        }
+        Self::Exited(status.code().unwrap_or(-1))
+    }
+}
+
+/// Output stream of a background process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// State of a background process with the tail of its output.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProcessPoll {
+    #[serde(flatten)]
+    pub entry: ProcessEntry,
+    /// Total bytes written to stdout so far.
+    pub stdout_bytes: u64,
+    /// Total bytes written to stderr so far.
+    pub stderr_bytes: u64,
+    pub stdout_tail: String,
+    pub stderr_tail: String,
+}
+
+/// A slice of a process's output.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OutputChunk {
+    /// Offset of the first returned byte in the stream.
+    pub offset: u64,
+    /// Offset to continue reading from.
+    pub next_offset: u64,
+    /// Requested bytes that were already dropped from the buffer.
+    pub dropped: u64,
+    pub data: String,
+}
+
+struct BackgroundProcess {
+    entry: std::sync::Mutex<ProcessEntry>,
+    stdout: Arc<std::sync::Mutex<OutputBuffer>>,
+    stderr: Arc<std::sync::Mutex<OutputBuffer>>,
+    stdin: tokio::sync::Mutex<Option<tokio::process::ChildStdin>>,
+    /// Set once the process and everything left in its group has exited,
+    /// after which the pid may belong to an unrelated process.
+    group_drained: AtomicBool,
+}
+
+impl BackgroundProcess {
+    fn entry(&self) -> ProcessEntry {
+        self.entry.lock().unwrap().clone()
+    }
+
+    fn buffer(&self, stream: OutputStream) -> &std::sync::Mutex<OutputBuffer> {
+        match stream {
+            OutputStream::Stdout => &self.stdout,
+            OutputStream::Stderr => &self.stderr,
+        }
+    }
+
+    /// Send `signal` to the process group.
+    ///
+    /// This is done even after the process itself has exited, since
+    /// children it left running in the background are still in its group.
+    /// Once the group is drained there is nothing left to signal.
+    fn signal(&self, signal: i32) -> Result<(), String> {
+        if self.group_drained.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        send_signal(self.entry.lock().unwrap().pid, signal)
+    }
+}
+
+/// The most recent output of a stream, addressed by absolute offsets.
+#[derive(Default)]
+struct OutputBuffer {
+    data: VecDeque<u8>,
+    /// Offset of the first byte still in `data`.
+    start: u64,
+}
+
+impl OutputBuffer {
+    fn push(&mut self, bytes: &[u8]) {
+        self.data.extend(bytes);
+        let excess = self.data.len().saturating_sub(MAX_BUFFERED_BYTES);
+        self.data.drain(..excess);
+        self.start += excess as u64;
+    }
+
+    fn end(&self) -> u64 {
+        self.start + self.data.len() as u64
+    }
+
+    fn read(&self, offset: u64, limit: usize) -> OutputChunk {
+        let from = offset.clamp(self.start, self.end());
+        let bytes: Vec<u8> = self
+            .data
+            .iter()
+            .skip((from - self.start) as usize)
+            .take(limit)
+            .copied()
+            .collect();
+        OutputChunk {
+            offset: from,
+            next_offset: from + bytes.len() as u64,
+            dropped: self.start.saturating_sub(offset),
+            data: String::from_utf8_lossy(&bytes).to_string(),
+        }
+    }
+
+    fn tail(&self, limit: usize) -> String {
+        self.read(self.end().saturating_sub(limit as u64), limit)
+            .data
+    }
 }
 
 impl BackgroundProcessRegistry {
     pub fn new() -> Self {
         Self {
             processes: tokio::sync::RwLock::new(Vec::new()),
+            next_id: AtomicU64::new(1),
         }
     }
 
-    pub async fn register(&self, entry: ProcessEntry) {
-        self.processes.write().await.push(entry);
+    /// Spawn `cmd` as a background process and start capturing its output.
+    ///
+    /// The command gets piped stdio and, on Unix, its own process group so
+    /// signals reach everything it starts.
+    pub async fn spawn(
+        &self,
+        cmd: &mut tokio::process::Command,
+        command: &str,
+    ) -> io::Result<ProcessEntry> {
+        cmd.stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        #[cfg(unix)]
+        cmd.process_group(0);
+        let mut child = cmd.spawn()?;
+
+        let entry = ProcessEntry {
+            session_id: format!("proc-{}", self.next_id.fetch_add(1, Ordering::Relaxed)),
+            pid: child.id().unwrap_or(0),
+            command: command.to_string(),
+            started_at: chrono::Utc::now().timestamp_millis(),
+            status: ProcessStatus::Running,
+        };
+        let process = Arc::new(BackgroundProcess {
+            entry: std::sync::Mutex::new(entry.clone()),
+            stdout: Default::default(),
+            stderr: Default::default(),
+            stdin: tokio::sync::Mutex::new(child.stdin.take()),
+            group_drained: AtomicBool::new(false),
+        });
+        capture(child.stdout.take(), process.stdout.clone());
+        capture(child.stderr.take(), process.stderr.clone());
+
+        let waiter = process.clone();
+        let pid = entry.pid;
+        tokio::spawn(async move {
+            let status = match child.wait().await {
+                Ok(status) => status.into(),
+                Err(e) => {
+                    tracing::warn!("Failed to wait for background process: {e}");
+                    ProcessStatus::Exited(-1)
+                }
+            };
+            waiter.entry.lock().unwrap().status = status;
+            // Children left running keep the group, and with it the pid, in
+            // use. Once they are gone the pid can be reused
+            while group_exists(pid) {
+                tokio::time::sleep(GROUP_POLL_INTERVAL).await;
+            }
+            waiter.group_drained.store(true, Ordering::Release);
+        });
+
+        self.processes.write().await.push(process);
+        Ok(entry)
     }
 
     pub async fn list(&self) -> Vec<ProcessEntry> {
-        self.processes.read().await.clone()
+        self.processes
+            .read()
+            .await
+            .iter()
+            .map(|p| p.entry())
+            .collect()
+    }
+
+    /// Status of a process and the tail of its output.
+    pub async fn poll(&self, session_id: &str) -> Option<ProcessPoll> {
+        let process = self.get(session_id).await?;
+        let stdout = process.stdout.lock().unwrap();
+        let stderr = process.stderr.lock().unwrap();
+        Some(ProcessPoll {
+            entry: process.entry(),
+            stdout_bytes: stdout.end(),
+            stderr_bytes: stderr.end(),
+            stdout_tail: stdout.tail(POLL_TAIL_BYTES),
+            stderr_tail: stderr.tail(POLL_TAIL_BYTES),
+        })
     }
 
-    pub async fn remove(&self, session_id: &str) -> bool {
+    /// Read up to `limit` bytes of a stream, starting at `offset`.
+    pub async fn log(
+        &self,
+        session_id: &str,
+        stream: OutputStream,
+        offset: u64,
+        limit: usize,
+    ) -> Option<OutputChunk> {
+        let process = self.get(session_id).await?;
+        let chunk = process.buffer(stream).lock().unwrap().read(offset, limit);
+        Some(chunk)
+    }
+
+    /// Write `data` to a process's stdin, closing it afterwards if `eof`.
+    pub async fn write(&self, session_id: &str, data: &str, eof: bool) -> Result<(), String> {
+        let process = self.get_or_err(session_id).await?;
+        let mut stdin = process.stdin.lock().await;
+        let pipe = stdin
+            .as_mut()
+            .ok_or_else(|| format!("stdin of process {session_id} is closed"))?;
+        let failed = |e: io::Error| format!("Failed to write to process {session_id}: {e}");
+        pipe.write_all(data.as_bytes()).await.map_err(failed)?;
+        pipe.flush().await.map_err(failed)?;
+        if eof {
+            stdin.take();
+        }
+        Ok(())
+    }
+
+    /// Send a signal to a process and its process group.
+    pub async fn kill(&self, session_id: &str, signal: i32) -> Result<(), String> {
+        self.get_or_err(session_id).await?.signal(signal)
+    }
+
+    /// Forget a process, killing whatever is left of its process group.
+    pub async fn remove(&self, session_id: &str) -> Option<ProcessEntry> {
         let mut procs = self.processes.write().await;
-        let len = procs.len();
-        procs.retain(|p| p.session_id != session_id);
-        procs.len() < len
+        let index = procs
+            .iter()
+            .position(|p| p.entry().session_id == session_id)?;
+        let process = procs.remove(index);
+        let _ = process.signal(KILL_SIGNAL);
+        Some(process.entry())
+    }
+
+    async fn get(&self, session_id: &str) -> Option<Arc<BackgroundProcess>> {
+        self.processes
+            .read()
+            .await
+            .iter()
+            .find(|p| p.entry().session_id == session_id)
+            .cloned()
+    }
+
+    async fn get_or_err(&self, session_id: &str) -> Result<Arc<BackgroundProcess>, String> {
+        self.get(session_id)
+            .await
+            .ok_or_else(|| format!("No background process {session_id}"))
     }
 }
 
@@ -62,8 +332,103 @@ impl Default for BackgroundProcessRegistry {
     }
 }
 
+impl Drop for BackgroundProcessRegistry {
+    fn drop(&mut self) {
+        for process in self.processes.get_mut().iter() {
+            let _ = process.signal(KILL_SIGNAL);
+        }
+    }
+}
+
+/// Copy a child's output stream into `buffer` until it closes.
+fn capture(
+    stream: Option<impl AsyncRead + Unpin + Send + 'static>,
+    buffer: Arc<std::sync::Mutex<OutputBuffer>>,
+) {
+    let Some(mut stream) = stream else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut chunk = [0u8; 8192];
+        while let Ok(n) = stream.read(&mut chunk).await
+            && n > 0
+        {
+            buffer.lock().unwrap().push(&chunk[..n]);
+        }
+    });
+}
+
+#[cfg(unix)]
+const KILL_SIGNAL: i32 = libc::SIGKILL;
+#[cfg(not(unix))]
+const KILL_SIGNAL: i32 = 9;
+
+/// Parse a signal name ("SIGTERM", "term") or number.
+#[cfg(unix)]
+pub fn parse_signal(name: &str) -> Result<i32, String> {
+    if let Ok(number) = name.parse() {
+        return Ok(number);
+    }
+    let upper = name.to_ascii_uppercase();
+    Ok(match upper.strip_prefix("SIG").unwrap_or(&upper) {
+        "TERM" => libc::SIGTERM,
+        "KILL" => libc::SIGKILL,
+        "INT" => libc::SIGINT,
+        "HUP" => libc::SIGHUP,
+        "QUIT" => libc::SIGQUIT,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        "STOP" => libc::SIGSTOP,
+        "CONT" => libc::SIGCONT,
+        _ => return Err(format!("Unknown signal: {name}")),
+    })
+}
+
+#[cfg(not(unix))]
+pub fn parse_signal(name: &str) -> Result<i32, String> {
+    Err(format!("Sending {name} requires Unix"))
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: i32) -> Result<(), String> {
+    // kill(0) would signal the gateway's own process group
+    if pid == 0 {
+        return Err("Process has no pid to signal".to_string());
+    }
+    // Background processes lead their own process group
+    // SAFETY: kill has no memory safety requirements.
+    if unsafe { libc::kill(-(pid as libc::pid_t), signal) } != 0 {
+        let err = io::Error::last_os_error();
+        // The whole group is gone already
+        if err.raw_os_error() == Some(libc::ESRCH) {
+            return Ok(());
+        }
+        return Err(format!("Failed to signal process {pid}: {err}"));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_signal(pid: u32, _signal: i32) -> Result<(), String> {
+    Err(format!("Cannot signal process {pid}: signals require Unix"))
+}
+
+/// Whether any process is left in the process group led by `pid`.
+#[cfg(unix)]
+fn group_exists(pid: u32) -> bool {
+    // SAFETY: kill has no memory safety requirements.
+    pid != 0
+        && (unsafe { libc::kill(-(pid as libc::pid_t), 0) } == 0
+            || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+}
+
+#[cfg(not(unix))]
+fn group_exists(_pid: u32) -> bool {
+    false
+}
+
 pub struct ProcessTool {
-    _ctx: Arc<GatewayToolContext>,
+    ctx: Arc<GatewayToolContext>,
     definition: Tool,
 }
 
@@ -72,19 +437,40 @@ impl ProcessTool {
         let definition = Tool {
             name: "process".to_string(),
             description:
-                "Manage background processes. Actions: list, poll, log, write, kill, remove."
+                "Manage background processes started with exec or bash. Actions: list, poll, log, write, kill, remove."
                     .to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "action": {
                         "type": "string",
-                        "enum": ["list", "poll", "kill", "remove"],
+                        "enum": ["list", "poll", "log", "write", "kill", "remove"],
                         "description": "The action to perform."
                     },
                     "session_id": {
                         "type": "string",
-                        "description": "Process session ID (for poll/kill/remove)."
+                        "description": "Process session ID returned when the process was started (for all actions but list)."
+                    },
+                    "stream": {
+                        "type": "string",
+                        "enum": ["stdout", "stderr"],
+                        "description": "Output stream to read (for log, default stdout)."
+                    },
+                    "offset": {
+                        "type": "integer",
+                        "description": "Byte offset to read from (for log, default 0). Pass the previous next_offset to read new output."
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum bytes to read (for log, default 65536)."
+                    },
+                    "data": {
+                        "type": "string",
+                        "description": "Text to write to stdin (for write)."
+                    },
+                    "eof": {
+                        "type": "boolean",
+                        "description": "Close stdin after writing (for write, default false)."
                     },
                     "signal": {
                         "type": "string",
@@ -94,10 +480,7 @@ impl ProcessTool {
                 "required": ["action"]
             }),
         };
-        Self {
-            _ctx: ctx,
-            definition,
-        }
+        Self { ctx, definition }
     }
 }
 
@@ -126,54 +509,80 @@ impl AgentTool for ProcessTool {
             .get("action")
             .and_then(|v| v.as_str())
             .ok_or("Missing required parameter: action")?;
+        let session_id = || {
+            params
+                .get("session_id")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| format!("Missing required parameter: session_id for {action}"))
+        };
+        let processes = &self.ctx.processes;
+        let not_found = |id: &str| format!("No background process {id}");
 
         let result_text = match action {
-            "list" => {
-                // List currently tracked background processes
+            "list" => json!({ "processes": processes.list().await }).to_string(),
+            "poll" => {
+                let id = session_id()?;
+                let poll = processes.poll(id).await.ok_or_else(|| not_found(id))?;
+                serde_json::to_string(&poll)?
+            }
+            "log" => {
+                let id = session_id()?;
+                let stream = match params.get("stream").and_then(|v| v.as_str()) {
+                    None | Some("stdout") => OutputStream::Stdout,
+                    Some("stderr") => OutputStream::Stderr,
+                    Some(other) => return Err(format!("Unknown stream: {other}").into()),
+                };
+                let offset = params.get("offset").and_then(|v| v.as_u64()).unwrap_or(0);
+                let limit = params
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map(|l| l as usize)
+                    .unwrap_or(DEFAULT_LOG_LIMIT);
+                let chunk = processes
+                    .log(id, stream, offset, limit)
+                    .await
+                    .ok_or_else(|| not_found(id))?;
+                serde_json::to_string(&chunk)?
+            }
+            "write" => {
+                let id = session_id()?;
+                let data = params
+                    .get("data")
+                    .and_then(|v| v.as_str())
+                    .ok_or("Missing required parameter: data for write")?;
+                let eof = params.get("eof").and_then(|v| v.as_bool()).unwrap_or(false);
+                processes.write(id, data, eof).await?;
                 json!({
-                    "processes": [],
-                    "note": "Background process tracking is managed per-session."
+                    "action": "write",
+                    "session_id": id,
+                    "bytes": data.len(),
+                    "stdin": if eof { "closed" } else { "open" }
                 })
                 .to_string()
             }
             "kill" => {
-                let session_id = params
-                    .get("session_id")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing required parameter: session_id for kill")?;
+                let id = session_id()?;
                 let signal = params
                     .get("signal")
                     .and_then(|v| v.as_str())
                     .unwrap_or("SIGTERM");
+                processes.kill(id, parse_signal(signal)?).await?;
                 json!({
                     "action": "kill",
-                    "session_id": session_id,
+                    "session_id": id,
                     "signal": signal,
                     "status": "signal_sent"
                 })
                 .to_string()
             }
-            "poll" => {
-                let session_id = params
-                    .get("session_id")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing required parameter: session_id for poll")?;
-                json!({
-                    "action": "poll",
-                    "session_id": session_id,
-                    "status": "not_found"
-                })
-                .to_string()
-            }
             "remove" => {
-                let session_id = params
-                    .get("session_id")
-                    .and_then(|v| v.as_str())
-                    .ok_or("Missing required parameter: session_id for remove")?;
+                let id = session_id()?;
+                let entry = processes.remove(id).await.ok_or_else(|| not_found(id))?;
                 json!({
                     "action": "remove",
-                    "session_id": session_id,
-                    "status": "removed"
+                    "session_id": id,
+                    "status": "removed",
+                    "killed": entry.status == ProcessStatus::Running
                 })
                 .to_string()
             }
@@ -189,3 +598,143 @@ impl AgentTool for ProcessTool {
         })
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    async fn wait_for_exit(registry: &BackgroundProcessRegistry, id: &str) -> ProcessStatus {
+        for _ in 0..100 {
+            let status = registry.poll(id).await.unwrap().entry.status;
+            if status != ProcessStatus::Running {
+                return status;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        panic!("process {id} did not exit");
+    }
+
+    #[test]
+    fn test_output_buffer_drops_oldest() {
+        let mut buffer = OutputBuffer::default();
+        buffer.push(&vec![b'a'; MAX_BUFFERED_BYTES]);
+        buffer.push(b"bcd");
+        assert_eq!(buffer.end(), MAX_BUFFERED_BYTES as u64 + 3);
+        assert_eq!(buffer.tail(3), "bcd");
+
+        let chunk = buffer.read(0, 2);
+        assert_eq!(chunk.offset, 3);
+        assert_eq!(chunk.dropped, 3);
+        assert_eq!(chunk.data, "aa");
+        let chunk = buffer.read(buffer.end() - 1, 10);
+        assert_eq!(chunk.data, "d");
+        assert_eq!(chunk.next_offset, buffer.end());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_write_and_log() {
+        let registry = BackgroundProcessRegistry::new();
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c")
+            .arg("echo hello; read line; echo \"got $line\"; echo oops >&2; exit 3");
+        let entry = registry.spawn(&mut cmd, "script").await.unwrap();
+        assert_eq!(entry.status, ProcessStatus::Running);
+        assert_eq!(registry.list().await.len(), 1);
+
+        registry
+            .write(&entry.session_id, "x\n", true)
+            .await
+            .unwrap();
+        assert_eq!(
+            wait_for_exit(&registry, &entry.session_id).await,
+            ProcessStatus::Exited(3)
+        );
+        // Output is captured concurrently with the exit
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let stdout = registry
+            .log(&entry.session_id, OutputStream::Stdout, 0, 100)
+            .await
+            .unwrap();
+        assert_eq!(stdout.data, "hello\ngot x\n");
+        let rest = registry
+            .log(&entry.session_id, OutputStream::Stdout, 6, 100)
+            .await
+            .unwrap();
+        assert_eq!(rest.data, "got x\n");
+        let poll = registry.poll(&entry.session_id).await.unwrap();
+        assert_eq!(poll.stderr_tail, "oops\n");
+
+        // Signalling an exited process group is a no-op
+        registry
+            .kill(&entry.session_id, libc::SIGTERM)
+            .await
+            .unwrap();
+        assert!(registry.remove(&entry.session_id).await.is_some());
+        assert!(registry.poll(&entry.session_id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_kill_signals_process() {
+        let registry = BackgroundProcessRegistry::new();
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg("sleep 30");
+        let entry = registry.spawn(&mut cmd, "sleep 30").await.unwrap();
+
+        let signal = parse_signal("term").unwrap();
+        registry.kill(&entry.session_id, signal).await.unwrap();
+        assert_eq!(
+            wait_for_exit(&registry, &entry.session_id).await,
+            ProcessStatus::Signaled(libc::SIGTERM)
+        );
+        assert!(parse_signal("SIGBOGUS").is_err());
+        assert!(registry.kill("proc-99", signal).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_kills_orphaned_children() {
+        let registry = BackgroundProcessRegistry::new();
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.arg("-c").arg("sleep 100 & exit");
+        let entry = registry.spawn(&mut cmd, "sleep 100 &").await.unwrap();
+        assert_eq!(
+            wait_for_exit(&registry, &entry.session_id).await,
+            ProcessStatus::Exited(0)
+        );
+
+        let group = -(entry.pid as libc::pid_t);
+        // SAFETY: kill has no memory safety requirements.
+        assert_eq!(unsafe { libc::kill(group, 0) }, 0, "sleep should still run");
+        let process = registry.get(&entry.session_id).await.unwrap();
+        assert!(!process.group_drained.load(Ordering::Acquire));
+        registry.remove(&entry.session_id).await.unwrap();
+        for _ in 0..100 {
+            // SAFETY: as above.
+            if unsafe { libc::kill(group, 0) } != 0 {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        panic!("process group {} survived remove", entry.pid);
+    }
+
+    #[tokio::test]
+    async fn test_drained_group_is_not_signalled() {
+        let registry = BackgroundProcessRegistry::new();
+        let mut cmd = tokio::process::Command::new("true");
+        let entry = registry.spawn(&mut cmd, "true").await.unwrap();
+        wait_for_exit(&registry, &entry.session_id).await;
+
+        let process = registry.get(&entry.session_id).await.unwrap();
+        for _ in 0..100 {
+            if process.group_drained.load(Ordering::Acquire) {
+                // The pid may have been reused by now; it must not be signalled
+                assert!(process.signal(KILL_SIGNAL).is_ok());
+                assert!(send_signal(0, KILL_SIGNAL).is_err());
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        panic!("process group {} was not marked drained", entry.pid);
+    }
+}
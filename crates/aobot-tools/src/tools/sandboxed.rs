@@ -0,0 +1,132 @@
+//! Sandbox enforcement for the base file and shell tools.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use pi_agent_core::agent_types::{AgentTool, AgentToolResult};
+use pi_agent_core::types::Tool;
+
+use crate::sandbox::{FileAccess, SandboxBackend, SandboxMode, SandboxPolicy};
+use crate::tools::exec::ExecTool;
+use crate::tools::process::BackgroundProcessRegistry;
+
+/// Parameters that name a file or directory in the file tools.
+const PATH_PARAMS: &[&str] = &["path", "file_path", "dir", "directory", "cwd"];
+
+/// Gateway tools kept as-is under a sandbox: they don't touch the host
+/// filesystem or run commands, or (`exec`, `process`) enforce the policy
+/// themselves.
+const SANDBOX_SAFE_TOOLS: &[&str] = &[
+    "sessions_list",
+    "sessions_history",
+    "sessions_send",
+    "sessions_spawn",
+    "session_status",
+    "agents_list",
+    "gateway",
+    "message",
+    "memory_search",
+    "memory_get",
+    "memory_write",
+    "memory_forget",
+    "process",
+    "exec",
+    "tts",
+    "cron",
+];
+
+/// Apply an agent's sandbox policy to its tools.
+///
+/// In `read-only` mode `write` and `edit` are removed. File tools are
+/// wrapped so their paths are checked against the policy, and `bash` is
+/// replaced by a shell tool that runs commands under the policy's write
+/// restrictions and on its backend, tracking background commands in
+/// `processes`. Any other tool (e.g. from an MCP server) is removed, since
+/// the sandbox can't confine it. With no sandbox the tools are returned
+/// unchanged.
+pub fn apply_sandbox(
+    tools: Vec<Arc<dyn AgentTool>>,
+    policy: &Arc<SandboxPolicy>,
+    processes: &Arc<BackgroundProcessRegistry>,
+) -> Vec<Arc<dyn AgentTool>> {
+    let mode = policy.mode();
+    if mode == SandboxMode::None && policy.backend() == SandboxBackend::Host {
+        return tools;
+    }
+    tools
+        .into_iter()
+        .filter_map(|tool| -> Option<Arc<dyn AgentTool>> {
+            match tool.name() {
+                "write" | "edit" if mode == SandboxMode::ReadOnly => None,
+                "write" | "edit" => Some(SandboxedTool::wrap(tool, policy, FileAccess::Write)),
+                "read" | "grep" | "find" | "ls" | "image" => {
+                    Some(SandboxedTool::wrap(tool, policy, FileAccess::Read))
+                }
+                "bash" => Some(Arc::new(ExecTool::bash(policy.clone(), processes.clone()))),
+                name if SANDBOX_SAFE_TOOLS.contains(&name) => Some(tool),
+                name => {
+                    tracing::warn!(tool = name, "Removing tool the sandbox can't confine");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// A file tool whose path parameters are checked (and resolved) against a
+/// sandbox policy before the call is forwarded.
+pub struct SandboxedTool {
+    inner: Arc<dyn AgentTool>,
+    policy: Arc<SandboxPolicy>,
+    access: FileAccess,
+}
+
+impl SandboxedTool {
+    pub fn wrap(
+        inner: Arc<dyn AgentTool>,
+        policy: &Arc<SandboxPolicy>,
+        access: FileAccess,
+    ) -> Arc<dyn AgentTool> {
+        Arc::new(Self {
+            inner,
+            policy: policy.clone(),
+            access,
+        })
+    }
+}
+
+#[async_trait]
+impl AgentTool for SandboxedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn label(&self) -> &str {
+        self.inner.label()
+    }
+
+    fn definition(&self) -> &Tool {
+        self.inner.definition()
+    }
+
+    async fn execute(
+        &self,
+        tool_call_id: &str,
+        mut params: Value,
+        cancel: CancellationToken,
+        on_update: Option<Box<dyn Fn(AgentToolResult) + Send + Sync>>,
+    ) -> Result<AgentToolResult, Box<dyn std::error::Error + Send + Sync>> {
+        for key in PATH_PARAMS {
+            if let Some(path) = params.get(*key).and_then(|v| v.as_str()) {
+                let resolved = self.policy.check_path(path, self.access)?;
+                params[*key] = Value::String(resolved.to_string_lossy().to_string());
+            }
+        }
+        self.inner
+            .execute(tool_call_id, params, cancel, on_update)
+            .await
+    }
+}
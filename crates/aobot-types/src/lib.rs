@@ -73,12 +73,46 @@ pub struct SandboxConfig {
     /// Allowed directories for file operations.
     #[serde(default)]
     pub allowed_dirs: Vec<String>,
+    /// Process isolation backend for shell commands: "host" (run as a plain
+    /// child process) or "linux" (user/mount namespaces, seccomp, resource
+    /// limits and a private `/tmp`).
+    #[serde(default = "default_sandbox_backend")]
+    pub backend: String,
+    /// Allow network access from isolated commands. When false, the "linux"
+    /// backend runs commands in an empty network namespace.
+    #[serde(default = "default_true")]
+    pub network: bool,
+    /// Resource limits for isolated commands.
+    #[serde(default)]
+    pub limits: SandboxLimits,
 }
 
 fn default_sandbox_mode() -> String {
     "none".to_string()
 }
 
+fn default_sandbox_backend() -> String {
+    "host".to_string()
+}
+
+/// Resource limits applied by the "linux" sandbox backend. Unset limits
+/// use the backend defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SandboxLimits {
+    /// CPU time per command, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+    /// Address space per process, in MiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// Maximum number of processes for the sandboxed user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_processes: Option<u64>,
+    /// Size of the private `/tmp`, in MiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmp_mb: Option<u64>,
+}
+
 // ──────────────────── Agent Types ────────────────────
 
 /// Helper enum for deserializing `tools` field which can be